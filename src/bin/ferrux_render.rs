@@ -0,0 +1,92 @@
+//! Renders a drawing script to an image file, without any window
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use ferrux_canvas::color::Color;
+use ferrux_canvas::script::Script;
+
+const USAGE: &str = "\
+Usage: ferrux-render <SCRIPT> [OPTIONS]
+
+Renders a drawing script written in JSON (.json) or RON (.ron) to an image file
+
+Options:
+  -o, --output <FILE>         Image to write, in the format of its extension. By default, a PNG
+                              with the name of the script
+      --width <PIXELS>        Width of the canvas, replacing the one of the script
+      --height <PIXELS>       Height of the canvas, replacing the one of the script
+      --background <COLOR>    Background CSS color, replacing the one of the script
+      --help                  Prints this message";
+
+/// Options of the command line
+#[derive(Debug, Default)]
+struct Options {
+	script: PathBuf,
+	output: Option<PathBuf>,
+	width: Option<u32>,
+	height: Option<u32>,
+	background: Option<Color>,
+}
+
+/// Parses the arguments of the command line, without the name of the binary. Returns none if the
+/// help is requested.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+	let mut options = Options::default();
+	let mut script = None;
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or_else(|| format!("missing value of {}", arg));
+		match arg.as_str() {
+			"--help" => return Ok(None),
+			"-o" | "--output" => options.output = Some(PathBuf::from(value()?)),
+			"--width" => options.width = Some(size(&value()?)?),
+			"--height" => options.height = Some(size(&value()?)?),
+			"--background" => {
+				let color = value()?;
+				options.background = Some(Color::parse(&color).map_err(|e| format!("{}: {}", color, e))?);
+			}
+			option if option.starts_with('-') => return Err(format!("unknown option {}", option)),
+			_ if script.is_some() => return Err(format!("unexpected argument {}", arg)),
+			_ => script = Some(PathBuf::from(arg)),
+		}
+	}
+	options.script = script.ok_or("missing script")?;
+	Ok(Some(options))
+}
+
+/// Parses a size of the canvas
+fn size(value: &str) -> Result<u32, String> {
+	value.parse().map_err(|_| format!("invalid size {}", value))
+}
+
+fn run(options: Options) -> Result<PathBuf, Box<dyn std::error::Error>> {
+	let mut script = Script::open(&options.script)?;
+	script.width = options.width.unwrap_or(script.width);
+	script.height = options.height.unwrap_or(script.height);
+	script.background = options.background.unwrap_or(script.background);
+	let output = options.output.unwrap_or_else(|| options.script.with_extension("png"));
+	script.render()?.save(&output)?;
+	Ok(output)
+}
+
+fn main() -> ExitCode {
+	match parse_args(std::env::args().skip(1)) {
+		Ok(None) => {
+			println!("{}", USAGE);
+			ExitCode::SUCCESS
+		}
+		Ok(Some(options)) => match run(options) {
+			Ok(output) => {
+				println!("{}", output.display());
+				ExitCode::SUCCESS
+			}
+			Err(e) => {
+				eprintln!("{}", e);
+				ExitCode::FAILURE
+			}
+		},
+		Err(e) => {
+			eprintln!("{}\n\n{}", e, USAGE);
+			ExitCode::from(2)
+		}
+	}
+}
@@ -10,6 +10,10 @@ pub enum CanvasError {
 	AdapterNotFound,
 	/// Error triggered during a render
 	Rendering,
+	/// Error triggered saving a rendered frame in a [`Recorder`]
+	///
+	/// [`Recorder`]: crate::record::Recorder
+	Recording,
 }
 
 impl CanvasError {
@@ -17,6 +21,7 @@ impl CanvasError {
 		match self {
 			Self::AdapterNotFound => "GPU adapter not found",
 			Self::Rendering => "Rendering has failed",
+			Self::Recording => "Recording the frame has failed",
 		}
 	}
 }
@@ -1,6 +1,10 @@
 use crate::canvas::canvas_error::CanvasError;
 use crate::canvas::Point;
 use crate::color::Color;
+use crate::font;
+use crate::font::{DefaultFont, Font, TextLayout};
+use crate::image;
+use crate::image::{Filter, Flip, Image, Rect, Transform};
 
 /// Entity managing the screen rendering and providing the tools to perform the drawing of the
 /// the figures and invoke the frame rendering.
@@ -60,6 +64,29 @@ pub trait Canvas {
 	///
 	fn draw_pixel(&mut self, x: u32, y: u32, color: Color);
 
+	/// Draws a single pixel on the buffer mixing the color with the current one of the pixel
+	/// according to its alpha, see [Color::blend]. By default the color is drawn as it is, the
+	/// canvases able to read back their buffer replace it to blend both colors.
+	///
+	/// # Arguments
+	/// * `x`, `y` - Pixel coordinates of the point
+	/// * `color` - Color to blend with the pixel
+	///
+	/// # Example
+	/// The pixel (100, 100) will be drawn purple on the screen with the next render call.
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// canvas.draw_pixel(100, 100, palette::BLUE);
+	/// canvas.blend_pixel(100, 100, Color { r: 255, g: 0, b: 0, a: 128 });
+	/// ```
+	///
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.draw_pixel(x, y, color);
+	}
+
 	/// Draws a line between the two specified points in the canvas
 	///
 	/// # Arguments
@@ -116,6 +143,170 @@ pub trait Canvas {
 	///
 	fn fill_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color);
 
+	/// Draws the image with its top-left corner in the destination point. The image is clipped at
+	/// the edges of the canvas, its translucent pixels are blended with the canvas using
+	/// [Canvas::blend_pixel] and the fully transparent ones are skipped.
+	///
+	/// # Arguments
+	/// * `dest` - Point of the canvas where the top-left corner of the image is placed
+	/// * `image` - Image to draw
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// let sprite = Image::filled(16, 16, palette::RED);
+	/// canvas.draw_image((100, 100), &sprite);
+	/// ```
+	///
+	fn draw_image(&mut self, dest: Point, image: &Image) {
+		image::draw(self, dest, image, image.bounds(), Flip::None);
+	}
+
+	/// Draws a region of the image, optionally flipped, with its top-left corner in the destination
+	/// point. Like [Canvas::draw_image], the image is clipped at the edges of the canvas and its
+	/// alpha is respected. It's useful to draw a single sprite of a sprite sheet.
+	///
+	/// # Arguments
+	/// * `dest` - Point of the canvas where the top-left corner of the region is placed
+	/// * `image` - Image to draw
+	/// * `source` - Region of the image to draw, clipped to the bounds of the image
+	/// * `flip` - Mirroring to apply to the region
+	///
+	/// # Example
+	/// The following example draws the second 16x16 sprite of a sheet, looking to the left.
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::{Flip, Image, Rect};
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// # let sheet = Image::filled(64, 16, palette::RED);
+	/// canvas.draw_image_region((100, 100), &sheet, Rect::new(16, 0, 16, 16), Flip::Horizontal);
+	/// ```
+	///
+	fn draw_image_region(&mut self, dest: Point, image: &Image, source: Rect, flip: Flip) {
+		image::draw(self, dest, image, source, flip);
+	}
+
+	/// Draws the image scaled and rotated around its pivot, which is placed in the destination
+	/// point. Like [Canvas::draw_image], the image is clipped at the edges of the canvas and its
+	/// alpha is respected.
+	///
+	/// # Arguments
+	/// * `dest` - Point of the canvas where the pivot of the image is placed
+	/// * `image` - Image to draw
+	/// * `transform` - Scaling, rotation and pivot to apply
+	/// * `filter` - Sampling method, [Filter::Nearest] for pixel art or [Filter::Bilinear] for
+	///   smooth results
+	///
+	/// # Example
+	/// The following example draws a sprite three times bigger, rotated 45 degrees around its
+	/// center, which is placed in the point (200, 200).
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::{Filter, Image, Transform};
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// let sprite = Image::filled(16, 16, palette::GREEN);
+	/// let transform = Transform::new()
+	///   .with_scale(3.0, 3.0)
+	///   .with_rotation(std::f32::consts::FRAC_PI_4)
+	///   .with_pivot(8.0, 8.0);
+	/// canvas.draw_image_transformed((200, 200), &sprite, &transform, Filter::Nearest);
+	/// ```
+	///
+	fn draw_image_transformed(&mut self, dest: Point, image: &Image, transform: &Transform,
+	                          filter: Filter) {
+		image::transform::draw(self, dest, image, transform, filter);
+	}
+
+	/// Draws the text using the built-in monospaced [`DefaultFont`]. Each new line character
+	/// (`\n`) starts a new line below the previous one. The space the text will take can be
+	/// known beforehand with [`measure_text`].
+	///
+	/// # Arguments
+	/// * `position` - Top-left point of the text
+	/// * `text` - Text to draw
+	/// * `color` - Color of the text
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// canvas.draw_text((10, 10), "FPS: 60\nObjects: 3", palette::WHITE);
+	/// ```
+	///
+	/// [`DefaultFont`]: crate::font::DefaultFont
+	/// [`measure_text`]: crate::font::measure_text
+	fn draw_text(&mut self, position: Point, text: &str, color: Color) {
+		font::draw(self, &DefaultFont, position, text, &TextLayout::default(), color);
+	}
+
+	/// Draws the text using the specified font, like a [`BitmapFont`] loaded from a file. It works
+	/// like [Canvas::draw_text], the space the text will take can be known with
+	/// [`measure_text_with_font`].
+	///
+	/// # Arguments
+	/// * `position` - Top-left point of the text
+	/// * `text` - Text to draw
+	/// * `font` - Font to use
+	/// * `color` - Color of the text
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::font::BitmapFont;
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// let font = BitmapFont::open("fonts/terminus.bdf").unwrap();
+	/// canvas.draw_text_with_font((10, 10), "Score: 100", &font, palette::WHITE);
+	/// ```
+	///
+	/// [`BitmapFont`]: crate::font::BitmapFont
+	/// [`measure_text_with_font`]: crate::font::measure_text_with_font
+	fn draw_text_with_font(&mut self, position: Point, text: &str, font: &dyn Font, color: Color) {
+		font::draw(self, font, position, text, &TextLayout::default(), color);
+	}
+
+	/// Draws the text using the specified font and laying it out in lines with the given options:
+	/// alignment and maximum width of the lines. The anti-aliased glyphs of a [`TrueTypeFont`] are
+	/// blended with the canvas using [Canvas::blend_pixel]. The space the text will take can be
+	/// known with [`measure_text_layout`].
+	///
+	/// # Arguments
+	/// * `position` - Top-left point of the text box
+	/// * `text` - Text to draw
+	/// * `font` - Font to use
+	/// * `layout` - Options to lay out the text
+	/// * `color` - Color of the text
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::font::{Alignment, TextLayout, TrueTypeFont};
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// let font = TrueTypeFont::from_bytes(std::fs::read("fonts/DejaVuSans.ttf").unwrap(), 18.0).unwrap();
+	/// let layout = TextLayout::new().with_alignment(Alignment::Right).with_max_width(200);
+	/// canvas.draw_text_layout((10, 10), "A long text wrapped in lines", &font, &layout, palette::WHITE);
+	/// ```
+	///
+	/// [`TrueTypeFont`]: crate::font::TrueTypeFont
+	/// [`measure_text_layout`]: crate::font::measure_text_layout
+	fn draw_text_layout(&mut self, position: Point, text: &str, font: &dyn Font, layout: &TextLayout,
+	                    color: Color) {
+		font::draw(self, font, position, text, layout, color);
+	}
+
 	/// Renders an empty frame. It mimics a call to [Canvas::render] after a [Canvas::reset_frame] but
 	/// it doesn't clear the buffer. Allowing to clear the screen without losing the current drawn
 	/// image
@@ -170,4 +361,25 @@ pub trait Canvas {
 	///
 	fn resize(&mut self, width: u32, height: u32);
 
-}
\ No newline at end of file
+}
+
+/// Canvas keeping its frame in memory as an [Image], which allows reading back the drawn pixels to
+/// save or record them
+pub trait RasterCanvas: Canvas {
+
+	/// Returns the current frame, with everything drawn since the last reset. It can be saved in
+	/// any of the formats supported by [Image::save].
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+	/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+	/// # use ferrux_canvas::color::palette;
+	/// let mut canvas = HeadlessCanvas::new(320, 240);
+	/// canvas.draw_line((100, 100), (300, 200), palette::WHITE);
+	/// assert_eq!(Some(&palette::WHITE), canvas.frame().pixel(300, 200));
+	/// ```
+	///
+	fn frame(&self) -> &Image;
+
+}
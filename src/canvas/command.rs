@@ -0,0 +1,337 @@
+//! Recording of the drawing operations to replay them later
+
+use std::sync::Arc;
+use crate::canvas::canvas_error::CanvasError;
+use crate::canvas::{Canvas, Point};
+use crate::color::Color;
+use crate::image::{Filter, Flip, Image, Rect, Transform};
+
+/// Drawing operation recorded by a [CommandBuffer], with the arguments of its [Canvas] method
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+	/// [Canvas::draw_pixel]
+	DrawPixel { x: u32, y: u32, color: Color },
+	/// [Canvas::blend_pixel]
+	BlendPixel { x: u32, y: u32, color: Color },
+	/// [Canvas::draw_line]
+	DrawLine { start: Point, end: Point, color: Color },
+	/// [Canvas::draw_triangle]
+	DrawTriangle { points: [Point; 3], color: Color },
+	/// [Canvas::fill_triangle]
+	FillTriangle { points: [Point; 3], color: Color },
+	/// [Canvas::draw_image_region], which also records [Canvas::draw_image]. The image is shared
+	/// by the clones of the command.
+	DrawImage { dest: Point, image: Arc<Image>, source: Rect, flip: Flip },
+	/// [Canvas::draw_image_transformed]. The image is shared by the clones of the command.
+	DrawImageTransformed { dest: Point, image: Arc<Image>, transform: Transform, filter: Filter },
+	/// [Canvas::draw_text]
+	DrawText { position: Point, text: String, color: Color },
+	/// [Canvas::clear_frame]
+	ClearFrame,
+	/// [Canvas::reset_frame]
+	ResetFrame,
+	/// [Canvas::resize]
+	Resize { width: u32, height: u32 },
+}
+
+impl Command {
+
+	/// Executes the command on the canvas
+	///
+	/// # Errors
+	/// Any error of the canvas clearing its frame
+	///
+	pub fn apply<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), CanvasError> {
+		match self {
+			Command::DrawPixel { x, y, color } => canvas.draw_pixel(*x, *y, *color),
+			Command::BlendPixel { x, y, color } => canvas.blend_pixel(*x, *y, *color),
+			Command::DrawLine { start, end, color } => canvas.draw_line(*start, *end, *color),
+			Command::DrawTriangle { points: [a, b, c], color } => canvas.draw_triangle(*a, *b, *c, *color),
+			Command::FillTriangle { points: [a, b, c], color } => canvas.fill_triangle(*a, *b, *c, *color),
+			Command::DrawImage { dest, image, source, flip } => canvas.draw_image_region(*dest, image, *source, *flip),
+			Command::DrawImageTransformed { dest, image, transform, filter } => {
+				canvas.draw_image_transformed(*dest, image, transform, *filter)
+			}
+			Command::DrawText { position, text, color } => canvas.draw_text(*position, text, *color),
+			Command::ClearFrame => canvas.clear_frame()?,
+			Command::ResetFrame => canvas.reset_frame(),
+			Command::Resize { width, height } => canvas.resize(*width, *height),
+		}
+		Ok(())
+	}
+
+}
+
+/// Canvas recording each drawing operation into a list of [Command]s instead of drawing pixels.
+/// The list can be replayed onto any other canvas, cloned, concatenated and inspected.
+///
+/// A buffer can be filled in any thread and sent to the one owning the window, so the generation
+/// of the scenes doesn't block the rendering. Rendering the buffer does nothing, the canvas it's
+/// replayed on is the one to render. Text drawn with a custom font is recorded pixel by pixel.
+///
+/// # Example
+/// ```no_run
+/// # use std::sync::mpsc;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::command::CommandBuffer;
+/// # use ferrux_canvas::color::palette;
+/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+/// let (sender, receiver) = mpsc::channel();
+/// std::thread::spawn(move || {
+///   for x in 1..100 {
+///     let mut scene = CommandBuffer::new(640, 480);
+///     scene.reset_frame();
+///     scene.draw_triangle((100, 100 - x), (100 - x, 100), (200 - x, 200 - x), palette::WHITE);
+///     sender.send(scene).unwrap();
+///   }
+/// });
+/// for scene in receiver {
+///   scene.replay(&mut canvas).unwrap();
+///   canvas.render().unwrap();
+/// }
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandBuffer {
+	commands: Vec<Command>,
+	width: u32,
+	height: u32,
+}
+
+impl CommandBuffer {
+
+	/// Returns an empty buffer reporting the given size as its canvas size, which should usually
+	/// be the size of the canvas it will be replayed on
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			commands: Vec::new(),
+			width,
+			height,
+		}
+	}
+
+	/// Returns the recorded commands, in the order they were drawn
+	pub fn commands(&self) -> &[Command] {
+		&self.commands
+	}
+
+	/// Returns the number of recorded commands
+	pub fn len(&self) -> usize {
+		self.commands.len()
+	}
+
+	/// Returns if no command has been recorded
+	pub fn is_empty(&self) -> bool {
+		self.commands.is_empty()
+	}
+
+	/// Records a command after the previous ones. Recording a [Command::Resize] also changes the
+	/// size of the buffer.
+	pub fn push(&mut self, command: Command) {
+		if let Command::Resize { width, height } = command {
+			self.width = width;
+			self.height = height;
+		}
+		self.commands.push(command);
+	}
+
+	/// Removes all the recorded commands, keeping the size
+	pub fn clear(&mut self) {
+		self.commands.clear();
+	}
+
+	/// Executes all the recorded commands on the canvas, in order
+	///
+	/// # Errors
+	/// Any error of the canvas clearing its frame, which stops the replay
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+	/// # use ferrux_canvas::canvas::command::CommandBuffer;
+	/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+	/// # use ferrux_canvas::color::palette;
+	/// let mut buffer = CommandBuffer::new(16, 16);
+	/// buffer.draw_line((0, 0), (15, 15), palette::RED);
+	/// let mut canvas = HeadlessCanvas::new(16, 16);
+	/// buffer.replay(&mut canvas).unwrap();
+	/// assert_eq!(Some(&palette::RED), canvas.frame().pixel(8, 8));
+	/// ```
+	///
+	pub fn replay<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), CanvasError> {
+		self.commands.iter().try_for_each(|command| command.apply(canvas))
+	}
+
+}
+
+impl Extend<Command> for CommandBuffer {
+
+	/// Records the commands after the previous ones, like [CommandBuffer::push]. Extending a buffer
+	/// with another one concatenates them.
+	fn extend<T: IntoIterator<Item = Command>>(&mut self, commands: T) {
+		commands.into_iter().for_each(|command| self.push(command));
+	}
+
+}
+
+impl IntoIterator for CommandBuffer {
+	type Item = Command;
+	type IntoIter = std::vec::IntoIter<Command>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.commands.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a CommandBuffer {
+	type Item = &'a Command;
+	type IntoIter = std::slice::Iter<'a, Command>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.commands.iter()
+	}
+}
+
+impl Canvas for CommandBuffer {
+
+	fn width(&self) -> u32 {
+		self.width
+	}
+
+	fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// The buffer isn't shown anywhere, so it does nothing and isn't recorded. The canvas the
+	/// buffer is replayed on should be rendered instead.
+	fn render(&mut self) -> Result<(), CanvasError> {
+		Ok(())
+	}
+
+	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.push(Command::DrawPixel { x, y, color });
+	}
+
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.push(Command::BlendPixel { x, y, color });
+	}
+
+	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+		self.push(Command::DrawLine { start, end, color });
+	}
+
+	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.push(Command::DrawTriangle { points: [point_a, point_b, point_c], color });
+	}
+
+	fn fill_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.push(Command::FillTriangle { points: [point_a, point_b, point_c], color });
+	}
+
+	/// Records the image as a [Command::DrawImage] of its whole area
+	fn draw_image(&mut self, dest: Point, image: &Image) {
+		self.draw_image_region(dest, image, image.bounds(), Flip::None);
+	}
+
+	fn draw_image_region(&mut self, dest: Point, image: &Image, source: Rect, flip: Flip) {
+		self.push(Command::DrawImage { dest, image: Arc::new(image.clone()), source, flip });
+	}
+
+	fn draw_image_transformed(&mut self, dest: Point, image: &Image, transform: &Transform,
+	                          filter: Filter) {
+		self.push(Command::DrawImageTransformed { dest, image: Arc::new(image.clone()), transform: *transform, filter });
+	}
+
+	fn draw_text(&mut self, position: Point, text: &str, color: Color) {
+		self.push(Command::DrawText { position, text: text.to_owned(), color });
+	}
+
+	/// Records the clearing, it's only executed when the buffer is replayed
+	fn clear_frame(&mut self) -> Result<(), CanvasError> {
+		self.push(Command::ClearFrame);
+		Ok(())
+	}
+
+	fn reset_frame(&mut self) {
+		self.push(Command::ResetFrame);
+	}
+
+	fn resize(&mut self, width: u32, height: u32) {
+		self.push(Command::Resize { width, height });
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::{Canvas, RasterCanvas};
+	use crate::canvas::command::{Command, CommandBuffer};
+	use crate::canvas::headless::HeadlessCanvas;
+	use crate::color::palette;
+	use crate::image::{Flip, Image};
+
+	#[test]
+	fn test_send() {
+		fn assert_send<T: Send>() {}
+		assert_send::<CommandBuffer>();
+	}
+
+	#[test]
+	fn record_and_inspect() {
+		let mut buffer = CommandBuffer::new(8, 8);
+		buffer.reset_frame();
+		buffer.draw_line((0, 0), (7, 0), palette::RED);
+		buffer.fill_triangle((0, 0), (7, 0), (0, 7), palette::BLUE);
+		buffer.draw_image((1, 1), &Image::filled(2, 2, palette::GREEN));
+		buffer.render().unwrap();
+		buffer.resize(4, 4);
+
+		assert_eq!(5, buffer.len());
+		assert_eq!((4, 4), (buffer.width(), buffer.height()));
+		assert_eq!(Command::DrawLine { start: (0, 0), end: (7, 0), color: palette::RED }, buffer.commands()[1]);
+		assert!(matches!(&buffer.commands()[3], Command::DrawImage { dest: (1, 1), flip: Flip::None, source, .. }
+			if source.width == 2));
+		assert_eq!(1, buffer.into_iter().filter(|command| matches!(command, Command::FillTriangle { .. })).count());
+	}
+
+	#[test]
+	fn replay_like_drawing() {
+		let draw = |canvas: &mut dyn Canvas| {
+			canvas.fill_triangle((0, 0), (15, 0), (0, 15), palette::BLUE);
+			canvas.draw_triangle((2, 2), (12, 3), (4, 10), palette::WHITE);
+			canvas.blend_pixel(14, 14, palette::RED);
+			canvas.draw_image_region((10, 10), &Image::filled(4, 4, palette::GREEN), Image::new(2, 2).bounds(), Flip::Both);
+			canvas.draw_text((0, 4), "Hi", palette::RED);
+		};
+		let mut expected = HeadlessCanvas::new(16, 16);
+		draw(&mut expected);
+
+		let mut buffer = CommandBuffer::new(16, 16);
+		draw(&mut buffer);
+		let mut canvas = HeadlessCanvas::new(16, 16);
+		buffer.replay(&mut canvas).unwrap();
+		assert_eq!(expected.frame(), canvas.frame());
+	}
+
+	#[test]
+	fn clone_and_concatenate() {
+		let mut background = CommandBuffer::new(4, 4);
+		background.reset_frame();
+		background.draw_line((0, 0), (3, 0), palette::WHITE);
+		let mut scene = background.clone();
+		let mut foreground = CommandBuffer::new(4, 4);
+		foreground.draw_pixel(1, 0, palette::RED);
+		scene.extend(foreground);
+
+		assert_eq!(2, background.len());
+		assert_eq!(3, scene.len());
+		let mut canvas = HeadlessCanvas::new(4, 4);
+		canvas.draw_pixel(3, 3, palette::BLUE);
+		scene.replay(&mut canvas).unwrap();
+		assert_eq!(Some(&palette::BLACK), canvas.frame().pixel(3, 3));
+		assert_eq!(Some(&palette::RED), canvas.frame().pixel(1, 0));
+		assert_eq!(Some(&palette::WHITE), canvas.frame().pixel(2, 0));
+	}
+
+}
@@ -0,0 +1,191 @@
+//! Canvas drawing in memory, without any window
+
+use crate::canvas::canvas_error::CanvasError;
+use crate::canvas::{Canvas, Point, RasterCanvas};
+use crate::canvas::helpers;
+use crate::color::*;
+use crate::image::Image;
+
+/// Canvas drawing on an in-memory frame instead of a screen. It doesn't need a window or a GPU, so
+/// it can be used in servers, tests and command line tools, reading the result with
+/// [RasterCanvas::frame].
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::palette;
+/// let mut canvas = HeadlessCanvas::new(64, 64);
+/// canvas.fill_triangle((32, 0), (0, 63), (63, 63), palette::RED);
+/// canvas.render().unwrap();
+/// assert_eq!(Some(&palette::RED), canvas.frame().pixel(32, 32));
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct HeadlessCanvas {
+	canvas: Image,
+	blend_space: BlendSpace,
+	width: u32,
+	height: u32,
+}
+
+impl HeadlessCanvas {
+
+	/// Returns a new headless canvas of the given size with a black frame
+	///
+	/// # Panics
+	/// If the frame has more than [Image::MAX_PIXELS] pixels
+	///
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			canvas: Image::filled(width, height, palette::BLACK),
+			blend_space: BlendSpace::default(),
+			width,
+			height,
+		}
+	}
+
+	/// Returns a new headless canvas drawing over a copy of the image, which sets its size
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+	/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// let canvas = HeadlessCanvas::from_image(&Image::filled(32, 16, palette::WHITE));
+	/// assert_eq!((32, 16), (canvas.width(), canvas.height()));
+	/// assert_eq!(Some(&palette::WHITE), canvas.frame().pixel(0, 0));
+	/// ```
+	///
+	pub fn from_image(image: &Image) -> Self {
+		Self {
+			canvas: image.clone(),
+			blend_space: BlendSpace::default(),
+			width: image.width(),
+			height: image.height(),
+		}
+	}
+
+	/// Sets the [BlendSpace] where the translucent pixels are blended, sRGB by default
+	pub fn with_blend_space(mut self, blend_space: BlendSpace) -> Self {
+		self.blend_space = blend_space;
+		self
+	}
+
+}
+
+impl Canvas for HeadlessCanvas {
+
+	fn width(&self) -> u32 {
+		self.width
+	}
+
+	fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// There's no screen to render the frame, so it does nothing. The frame can be read at any
+	/// moment with [RasterCanvas::frame].
+	fn render(&mut self) -> Result<(), CanvasError> {
+		Ok(())
+	}
+
+	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.canvas.set_pixel(x, y, color);
+	}
+
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		if let Some(pixel) = self.canvas.pixel(x, y) {
+			let blended = self.blend_space.blend(&color, pixel);
+			self.canvas.set_pixel(x, y, blended);
+		}
+	}
+
+	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+		helpers::draw_line(self, start, end, color);
+	}
+
+	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.draw_line(point_a, point_b, color);
+		self.draw_line(point_b, point_c, color);
+		self.draw_line(point_c, point_a, color);
+	}
+
+	fn fill_triangle(&mut self, p1: Point, p2: Point, p3: Point, color: Color) {
+		helpers::fill_triangle(self, p1, p2, p3, color);
+	}
+
+	/// There's no screen to clear, so it does nothing
+	fn clear_frame(&mut self) -> Result<(), CanvasError> {
+		Ok(())
+	}
+
+	fn reset_frame(&mut self) {
+		self.canvas = Image::filled(self.width, self.height, palette::BLACK);
+	}
+
+	fn resize(&mut self, width: u32, height: u32) {
+		self.width = width;
+		self.height = height;
+		self.reset_frame();
+	}
+
+}
+
+impl RasterCanvas for HeadlessCanvas {
+
+	fn frame(&self) -> &Image {
+		&self.canvas
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::{Canvas, RasterCanvas};
+	use crate::canvas::headless::HeadlessCanvas;
+	use crate::color::{BlendSpace, Color, palette};
+
+	#[test]
+	fn draw_lines() {
+		let mut canvas = HeadlessCanvas::new(8, 8);
+		canvas.draw_line((1, 1), (1, 3), palette::RED);
+		canvas.draw_line((6, 0), (4, 0), palette::GREEN);
+		canvas.draw_line((0, 7), (2, 5), palette::BLUE);
+		canvas.draw_line((7, 7), (20, 7), palette::WHITE);
+		let count = |color| canvas.frame().pixels().iter().filter(|&pixel| *pixel == color).count();
+		assert_eq!((3, 3, 3, 1), (count(palette::RED), count(palette::GREEN), count(palette::BLUE),
+		                           count(palette::WHITE)));
+		assert_eq!(Some(&palette::BLUE), canvas.frame().pixel(1, 6));
+	}
+
+	#[test]
+	fn fill_triangles() {
+		let mut canvas = HeadlessCanvas::new(8, 8);
+		canvas.fill_triangle((0, 0), (7, 0), (0, 7), palette::WHITE);
+		assert_eq!(Some(&palette::WHITE), canvas.frame().pixel(3, 3));
+		assert_eq!(Some(&palette::BLACK), canvas.frame().pixel(6, 6));
+	}
+
+	#[test]
+	fn blend_and_reset() {
+		let mut canvas = HeadlessCanvas::new(2, 2);
+		canvas.blend_pixel(0, 0, Color { r: 255, g: 255, b: 255, a: 128 });
+		assert_eq!(Some(&Color { r: 128, g: 128, b: 128, a: 255 }), canvas.frame().pixel(0, 0));
+		canvas.resize(3, 1);
+		assert_eq!((3, 1), (canvas.frame().width(), canvas.frame().height()));
+		assert!(canvas.frame().pixels().iter().all(|pixel| *pixel == palette::BLACK));
+	}
+
+	#[test]
+	fn linear_blending() {
+		let mut canvas = HeadlessCanvas::new(2, 1).with_blend_space(BlendSpace::Linear);
+		canvas.blend_pixel(0, 0, Color { r: 255, g: 255, b: 255, a: 128 });
+		canvas.draw_pixel(1, 0, palette::RED);
+		canvas.blend_pixel(1, 0, Color { r: 0, g: 255, b: 0, a: 128 });
+		assert_eq!(Some(&Color { r: 188, g: 188, b: 188, a: 255 }), canvas.frame().pixel(0, 0));
+		assert_eq!(Some(&Color { r: 187, g: 188, b: 0, a: 255 }), canvas.frame().pixel(1, 0));
+	}
+
+}
@@ -1,4 +1,64 @@
-use crate::canvas::Point;
+use bresenham_zip::build_zip;
+use line_drawing::Bresenham;
+use crate::canvas::{Canvas, Point};
+use crate::color::Color;
+
+/// Draws a line between two points, choosing the simplest algorithm for its direction
+pub fn draw_line<C: Canvas + ?Sized>(canvas: &mut C, start: Point, end: Point, color: Color) {
+	match start {
+		(x, _) if x == end.0 => draw_vertical_line(canvas, start, end, color),
+		(_, y) if y == end.1 => draw_horizontal_line(canvas, start, end, color),
+		_ => draw_diagonal_line(canvas, start, end, color)
+	}
+}
+
+/// Draws an horizontal line between two points
+fn draw_horizontal_line<C: Canvas + ?Sized>(canvas: &mut C, start: Point, end: Point, color: Color) {
+	let y = start.1;
+	for x in if start.0 < end.0 { start.0..=end.0 } else { end.0..=start.0 } {
+		canvas.draw_pixel(x, y, color);
+	}
+}
+
+/// Draws a vertical line between two points
+fn draw_vertical_line<C: Canvas + ?Sized>(canvas: &mut C, start: Point, end: Point, color: Color) {
+	let x = start.0;
+	for y in if start.1 < end.1 { start.1..=end.1 } else { end.1..=start.1} {
+		canvas.draw_pixel(x, y, color);
+	}
+}
+
+/// Draws a diagonal line between two points using Bresenham's algorithm
+fn draw_diagonal_line<C: Canvas + ?Sized>(canvas: &mut C, start: Point, end: Point, color: Color) {
+	for (x, y) in Bresenham::new(as_signed(start),as_signed(end)) {
+		canvas.draw_pixel(x as u32, y as u32, color);
+	}
+}
+
+/// Fills the triangle made with the three passed points, splitting it in two flat triangles
+pub fn fill_triangle<C: Canvas + ?Sized>(canvas: &mut C, p1: Point, p2: Point, p3: Point, color: Color) {
+	let (p1, p2, p3) = sort_vectors(p1, p2, p3);
+	match p2 {
+		(_, y) if y == p1.1 => fill_flat_triangle(canvas, p3, p1, p2, color),
+		(_, y) if y == p3.1 => fill_flat_triangle(canvas, p1, p2, p3, color),
+		_ => {
+			let p4 = calculate_intersection(p3, p2, p1);
+			fill_flat_triangle(canvas, p1, p2, p4, color);
+			fill_flat_triangle(canvas, p3, p2, p4, color);
+		}
+	}
+}
+
+/// Fills the flat triangle (a triangle were two points share the same height) made with the three
+/// passed points using Bresenham
+fn fill_flat_triangle<C: Canvas + ?Sized>(canvas: &mut C, peak: Point, side_a: Point, side_b: Point,
+                                          color: Color) {
+	#![allow(unused_parens)]
+	let bresenham = build_zip!(2D:Y - (as_signed(peak)) -> (as_signed(side_a)), (as_signed(side_b)));
+	for (left, right) in bresenham.unwrap() {
+		canvas.draw_line(as_u32(left), as_u32(right), color);
+	}
+}
 
 /// Receives three points and returns them sorted by Y value.
 /// This is a method to ease the finding of the middle vector and both peaks when filling a triangle
@@ -0,0 +1,293 @@
+//! Canvas drawing palette indices instead of colors
+
+use std::ops::Range;
+use crate::canvas::canvas_error::CanvasError;
+use crate::canvas::{Canvas, Point, RasterCanvas};
+use crate::canvas::helpers;
+use crate::color::Color;
+use crate::color::palette::{self, Palette};
+use crate::image::Image;
+
+/// Maximum number of colors of the palette that can be indexed
+const MAX_COLORS: usize = u8::MAX as usize + 1;
+
+/// Canvas storing the index of a [Palette] color in each pixel, like the graphic modes of the
+/// retro systems. The colors drawn are replaced by the closest one of the palette, and the indices
+/// are resolved into colors when the canvas is rendered on the wrapped one. Changing a color of the
+/// palette recolors all the pixels with its index, without drawing them again.
+///
+/// Only the first 256 colors of the palette are used. The pixels with an index missing in the
+/// palette are drawn black.
+///
+/// # Example
+/// The following example draws a waterfall cycling three shades of blue each frame.
+/// ```no_run
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::indexed::IndexedCanvas;
+/// # use ferrux_canvas::color::Color;
+/// # use ferrux_canvas::color::palette::Palette;
+/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+/// # let canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+/// let mut palette = Palette::pico8();
+/// palette.push(Color { r: 0, g: 40, b: 120, a: 255 }, None);
+/// palette.push(Color { r: 0, g: 90, b: 200, a: 255 }, None);
+/// palette.push(Color { r: 120, g: 180, b: 255, a: 255 }, None);
+/// let mut canvas = IndexedCanvas::new(canvas, palette);
+/// for y in 0..100 {
+///   for x in 50..80 {
+///     canvas.draw_index(x, y, 16 + (y % 3) as u8);
+///   }
+/// }
+/// loop {
+///   canvas.cycle(16..19, 1);
+///   canvas.render().unwrap();
+/// }
+/// ```
+///
+pub struct IndexedCanvas<C: Canvas> {
+	canvas: C,
+	palette: Palette,
+	indices: Vec<u8>,
+	nearest: Option<(Color, u8)>,
+	width: u32,
+	height: u32,
+}
+
+impl<C: Canvas> IndexedCanvas<C> {
+
+	/// Returns a new indexed canvas of the size of the wrapped one, with all its pixels set to the
+	/// first color of the palette
+	///
+	/// # Arguments
+	/// * `canvas` - Canvas where the colors are rendered
+	/// * `palette` - Colors of the indices
+	///
+	pub fn new(canvas: C, palette: Palette) -> Self {
+		let (width, height) = (canvas.width(), canvas.height());
+		Self {
+			canvas,
+			palette,
+			indices: vec![0; width as usize * height as usize],
+			nearest: None,
+			width,
+			height,
+		}
+	}
+
+	/// Returns the palette of the canvas
+	pub fn palette(&self) -> &Palette {
+		&self.palette
+	}
+
+	/// Returns the palette of the canvas as mutable. The changes are shown in the next render.
+	pub fn palette_mut(&mut self) -> &mut Palette {
+		self.nearest = None;
+		&mut self.palette
+	}
+
+	/// Replaces the palette of the canvas, keeping the indices of the pixels
+	pub fn set_palette(&mut self, palette: Palette) {
+		self.nearest = None;
+		self.palette = palette;
+	}
+
+	/// Rotates the colors of the palette in the range of indices, see [Palette::cycle]
+	pub fn cycle(&mut self, range: Range<usize>, steps: isize) {
+		self.nearest = None;
+		self.palette.cycle(range, steps);
+	}
+
+	/// Returns the palette index of the pixel, if it's inside the canvas
+	pub fn index(&self, x: u32, y: u32) -> Option<u8> {
+		self.position(x, y).map(|i| self.indices[i])
+	}
+
+	/// Sets the palette index of the pixel, if it's inside the canvas
+	pub fn draw_index(&mut self, x: u32, y: u32, index: u8) {
+		if let Some(i) = self.position(x, y) {
+			self.indices[i] = index;
+		}
+	}
+
+	/// Returns the palette indices of all the pixels, row by row
+	pub fn indices(&self) -> &[u8] {
+		&self.indices
+	}
+
+	/// Returns the wrapped canvas
+	pub fn canvas(&self) -> &C {
+		&self.canvas
+	}
+
+	/// Returns the wrapped canvas, consuming the indexed one
+	pub fn into_inner(self) -> C {
+		self.canvas
+	}
+
+	/// Returns the position of the pixel in the indices, if it's inside the canvas
+	fn position(&self, x: u32, y: u32) -> Option<usize> {
+		(x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+	}
+
+	/// Returns the index of the color of the palette closest to the given one. The last search is
+	/// kept, as the shapes draw all their pixels with the same color.
+	fn nearest(&mut self, color: Color) -> u8 {
+		match &self.nearest {
+			Some((last, index)) if *last == color => *index,
+			_ => {
+				let colors = self.palette.colors();
+				let index = palette::nearest(&colors[..colors.len().min(MAX_COLORS)], &color).unwrap_or(0) as u8;
+				self.nearest = Some((color, index));
+				index
+			}
+		}
+	}
+
+	/// Returns the color of the palette index
+	fn resolve(&self, index: u8) -> Color {
+		self.palette.get(index as usize).copied().unwrap_or(palette::BLACK)
+	}
+
+}
+
+impl<C: Canvas> Canvas for IndexedCanvas<C> {
+
+	fn width(&self) -> u32 {
+		self.width
+	}
+
+	fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Resolves the colors of the indices with the current palette, draws them on the wrapped
+	/// canvas and renders it
+	///
+	/// # Errors
+	/// Any error rendering the wrapped canvas
+	///
+	fn render(&mut self) -> Result<(), CanvasError> {
+		let colors = (0..MAX_COLORS).map(|index| self.resolve(index as u8)).collect::<Vec<_>>();
+		for (i, index) in self.indices.iter().enumerate() {
+			let (x, y) = (i as u32 % self.width, i as u32 / self.width);
+			self.canvas.draw_pixel(x, y, colors[*index as usize]);
+		}
+		self.canvas.render()
+	}
+
+	/// Draws the index of the palette color closest to the given one
+	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
+		let index = self.nearest(color);
+		self.draw_index(x, y, index);
+	}
+
+	/// Blends the color with the one of the pixel in sRGB, and draws the index of the palette
+	/// color closest to the result
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		if let Some(index) = self.index(x, y) {
+			let blended = color.blend(&self.resolve(index));
+			self.draw_pixel(x, y, blended);
+		}
+	}
+
+	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+		helpers::draw_line(self, start, end, color);
+	}
+
+	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.draw_line(point_a, point_b, color);
+		self.draw_line(point_b, point_c, color);
+		self.draw_line(point_c, point_a, color);
+	}
+
+	fn fill_triangle(&mut self, p1: Point, p2: Point, p3: Point, color: Color) {
+		helpers::fill_triangle(self, p1, p2, p3, color);
+	}
+
+	fn clear_frame(&mut self) -> Result<(), CanvasError> {
+		self.canvas.clear_frame()
+	}
+
+	/// Sets all the pixels to the first color of the palette
+	fn reset_frame(&mut self) {
+		self.indices.fill(0);
+	}
+
+	fn resize(&mut self, width: u32, height: u32) {
+		self.canvas.resize(width, height);
+		self.width = width;
+		self.height = height;
+		self.indices = vec![0; width as usize * height as usize];
+	}
+
+}
+
+impl<C: RasterCanvas> RasterCanvas for IndexedCanvas<C> {
+
+	/// Returns the frame of the wrapped canvas, with the colors resolved in the last render
+	fn frame(&self) -> &Image {
+		self.canvas.frame()
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::{Canvas, RasterCanvas};
+	use crate::canvas::headless::HeadlessCanvas;
+	use crate::canvas::indexed::IndexedCanvas;
+	use crate::color::{Color, palette};
+	use crate::color::palette::Palette;
+
+	fn canvas() -> IndexedCanvas<HeadlessCanvas> {
+		IndexedCanvas::new(HeadlessCanvas::new(4, 4), Palette::cga())
+	}
+
+	#[test]
+	fn draw_indices() {
+		let mut canvas = canvas();
+		canvas.draw_pixel(0, 0, Color { r: 250, g: 250, b: 90, a: 255 });
+		canvas.draw_line((0, 3), (3, 3), Color { r: 0, g: 0, b: 160, a: 255 });
+		canvas.draw_index(1, 1, 4);
+		canvas.draw_index(9, 9, 4);
+		assert_eq!(Some(14), canvas.index(0, 0));
+		assert_eq!(Some(4), canvas.index(1, 1));
+		assert_eq!(None, canvas.index(4, 0));
+		assert_eq!(4, canvas.indices().iter().filter(|&&index| index == 1).count());
+		canvas.render().unwrap();
+		assert_eq!(Some(&Color { r: 255, g: 255, b: 85, a: 255 }), canvas.frame().pixel(0, 0));
+		assert_eq!(Some(&palette::BLACK), canvas.frame().pixel(3, 0));
+	}
+
+	#[test]
+	fn recolor_and_cycle() {
+		let mut canvas = canvas();
+		canvas.fill_triangle((0, 0), (3, 0), (0, 3), Color { r: 170, g: 0, b: 0, a: 255 });
+		*canvas.palette_mut().get_mut(4).unwrap() = palette::RED;
+		canvas.render().unwrap();
+		assert_eq!(Some(&palette::RED), canvas.frame().pixel(0, 0));
+		canvas.cycle(0..5, 1);
+		canvas.render().unwrap();
+		assert_eq!(Some(&Color { r: 0, g: 170, b: 170, a: 255 }), canvas.frame().pixel(0, 0));
+		assert_eq!(Some(&palette::RED), canvas.frame().pixel(3, 3));
+		canvas.set_palette(Palette::from_colors(vec![palette::WHITE]));
+		canvas.render().unwrap();
+		assert_eq!(Some(&palette::BLACK), canvas.frame().pixel(0, 0));
+		assert_eq!(Some(&palette::WHITE), canvas.frame().pixel(3, 3));
+	}
+
+	#[test]
+	fn blend_and_resize() {
+		let mut canvas = canvas();
+		canvas.draw_index(0, 0, 15);
+		canvas.blend_pixel(0, 0, Color { r: 0, g: 0, b: 0, a: 100 });
+		assert_eq!(Some(7), canvas.index(0, 0));
+		canvas.resize(2, 3);
+		assert_eq!(6, canvas.indices().len());
+		assert_eq!((2, 3), (canvas.canvas().width(), canvas.canvas().height()));
+		canvas.draw_index(1, 2, 9);
+		canvas.reset_frame();
+		assert!(canvas.indices().iter().all(|&index| index == 0));
+	}
+
+}
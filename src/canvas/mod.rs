@@ -1,7 +1,12 @@
 //! The Canvas trait and all its implementations
 
-pub use canvas_trait::Canvas;
+pub use canvas_trait::{Canvas, RasterCanvas};
 
+pub mod command;
+pub mod headless;
+pub mod indexed;
+pub mod svg;
+pub mod terminal;
 pub mod winit;
 pub mod canvas_error;
 mod canvas_trait;
@@ -0,0 +1,271 @@
+//! Canvas recording the drawing as an SVG document
+
+use std::fmt::Write as FmtWrite;
+use std::path::Path;
+use log::error;
+use crate::canvas::canvas_error::CanvasError;
+use crate::canvas::{Canvas, Point};
+use crate::color::*;
+use crate::image::graphics::base64;
+use crate::image::{Flip, Image, ImageFormat, Rect};
+
+/// Canvas translating the drawing operations into SVG elements instead of pixels, so the same
+/// drawing code exports resolution-independent diagrams. Each render builds the document with
+/// all the elements drawn since the last [Canvas::reset_frame].
+///
+/// The coordinates of the points are the centers of their pixels, so the lines and triangles are
+/// aligned with the ones drawn by the raster canvases. Images and their regions are embedded as
+/// PNG, and the text and transformed images are drawn pixel by pixel.
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::svg::SvgCanvas;
+/// # use ferrux_canvas::color::palette;
+/// let mut canvas = SvgCanvas::new(64, 64).with_background(palette::WHITE);
+/// canvas.fill_triangle((32, 0), (0, 63), (63, 63), palette::RED);
+/// canvas.render().unwrap();
+/// assert!(canvas.document().contains(r##"<polygon points="32.5,0.5 0.5,63.5 63.5,63.5""##));
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct SvgCanvas {
+	elements: Vec<String>,
+	document: String,
+	background: Color,
+	width: u32,
+	height: u32,
+}
+
+impl SvgCanvas {
+
+	/// Returns a new SVG canvas of the given size with a black background
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			elements: Vec::new(),
+			document: String::new(),
+			background: palette::BLACK,
+			width,
+			height,
+		}
+	}
+
+	/// Sets the color of the background, which is left out of the document if it's fully
+	/// transparent
+	pub fn with_background(mut self, background: Color) -> Self {
+		self.background = background;
+		self
+	}
+
+	/// Returns the document built in the last render, empty before rendering
+	pub fn document(&self) -> &str {
+		&self.document
+	}
+
+	/// Writes the document built in the last render to a file
+	///
+	/// # Errors
+	/// [std::io::Error] if the file can't be written
+	///
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+		std::fs::write(path, &self.document)
+	}
+
+	/// Adds an element embedding the image as PNG, with the extra attributes given
+	fn embed_image(&mut self, dest: Point, image: &Image, attributes: &str) {
+		let mut png = Vec::new();
+		if let Err(e) = image.encode(ImageFormat::Png, &mut png) {
+			error!("image.encode() failed: {:?}", e);
+			return;
+		}
+		let data = String::from_utf8(base64(&png)).unwrap_or_default();
+		self.elements.push(format!(r#"<image x="{}" y="{}" width="{}" height="{}"{} style="image-rendering:pixelated" href="data:image/png;base64,{}"/>"#,
+		                           dest.0, dest.1, image.width(), image.height(), attributes, data));
+	}
+
+}
+
+impl Canvas for SvgCanvas {
+
+	fn width(&self) -> u32 {
+		self.width
+	}
+
+	fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Builds the SVG document with the elements drawn, readable with [SvgCanvas::document]
+	fn render(&mut self) -> Result<(), CanvasError> {
+		let mut document = String::new();
+		let _ = writeln!(document, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+		                 self.width, self.height);
+		if self.background.a > 0 {
+			let _ = writeln!(document, r#"<rect width="{}" height="{}"{}/>"#, self.width, self.height,
+			                 paint("fill", &self.background));
+		}
+		for element in &self.elements {
+			document.push_str(element);
+			document.push('\n');
+		}
+		document.push_str("</svg>\n");
+		self.document = document;
+		Ok(())
+	}
+
+	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.elements.push(format!(r#"<rect x="{}" y="{}" width="1" height="1"{}/>"#, x, y, paint("fill", &color)));
+	}
+
+	/// Draws the pixel with the opacity of the color, the viewer of the document blends it with
+	/// the elements under it
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.draw_pixel(x, y, color);
+	}
+
+	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+		self.elements.push(format!(r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{} stroke-linecap="square"/>"#,
+		                           center(start.0), center(start.1), center(end.0), center(end.1),
+		                           paint("stroke", &color)));
+	}
+
+	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.elements.push(format!(r#"<polygon points="{}" fill="none"{}/>"#,
+		                           points(&[point_a, point_b, point_c]), paint("stroke", &color)));
+	}
+
+	/// Draws a filled polygon with an outline of the same color, covering the pixels of the edges
+	/// like the raster canvases
+	fn fill_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.elements.push(format!(r#"<polygon points="{}"{}{}/>"#, points(&[point_a, point_b, point_c]),
+		                           paint("fill", &color), paint("stroke", &color)));
+	}
+
+	/// Embeds the image as PNG, keeping its pixels sharp when the document is scaled
+	fn draw_image(&mut self, dest: Point, image: &Image) {
+		self.embed_image(dest, image, "");
+	}
+
+	/// Embeds the region of the image as PNG, mirroring it with a transform instead of moving its
+	/// pixels
+	fn draw_image_region(&mut self, dest: Point, image: &Image, source: Rect, flip: Flip) {
+		let region = image.sub_image(source);
+		if region.width() == 0 || region.height() == 0 {
+			return;
+		}
+		// Mirroring around the center of the image maps x to 2 * center - x
+		let (mirror_x, mirror_y) = (2 * dest.0 as u64 + region.width() as u64,
+		                            2 * dest.1 as u64 + region.height() as u64);
+		let transform = match flip {
+			Flip::None => String::new(),
+			Flip::Horizontal => format!(r#" transform="translate({} 0) scale(-1 1)""#, mirror_x),
+			Flip::Vertical => format!(r#" transform="translate(0 {}) scale(1 -1)""#, mirror_y),
+			Flip::Both => format!(r#" transform="translate({} {}) scale(-1 -1)""#, mirror_x, mirror_y),
+		};
+		self.embed_image(dest, &region, &transform);
+	}
+
+	/// There's no screen to clear, so it does nothing
+	fn clear_frame(&mut self) -> Result<(), CanvasError> {
+		Ok(())
+	}
+
+	/// Removes all the elements drawn
+	fn reset_frame(&mut self) {
+		self.elements.clear();
+	}
+
+	fn resize(&mut self, width: u32, height: u32) {
+		self.width = width;
+		self.height = height;
+		self.reset_frame();
+	}
+
+}
+
+/// Returns the coordinate of the center of a pixel
+fn center(coordinate: u32) -> f64 {
+	coordinate as f64 + 0.5
+}
+
+/// Returns the value of the `points` attribute of a polygon
+fn points(points: &[Point]) -> String {
+	points.iter()
+		.map(|point| format!("{},{}", center(point.0), center(point.1)))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Returns the attributes painting the fill or the stroke of an element with the color
+fn paint(attribute: &str, color: &Color) -> String {
+	let mut paint = format!(r##" {}="#{:02x}{:02x}{:02x}""##, attribute, color.r, color.g, color.b);
+	if color.a < u8::MAX {
+		let _ = write!(paint, r#" {}-opacity="{:.3}""#, attribute, color.a as f64 / 255.0);
+	}
+	paint
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::Canvas;
+	use crate::canvas::svg::SvgCanvas;
+	use crate::color::{Color, palette};
+	use crate::image::graphics::base64;
+	use crate::image::{Flip, Image, ImageFormat, Rect};
+
+	#[test]
+	fn record_elements() {
+		let mut canvas = SvgCanvas::new(16, 8);
+		canvas.draw_pixel(1, 2, palette::RED);
+		canvas.blend_pixel(3, 4, Color { r: 0, g: 0, b: 255, a: 128 });
+		canvas.draw_line((0, 0), (15, 7), palette::GREEN);
+		canvas.draw_triangle((0, 0), (4, 0), (0, 4), palette::WHITE);
+		canvas.fill_triangle((8, 0), (12, 0), (8, 4), palette::BLUE);
+		canvas.render().unwrap();
+		assert_eq!(concat!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="8" viewBox="0 0 16 8">"#, "\n",
+			r##"<rect width="16" height="8" fill="#000000"/>"##, "\n",
+			r##"<rect x="1" y="2" width="1" height="1" fill="#ff0000"/>"##, "\n",
+			r##"<rect x="3" y="4" width="1" height="1" fill="#0000ff" fill-opacity="0.502"/>"##, "\n",
+			r##"<line x1="0.5" y1="0.5" x2="15.5" y2="7.5" stroke="#00ff00" stroke-linecap="square"/>"##, "\n",
+			r##"<polygon points="0.5,0.5 4.5,0.5 0.5,4.5" fill="none" stroke="#ffffff"/>"##, "\n",
+			r##"<polygon points="8.5,0.5 12.5,0.5 8.5,4.5" fill="#0000ff" stroke="#0000ff"/>"##, "\n",
+			"</svg>\n"), canvas.document());
+	}
+
+	#[test]
+	fn reset_and_resize() {
+		let mut canvas = SvgCanvas::new(4, 4).with_background(Color { r: 0, g: 0, b: 0, a: 0 });
+		assert_eq!("", canvas.document());
+		canvas.draw_image((1, 1), &Image::filled(2, 2, palette::RED));
+		canvas.render().unwrap();
+		assert!(canvas.document().contains(r#"<image x="1" y="1" width="2" height="2""#));
+		assert!(canvas.document().contains(r#"href="data:image/png;base64,iVBORw0KGgo"#));
+		canvas.resize(8, 2);
+		canvas.render().unwrap();
+		assert_eq!(concat!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="2" viewBox="0 0 8 2">"#,
+		                   "\n</svg>\n"), canvas.document());
+	}
+
+	#[test]
+	fn draw_image_regions() {
+		let mut image = Image::filled(4, 4, palette::RED);
+		image.set_pixel(3, 3, palette::BLUE);
+		let mut canvas = SvgCanvas::new(16, 16);
+		canvas.draw_image_region((2, 1), &image, Rect::new(1, 1, 3, 2), Flip::None);
+		canvas.draw_image_region((2, 1), &image, Rect::new(1, 1, 3, 2), Flip::Horizontal);
+		canvas.draw_image_region((2, 1), &image, Rect::new(1, 1, 3, 2), Flip::Both);
+		canvas.draw_image_region((2, 1), &image, Rect::new(4, 4, 2, 2), Flip::Vertical);
+		canvas.render().unwrap();
+		let images = canvas.document().lines().filter(|line| line.starts_with("<image")).collect::<Vec<_>>();
+		assert_eq!(3, images.len());
+		assert!(images[0].starts_with(r#"<image x="2" y="1" width="3" height="2" style"#));
+		assert!(images[1].starts_with(r#"<image x="2" y="1" width="3" height="2" transform="translate(7 0) scale(-1 1)" style"#));
+		assert!(images[2].contains(r#" transform="translate(7 4) scale(-1 -1)" "#));
+
+		let mut png = Vec::new();
+		image.sub_image(Rect::new(1, 1, 3, 2)).encode(ImageFormat::Png, &mut png).unwrap();
+		assert!(images[0].contains(std::str::from_utf8(&base64(&png)).unwrap()));
+	}
+
+}
@@ -0,0 +1,462 @@
+//! Canvas drawing on a terminal with ANSI escape sequences
+
+use std::fmt::Write as FmtWrite;
+use std::io::Write;
+use log::error;
+use crate::canvas::canvas_error::CanvasError;
+use crate::canvas::{Canvas, Point, RasterCanvas};
+use crate::canvas::helpers;
+use crate::color::*;
+use crate::image::Image;
+
+/// Offsets of the dots of a Braille character in its block of 2x4 pixels, by bit of the character
+const BRAILLE_DOTS: [(u32, u32); 8] = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (0, 3), (1, 3)];
+
+/// Ordered dithering matrix of 4x4 pixels
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Way the pixels of a [TerminalCanvas] are drawn with characters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TerminalMode {
+	/// Each character cell shows two vertically stacked pixels with an upper half-block (`▀`),
+	/// keeping all their colors
+	#[default]
+	HalfBlocks,
+	/// Each character cell shows a block of 2x4 pixels with a Braille character, drawing a dot for
+	/// each bright pixel. It has four times the resolution of the half-blocks, but a single color
+	/// per cell.
+	Braille(BrailleOptions),
+}
+
+impl TerminalMode {
+
+	/// Returns the width and height of the pixel block of each character cell
+	fn cell_size(&self) -> (u32, u32) {
+		match self {
+			TerminalMode::HalfBlocks => (1, 2),
+			TerminalMode::Braille(_) => (2, 4),
+		}
+	}
+
+}
+
+/// Options of the Braille mode of a [TerminalCanvas]
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::canvas::terminal::{BrailleOptions, TerminalMode};
+/// let mode = TerminalMode::Braille(BrailleOptions::new().with_dithering(true).with_colors(true));
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BrailleOptions {
+	threshold: u8,
+	dithering: bool,
+	colored: bool,
+}
+
+impl BrailleOptions {
+
+	/// Returns the default options: pixels with a luma of at least 128 are dots, drawn with the
+	/// default color of the terminal
+	pub fn new() -> Self {
+		Self {
+			threshold: 128,
+			dithering: false,
+			colored: false,
+		}
+	}
+
+	/// Sets the minimum luma, from 0 to 255, of the pixels drawn as dots
+	pub fn with_threshold(mut self, threshold: u8) -> Self {
+		self.threshold = threshold;
+		self
+	}
+
+	/// Sets whether the threshold is varied with an ordered dithering pattern, which draws
+	/// gradients and mid-tones as dot densities instead of flat areas
+	pub fn with_dithering(mut self, dithering: bool) -> Self {
+		self.dithering = dithering;
+		self
+	}
+
+	/// Sets whether each cell is colored with the average of its dots
+	pub fn with_colors(mut self, colored: bool) -> Self {
+		self.colored = colored;
+		self
+	}
+
+	/// Returns if the pixel in the given position is drawn as a dot
+	fn is_dot(&self, x: u32, y: u32, color: &Color) -> bool {
+		let luma = (299 * color.r as u32 + 587 * color.g as u32 + 114 * color.b as u32) / 1000;
+		let threshold = match self.dithering {
+			true => {
+				let offset = BAYER[y as usize % 4][x as usize % 4] as u32 * 16 + 8;
+				(self.threshold as u32 + offset).saturating_sub(128)
+			}
+			false => self.threshold as u32,
+		};
+		luma >= threshold
+	}
+
+}
+
+impl Default for BrailleOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Character cell of the terminal, without colors to use the default ones of the terminal
+#[derive(Clone, Debug, PartialEq)]
+struct Cell {
+	character: char,
+	foreground: Option<Color>,
+	background: Option<Color>,
+}
+
+/// Canvas drawing on a terminal supporting 24-bit colors, like most of the modern ones, even over
+/// SSH. The pixels are drawn as half-blocks by default, or as Braille dots with
+/// [TerminalMode::Braille]. The canvas starts at the top-left corner of the terminal, and each
+/// render only writes the cells that changed since the previous one.
+///
+/// # Example
+/// ```no_run
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::terminal::TerminalCanvas;
+/// # use ferrux_canvas::color::palette;
+/// let mut canvas = TerminalCanvas::new(std::io::stdout(), 80, 48);
+/// canvas.clear_frame().unwrap();
+/// canvas.fill_triangle((40, 0), (0, 47), (79, 47), palette::RED);
+/// canvas.render().unwrap();
+/// ```
+///
+/// The Braille mode can plot charts on consoles without colors:
+/// ```no_run
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::terminal::{BrailleOptions, TerminalCanvas, TerminalMode};
+/// # use ferrux_canvas::color::palette;
+/// let mut canvas = TerminalCanvas::new(std::io::stdout(), 160, 96)
+///   .with_mode(TerminalMode::Braille(BrailleOptions::new()));
+/// for x in 1..160 {
+///   let y = |x: u32| 48 - ((x as f64 / 10.0).sin() * 47.0) as u32;
+///   canvas.draw_line((x - 1, y(x - 1)), (x, y(x)), palette::WHITE);
+/// }
+/// canvas.render().unwrap();
+/// ```
+///
+pub struct TerminalCanvas<W: Write> {
+	writer: W,
+	canvas: Image,
+	blend_space: BlendSpace,
+	mode: TerminalMode,
+	rendered: Vec<Option<Cell>>,
+	width: u32,
+	height: u32,
+}
+
+impl<W: Write> TerminalCanvas<W> {
+
+	/// Returns a new terminal canvas writing to the given terminal, usually [std::io::stdout],
+	/// drawing half-blocks
+	///
+	/// # Arguments
+	/// * `writer` - Terminal to write on
+	/// * `width` - Width of the canvas in pixels, which is the number of columns it takes
+	/// * `height` - Height of the canvas in pixels, which is twice the number of rows it takes
+	///
+	pub fn new(writer: W, width: u32, height: u32) -> Self {
+		let mode = TerminalMode::default();
+		Self {
+			writer,
+			canvas: Image::filled(width, height, palette::BLACK),
+			blend_space: BlendSpace::default(),
+			mode,
+			rendered: vec![None; Self::cell_count(mode, width, height)],
+			width,
+			height,
+		}
+	}
+
+	/// Sets the way the pixels are drawn. Changing the mode repaints all the cells in the next
+	/// render.
+	pub fn with_mode(mut self, mode: TerminalMode) -> Self {
+		self.mode = mode;
+		self.rendered = vec![None; Self::cell_count(mode, self.width, self.height)];
+		self
+	}
+
+	/// Sets the [BlendSpace] where the translucent pixels are blended, sRGB by default
+	pub fn with_blend_space(mut self, blend_space: BlendSpace) -> Self {
+		self.blend_space = blend_space;
+		self
+	}
+
+	/// Returns the terminal the canvas writes on
+	pub fn get_ref(&self) -> &W {
+		&self.writer
+	}
+
+	/// Returns the terminal the canvas writes on as mutable. Writing on it directly can leave
+	/// outdated cells, [Canvas::clear_frame] makes the next render repaint them all.
+	pub fn get_mut(&mut self) -> &mut W {
+		&mut self.writer
+	}
+
+	/// Returns the terminal the canvas writes on, consuming the canvas
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+
+	/// Returns the number of columns and rows taken by the canvas
+	fn cells(mode: TerminalMode, width: u32, height: u32) -> (u32, u32) {
+		let (cell_width, cell_height) = mode.cell_size();
+		(width.div_ceil(cell_width), height.div_ceil(cell_height))
+	}
+
+	fn cell_count(mode: TerminalMode, width: u32, height: u32) -> usize {
+		let (columns, rows) = Self::cells(mode, width, height);
+		columns as usize * rows as usize
+	}
+
+	/// Returns the cell in the given column and row
+	fn cell(&self, column: u32, row: u32) -> Cell {
+		match self.mode {
+			TerminalMode::HalfBlocks => Cell {
+				character: '▀',
+				foreground: self.canvas.pixel(column, row * 2).copied(),
+				background: self.canvas.pixel(column, row * 2 + 1).copied(),
+			},
+			TerminalMode::Braille(options) => self.braille_cell(options, column, row),
+		}
+	}
+
+	/// Returns the Braille character of the block of pixels in the given column and row, colored
+	/// with the average of its dots if requested
+	fn braille_cell(&self, options: BrailleOptions, column: u32, row: u32) -> Cell {
+		let mut dots = 0u32;
+		let mut sums = [0u32; 3];
+		let mut count = 0;
+		for (bit, (dx, dy)) in BRAILLE_DOTS.iter().enumerate() {
+			let (x, y) = (column * 2 + dx, row * 4 + dy);
+			let Some(color) = self.canvas.pixel(x, y) else {
+				continue;
+			};
+			if options.is_dot(x, y, color) {
+				dots |= 1 << bit;
+				sums[0] += color.r as u32;
+				sums[1] += color.g as u32;
+				sums[2] += color.b as u32;
+				count += 1;
+			}
+		}
+		let foreground = (options.colored && count > 0).then(|| Color {
+			r: ((sums[0] + count / 2) / count) as u8,
+			g: ((sums[1] + count / 2) / count) as u8,
+			b: ((sums[2] + count / 2) / count) as u8,
+			a: 255,
+		});
+		Cell {
+			character: char::from_u32(0x2800 + dots).unwrap_or(' '),
+			foreground,
+			background: None,
+		}
+	}
+
+	/// Returns the escape sequences and characters to paint the cells that changed since the last
+	/// render, updating the rendered ones
+	fn changes(&mut self) -> String {
+		let mut output = String::new();
+		let mut cursor = None;
+		let mut foreground = None;
+		let mut background = None;
+		let (columns, rows) = Self::cells(self.mode, self.width, self.height);
+		for (i, (column, row)) in (0..rows)
+			.flat_map(|row| (0..columns).map(move |column| (column, row)))
+			.enumerate() {
+			let cell = self.cell(column, row);
+			if self.rendered[i].as_ref() == Some(&cell) {
+				continue;
+			}
+			if cursor != Some((column, row)) {
+				let _ = write!(output, "\x1b[{};{}H", row + 1, column + 1);
+			}
+			if foreground.as_ref() != Some(&cell.foreground) {
+				let _ = match &cell.foreground {
+					Some(color) => write!(output, "\x1b[38;2;{};{};{}m", color.r, color.g, color.b),
+					None => write!(output, "\x1b[39m"),
+				};
+				foreground = Some(cell.foreground);
+			}
+			if background.as_ref() != Some(&cell.background) {
+				let _ = match &cell.background {
+					Some(color) => write!(output, "\x1b[48;2;{};{};{}m", color.r, color.g, color.b),
+					None => write!(output, "\x1b[49m"),
+				};
+				background = Some(cell.background);
+			}
+			output.push(cell.character);
+			cursor = Some((column + 1, row));
+			self.rendered[i] = Some(cell);
+		}
+		if !output.is_empty() {
+			output.push_str("\x1b[0m");
+		}
+		output
+	}
+
+}
+
+impl<W: Write> Canvas for TerminalCanvas<W> {
+
+	fn width(&self) -> u32 {
+		self.width
+	}
+
+	fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Writes the cells of the terminal that changed since the previous render
+	///
+	/// # Errors
+	/// [CanvasError::Rendering] if the terminal can't be written
+	///
+	fn render(&mut self) -> Result<(), CanvasError> {
+		let changes = self.changes();
+		if changes.is_empty() {
+			return Ok(());
+		}
+		self.writer.write_all(changes.as_bytes())
+			.and_then(|_| self.writer.flush())
+			.map_err(|e| {
+				error!("writing to the terminal failed: {:?}", e);
+				CanvasError::Rendering
+			})
+	}
+
+	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.canvas.set_pixel(x, y, color);
+	}
+
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		if let Some(pixel) = self.canvas.pixel(x, y) {
+			let blended = self.blend_space.blend(&color, pixel);
+			self.canvas.set_pixel(x, y, blended);
+		}
+	}
+
+	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+		helpers::draw_line(self, start, end, color);
+	}
+
+	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.draw_line(point_a, point_b, color);
+		self.draw_line(point_b, point_c, color);
+		self.draw_line(point_c, point_a, color);
+	}
+
+	fn fill_triangle(&mut self, p1: Point, p2: Point, p3: Point, color: Color) {
+		helpers::fill_triangle(self, p1, p2, p3, color);
+	}
+
+	/// Clears the whole terminal, keeping the current frame to paint it again in the next render
+	///
+	/// # Errors
+	/// [CanvasError::Rendering] if the terminal can't be written
+	///
+	fn clear_frame(&mut self) -> Result<(), CanvasError> {
+		self.rendered.iter_mut().for_each(|cell| *cell = None);
+		self.writer.write_all(b"\x1b[0m\x1b[2J")
+			.and_then(|_| self.writer.flush())
+			.map_err(|e| {
+				error!("writing to the terminal failed: {:?}", e);
+				CanvasError::Rendering
+			})
+	}
+
+	fn reset_frame(&mut self) {
+		self.canvas = Image::filled(self.width, self.height, palette::BLACK);
+	}
+
+	fn resize(&mut self, width: u32, height: u32) {
+		self.width = width;
+		self.height = height;
+		self.reset_frame();
+		self.rendered = vec![None; Self::cell_count(self.mode, width, height)];
+	}
+
+}
+
+impl<W: Write> RasterCanvas for TerminalCanvas<W> {
+
+	fn frame(&self) -> &Image {
+		&self.canvas
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::Canvas;
+	use crate::canvas::terminal::{BrailleOptions, TerminalCanvas, TerminalMode};
+	use crate::color::{Color, palette};
+
+	fn rendered(canvas: &mut TerminalCanvas<Vec<u8>>) -> String {
+		canvas.render().unwrap();
+		String::from_utf8(std::mem::take(canvas.get_mut())).unwrap()
+	}
+
+	#[test]
+	fn render_half_blocks() {
+		let mut canvas = TerminalCanvas::new(Vec::new(), 2, 3);
+		canvas.draw_pixel(0, 0, palette::RED);
+		canvas.draw_pixel(1, 2, palette::WHITE);
+		assert_eq!("\x1b[1;1H\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀\
+		            \x1b[38;2;0;0;0m▀\
+		            \x1b[2;1H\x1b[49m▀\
+		            \x1b[38;2;255;255;255m▀\x1b[0m", rendered(&mut canvas));
+	}
+
+	#[test]
+	fn render_only_changes() {
+		let mut canvas = TerminalCanvas::new(Vec::new(), 4, 4);
+		rendered(&mut canvas);
+		assert_eq!("", rendered(&mut canvas));
+		canvas.draw_pixel(2, 3, palette::BLUE);
+		assert_eq!("\x1b[2;3H\x1b[38;2;0;0;0m\x1b[48;2;0;0;255m▀\x1b[0m", rendered(&mut canvas));
+		canvas.clear_frame().unwrap();
+		assert_eq!("\x1b[0m\x1b[2J", String::from_utf8(std::mem::take(canvas.get_mut())).unwrap());
+		assert_eq!(8, rendered(&mut canvas).matches('▀').count());
+	}
+
+	#[test]
+	fn render_braille_dots() {
+		let mut canvas = TerminalCanvas::new(Vec::new(), 3, 5)
+			.with_mode(TerminalMode::Braille(BrailleOptions::new()));
+		canvas.draw_pixel(0, 0, palette::WHITE);
+		canvas.draw_pixel(1, 3, palette::WHITE);
+		canvas.draw_pixel(2, 4, Color { r: 100, g: 100, b: 100, a: 255 });
+		assert_eq!("\x1b[1;1H\x1b[39m\x1b[49m⢁\u{2800}\x1b[2;1H\u{2800}\u{2800}\x1b[0m", rendered(&mut canvas));
+
+		let options = BrailleOptions::new().with_threshold(100).with_colors(true);
+		let mut canvas = canvas.with_mode(TerminalMode::Braille(options));
+		assert_eq!("\x1b[1;1H\x1b[38;2;255;255;255m\x1b[49m⢁\x1b[39m\u{2800}\
+		            \x1b[2;1H\u{2800}\x1b[38;2;100;100;100m⠁\x1b[0m", rendered(&mut canvas));
+	}
+
+	#[test]
+	fn dither_braille_dots() {
+		let gray = Color { r: 128, g: 128, b: 128, a: 255 };
+		let options = BrailleOptions::new().with_dithering(true);
+		let mut canvas = TerminalCanvas::new(Vec::new(), 4, 4).with_mode(TerminalMode::Braille(options));
+		canvas.fill_triangle((0, 0), (3, 0), (0, 3), gray);
+		canvas.fill_triangle((3, 0), (3, 3), (0, 3), gray);
+		let dots = rendered(&mut canvas).chars()
+			.filter(|c| ('\u{2800}'..='\u{28ff}').contains(c))
+			.map(|c| (c as u32 - 0x2800).count_ones())
+			.sum::<u32>();
+		assert_eq!(8, dots);
+	}
+
+}
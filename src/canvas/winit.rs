@@ -1,20 +1,20 @@
 //! Tools of the library to work with [winit]
 
 use std::fmt::{Debug, Formatter};
-use bresenham_zip::build_zip;
-use line_drawing::Bresenham;
 use log::{error, info};
 use pixels::{Pixels, SurfaceTexture};
 use winit::window::Window;
 use crate::canvas::canvas_error::CanvasError;
-use crate::canvas::{Canvas, Point};
-use crate::canvas::helpers::{as_signed, as_u32, calculate_intersection, sort_vectors};
+use crate::canvas::{Canvas, Point, RasterCanvas};
+use crate::canvas::helpers;
 use crate::color::*;
+use crate::image::Image;
 
 /// Canvas to use with a [winit::window::Window]
 pub struct WinitCanvas {
 	pixels: Pixels,
-	canvas: Vec<Vec<Color>>,
+	canvas: Image,
+	blend_space: BlendSpace,
 	width: u32,
 	height: u32,
 }
@@ -59,43 +59,17 @@ impl WinitCanvas {
 
 		Ok(Self {
 			pixels,
-			canvas: vec![vec![palette::BLACK; height as usize]; width as usize],
+			canvas: Image::filled(width, height, palette::BLACK),
+			blend_space: BlendSpace::default(),
 			width,
 			height,
 		})
 	}
 
-	/// Draws an horizontal line between two points
-	fn draw_horizontal_line(&mut self, start: Point, end: Point, color: Color) {
-		let y = start.1;
-		for x in if start.0 < end.0 { start.0..=end.0 } else { end.0..=start.0 } {
-			self.draw_pixel(x, y, color.clone());
-		}
-	}
-
-	/// Draws a vertical line between two points
-	fn draw_vertical_line(&mut self, start: Point, end: Point, color: Color) {
-		let x = start.0;
-		for y in if start.1 < end.1 { start.1..=end.1 } else { end.1..=start.1} {
-			self.draw_pixel(x, y, color.clone());
-		}
-	}
-
-	/// Draws a diagonal line between two points using Bresenham's algorithm
-	fn draw_diagonal_line(&mut self, start: Point, end: Point, color: Color) {
-		for (x, y) in Bresenham::new(as_signed(start),as_signed(end)) {
-			self.draw_pixel(x as u32, y as u32, color.clone());
-		}
-	}
-
-	/// Fills the flat triangle (a triangle were two points share the same height) made with the three
-	/// passed points using Bresenham
-	fn fill_flat_triangle(&mut self, peak: Point, side_a: Point, side_b: Point, color: Color) {
-		#![allow(unused_parens)]
-		let bresenham = build_zip!(2D:Y - (as_signed(peak)) -> (as_signed(side_a)), (as_signed(side_b)));
-		for (left, right) in bresenham.unwrap() {
-			self.draw_line(as_u32(left), as_u32(right), color.clone());
-		}
+	/// Sets the [BlendSpace] where the translucent pixels are blended, sRGB by default
+	pub fn with_blend_space(mut self, blend_space: BlendSpace) -> Self {
+		self.blend_space = blend_space;
+		self
 	}
 
 }
@@ -140,8 +114,8 @@ impl Canvas for WinitCanvas {
 	/// [CanvasError::Rendering] if something goes wrong loading the current texture
 	///
 	fn render(&mut self) -> Result<(), CanvasError> {
-		for (i, pixel) in self.pixels.get_frame().chunks_exact_mut(4).enumerate() {
-			pixel.copy_from_slice(&self.canvas[i % self.width as usize][i / self.width as usize].as_u8());
+		for (pixel, color) in self.pixels.get_frame().chunks_exact_mut(4).zip(self.canvas.pixels()) {
+			pixel.copy_from_slice(&color.as_u8());
 		}
 
 		self.pixels.render().map_err(|e| {
@@ -151,36 +125,28 @@ impl Canvas for WinitCanvas {
 	}
 
 	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
-		if x < self.width && y < self.height {
-			self.canvas[x as usize][y as usize] = color;
+		self.canvas.set_pixel(x, y, color);
+	}
+
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		if let Some(pixel) = self.canvas.pixel(x, y) {
+			let blended = self.blend_space.blend(&color, pixel);
+			self.canvas.set_pixel(x, y, blended);
 		}
 	}
 
 	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
-		match start {
-			(x, _) if x == end.0 => self.draw_vertical_line(start, end, color),
-			(_, y) if y == end.1 => self.draw_horizontal_line(start, end, color),
-			_ => self.draw_diagonal_line(start, end, color)
-		}
+		helpers::draw_line(self, start, end, color);
 	}
 
 	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
-		self.draw_line(point_a, point_b, color.clone());
-		self.draw_line(point_b, point_c, color.clone());
+		self.draw_line(point_a, point_b, color);
+		self.draw_line(point_b, point_c, color);
 		self.draw_line(point_c, point_a, color);
 	}
 
 	fn fill_triangle(&mut self, p1: Point, p2: Point, p3: Point, color: Color) {
-		let (p1, p2, p3) = sort_vectors(p1, p2, p3);
-		match p2 {
-			(_, y) if y == p1.1 => self.fill_flat_triangle(p3, p1, p2, color),
-			(_, y) if y == p3.1 => self.fill_flat_triangle(p1, p2, p3, color),
-			_ => {
-				let p4 = calculate_intersection(p3, p2, p1);
-				self.fill_flat_triangle(p1, p2, p4, color.clone());
-				self.fill_flat_triangle(p3, p2, p4, color);
-			}
-		}
+		helpers::fill_triangle(self, p1, p2, p3, color);
 	}
 
 	fn clear_frame(&mut self) -> Result<(), CanvasError> {
@@ -195,7 +161,7 @@ impl Canvas for WinitCanvas {
 	}
 
 	fn reset_frame(&mut self) {
-		self.canvas = vec![vec![palette::BLACK; self.height as usize]; self.width as usize];
+		self.canvas = Image::filled(self.width, self.height, palette::BLACK);
 	}
 
 	fn resize(&mut self, width: u32, height: u32) {
@@ -207,6 +173,26 @@ impl Canvas for WinitCanvas {
 
 }
 
+impl RasterCanvas for WinitCanvas {
+
+	/// Returns the current frame, with everything drawn since the last reset
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+	/// # use ferrux_canvas::color::palette;
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// canvas.draw_line((100, 100), (300, 200), palette::WHITE);
+	/// canvas.frame().save("frame.ppm").unwrap();
+	/// ```
+	///
+	fn frame(&self) -> &Image {
+		&self.canvas
+	}
+
+}
+
 impl Debug for WinitCanvas {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		write!(f, "[WinitCanvas]\
@@ -2,31 +2,73 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
-/// Errors than can occur using [`Color`]
+/// Errors than can occur using [`Color`]. The positions are byte offsets in the parsed text.
 ///
 /// [`Color`]: crate::color::Color
-pub enum ColorError<'a> {
-	/// The syntax of the color is invalid
-	InvalidSyntax(&'a str),
+#[derive(Clone, PartialEq, Eq)]
+pub enum ColorError {
+	/// The syntax of the color is invalid. Contains the expected syntax.
+	InvalidSyntax(&'static str),
+	/// The text to parse is empty or only has whitespaces
+	Empty,
+	/// The hexadecimal color doesn't have 3, 4, 6 or 8 digits. Contains the number of digits.
+	InvalidHexLength(usize),
+	/// The character is not valid in its position
+	InvalidCharacter {
+		/// Invalid character
+		character: char,
+		/// Position of the character
+		position: usize,
+	},
+	/// The name is not one of the CSS named colors
+	UnknownName(String),
+	/// The function is not one of the supported CSS color functions
+	UnknownFunction(String),
+	/// The function call is not closed with a parenthesis
+	UnclosedFunction(String),
+	/// The function doesn't have the right number of arguments. Contains the function and the
+	/// number of arguments found.
+	ArgumentCount(String, usize),
+	/// The argument is not a valid value for its position
+	InvalidArgument {
+		/// Invalid argument
+		argument: String,
+		/// Position of the argument
+		position: usize,
+	},
 }
 
-impl<'a> ColorError<'a> {
+impl ColorError {
 	fn message(&self) -> String {
 		match self {
-			Self::InvalidSyntax(syntax) => "The color syntax is invalid. It should be. ".to_owned() + *syntax,
+			Self::InvalidSyntax(syntax) => "The color syntax is invalid. It should be: ".to_owned() + syntax,
+			Self::Empty => "The color is empty".to_owned(),
+			Self::InvalidHexLength(digits) => format!("The hexadecimal color should have 3, 4, 6 or 8 digits, \
+				not {}", digits),
+			Self::InvalidCharacter { character, position } => format!("The character {:?} at position {} is \
+				not valid", character, position),
+			Self::UnknownName(name) => format!("{:?} is not a CSS color name", name),
+			Self::UnknownFunction(function) => format!("{}() is not a supported color function. It should be \
+				rgb(), rgba(), hsl() or hsla()", function),
+			Self::UnclosedFunction(function) => format!("The call to {}() is missing the closing parenthesis",
+				function),
+			Self::ArgumentCount(function, count) => format!("{}() takes three values and an optional alpha, \
+				not {} arguments", function, count),
+			Self::InvalidArgument { argument, position } => format!("The argument {:?} at position {} is not \
+				valid", argument, position),
 		}
 	}
 }
 
-impl<'a> Error for ColorError<'a> {}
+impl Error for ColorError {}
 
-impl<'a> Debug for ColorError<'a> {
+impl Debug for ColorError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		write!(f, "{}", self.message())
 	}
 }
 
-impl<'a> Display for ColorError<'a> {
+impl Display for ColorError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		write!(f, "{}", self.message())
 	}
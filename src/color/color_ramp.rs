@@ -0,0 +1,340 @@
+//! Interpolation of colors and ramps mapping values to colors, like the ones used in heatmaps
+use crate::color::{Color, Hsl, LinearColor, Oklab};
+
+/// Number of stops of the built-in colormaps, sampled from their polynomial fits or lookup tables
+const COLORMAP_STOPS: usize = 32;
+
+/// Coefficients of the polynomial fit of viridis, by powers of the position
+const VIRIDIS: [[f32; 3]; 7] = [
+	[0.277727, 0.00540734, 0.3341],
+	[0.105093, 1.40461, 1.38459],
+	[-0.330862, 0.214848, 0.0950952],
+	[-4.63423, -5.7991, -19.3324],
+	[6.22827, 14.1799, 56.6906],
+	[4.77639, -13.7451, -65.353],
+	[-5.43546, 4.64585, 26.3124],
+];
+
+/// Coefficients of the polynomial fit of plasma, by powers of the position
+const PLASMA: [[f32; 3]; 7] = [
+	[0.0587323, 0.0233367, 0.54334],
+	[2.17651, 0.238383, 0.75396],
+	[-2.68946, -7.45585, 3.1108],
+	[6.13035, 42.3462, -28.5189],
+	[-11.1074, -82.6663, 60.1398],
+	[10.0231, 71.4136, -54.0722],
+	[-3.65871, -22.9315, 18.1919],
+];
+
+/// Coefficients of the polynomial fit of magma, by powers of the position
+const MAGMA: [[f32; 3]; 7] = [
+	[-0.00213649, -0.000749655, -0.00538613],
+	[0.251661, 0.677523, 2.49403],
+	[8.35372, -3.57772, 0.314468],
+	[-27.6687, 14.2647, -13.6492],
+	[52.1761, -27.9436, 12.9442],
+	[-50.7685, 29.0466, 4.23415],
+	[18.6557, -11.4898, -5.60196],
+];
+
+/// Coefficients of the polynomial fit of inferno, by powers of the position
+const INFERNO: [[f32; 3]; 7] = [
+	[0.00021894, 0.001651, -0.0194809],
+	[0.106513, 0.563956, 3.93271],
+	[11.6025, -3.97285, -15.9424],
+	[-41.704, 17.4364, 44.3541],
+	[77.1629, -33.4024, -81.8073],
+	[-71.3194, 32.6261, 73.2095],
+	[25.1311, -12.2427, -23.0703],
+];
+
+/// Colors of turbo, sampled evenly from its published lookup table of 256 colors
+const TURBO: [[u8; 3]; COLORMAP_STOPS] = [
+	[48, 18, 59],
+	[57, 42, 115],
+	[64, 64, 162],
+	[69, 89, 203],
+	[71, 110, 230],
+	[70, 130, 248],
+	[65, 150, 255],
+	[51, 173, 247],
+	[37, 192, 231],
+	[26, 210, 210],
+	[24, 224, 189],
+	[34, 235, 170],
+	[60, 245, 142],
+	[89, 251, 115],
+	[121, 254, 89],
+	[150, 254, 68],
+	[175, 250, 55],
+	[195, 241, 52],
+	[215, 229, 53],
+	[231, 215, 57],
+	[245, 197, 58],
+	[252, 179, 54],
+	[254, 158, 47],
+	[252, 135, 37],
+	[247, 111, 26],
+	[237, 85, 16],
+	[226, 67, 10],
+	[212, 51, 5],
+	[195, 37, 3],
+	[172, 23, 1],
+	[149, 13, 1],
+	[122, 4, 3],
+];
+
+/// Color space where two colors are interpolated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+	/// Mixes the sRGB encoded channels. It's the fastest and the usual one of CSS gradients, but
+	/// its middle colors are darker and grayer than expected.
+	#[default]
+	Srgb,
+	/// Mixes the light of the channels, keeping the brightness of the colors
+	Linear,
+	/// Mixes the hue, through the shortest arc, the saturation and the lightness, going around the
+	/// color wheel instead of through the grays
+	Hsl,
+	/// Mixes the perceptual components, changing the lightness and chroma evenly
+	Oklab,
+}
+
+/// Ramp of colors mapping positions from 0 to 1 to colors, interpolating the colors between its
+/// stops. Besides custom ramps, the scientific colormaps viridis, magma, inferno, plasma and turbo
+/// are available. The first four are approximated with polynomial fits of the originals and turbo
+/// is sampled from its lookup table.
+///
+/// # Example
+/// The following example draws a heatmap of the distance to the center of the canvas.
+/// ```rust
+/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::ColorRamp;
+/// let ramp = ColorRamp::viridis();
+/// let mut canvas = HeadlessCanvas::new(64, 64);
+/// for y in 0..64 {
+///   for x in 0..64 {
+///     let distance = ((x as f32 - 32.0).hypot(y as f32 - 32.0) / 45.0).min(1.0);
+///     canvas.draw_pixel(x, y, ramp.color_at(distance));
+///   }
+/// }
+/// assert_eq!(Some(&ramp.color_at(0.0)), canvas.frame().pixel(32, 32));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorRamp {
+	stops: Vec<(f32, Color)>,
+	interpolation: Interpolation,
+}
+
+impl ColorRamp {
+
+	/// Returns a ramp going through the colors, evenly spaced from 0 to 1
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, ColorRamp, palette};
+	/// let ramp = ColorRamp::new(vec![palette::BLACK, palette::RED, palette::WHITE]);
+	/// assert_eq!(palette::RED, ramp.color_at(0.5));
+	/// assert_eq!(Color { r: 255, g: 128, b: 128, a: 255 }, ramp.color_at(0.75));
+	/// ```
+	///
+	pub fn new(colors: Vec<Color>) -> Self {
+		let last = colors.len().saturating_sub(1).max(1) as f32;
+		Self::from_stops(colors.into_iter().enumerate().map(|(i, color)| (i as f32 / last, color)).collect())
+	}
+
+	/// Returns a ramp going through the colors at the given positions. The stops don't need to be
+	/// sorted, and the positions before the first stop or after the last one take its color.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, ColorRamp, palette};
+	/// let ramp = ColorRamp::from_stops(vec![(0.8, palette::WHITE), (0.2, palette::BLACK)]);
+	/// assert_eq!(palette::BLACK, ramp.color_at(0.1));
+	/// assert_eq!(Color { r: 128, g: 128, b: 128, a: 255 }, ramp.color_at(0.5));
+	/// ```
+	///
+	pub fn from_stops(mut stops: Vec<(f32, Color)>) -> Self {
+		stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+		Self { stops, interpolation: Interpolation::default() }
+	}
+
+	/// Sets the color space where the colors between the stops are interpolated
+	pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+		self.interpolation = interpolation;
+		self
+	}
+
+	/// Returns the color at the given position of the ramp, usually from 0 to 1. An empty ramp
+	/// is fully transparent.
+	pub fn color_at(&self, position: f32) -> Color {
+		let next = self.stops.partition_point(|(stop, _)| *stop <= position);
+		match (next.checked_sub(1).and_then(|i| self.stops.get(i)), self.stops.get(next)) {
+			(Some((start, from)), Some((end, to))) => {
+				from.lerp(to, (position - start) / (end - start), self.interpolation)
+			}
+			(Some((_, color)), None) | (None, Some((_, color))) => *color,
+			(None, None) => Color { r: 0, g: 0, b: 0, a: 0 },
+		}
+	}
+
+	/// Returns the given number of colors evenly spaced along the ramp, from its start to its end
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{ColorRamp, palette};
+	/// let ramp = ColorRamp::new(vec![palette::RED, palette::BLUE]);
+	/// assert_eq!(vec![palette::RED, palette::BLUE], ramp.colors(2));
+	/// ```
+	///
+	pub fn colors(&self, count: usize) -> Vec<Color> {
+		let last = count.saturating_sub(1).max(1) as f32;
+		(0..count).map(|i| self.color_at(i as f32 / last)).collect()
+	}
+
+	/// Returns the viridis colormap, from dark purple to yellow through blue and green. It's
+	/// perceptually uniform and readable by color blind people, a good default for heatmaps.
+	pub fn viridis() -> Self {
+		Self::colormap(&VIRIDIS)
+	}
+
+	/// Returns the magma colormap, from black to pale yellow through purple and orange
+	pub fn magma() -> Self {
+		Self::colormap(&MAGMA)
+	}
+
+	/// Returns the inferno colormap, from black to pale yellow through purple, red and orange
+	pub fn inferno() -> Self {
+		Self::colormap(&INFERNO)
+	}
+
+	/// Returns the plasma colormap, from dark blue to yellow through purple and orange
+	pub fn plasma() -> Self {
+		Self::colormap(&PLASMA)
+	}
+
+	/// Returns the turbo colormap, a rainbow from dark blue to dark red with smooth transitions.
+	/// It's not perceptually uniform, but makes it easy to read the values back.
+	pub fn turbo() -> Self {
+		let last = (COLORMAP_STOPS - 1) as f32;
+		Self::from_stops(TURBO.iter().enumerate()
+			.map(|(i, &[r, g, b])| (i as f32 / last, Color { r, g, b, a: u8::MAX }))
+			.collect())
+	}
+
+	/// Returns a ramp sampling the polynomial, from 0 to 1, with the given coefficients
+	fn colormap(coefficients: &[[f32; 3]]) -> Self {
+		let channel = |t: f32, i: usize| {
+			let value = coefficients.iter().rev().fold(0.0, |value, coefficient| value * t + coefficient[i]);
+			(value.clamp(0.0, 1.0) * 255.0).round() as u8
+		};
+		let last = (COLORMAP_STOPS - 1) as f32;
+		Self::from_stops((0..COLORMAP_STOPS).map(|i| i as f32 / last)
+			.map(|t| (t, Color { r: channel(t, 0), g: channel(t, 1), b: channel(t, 2), a: u8::MAX }))
+			.collect())
+	}
+
+}
+
+/// Interpolates the two colors in the given space, see [Color::lerp]
+pub(crate) fn lerp(from: &Color, to: &Color, t: f32, interpolation: Interpolation) -> Color {
+	let mix = |from: f32, to: f32| from + (to - from) * t;
+	let alpha = mix(from.a as f32, to.a as f32).round().clamp(0.0, 255.0) as u8;
+	match interpolation {
+		Interpolation::Srgb => {
+			let channel = |from: u8, to: u8| mix(from as f32, to as f32).round().clamp(0.0, 255.0) as u8;
+			Color { r: channel(from.r, to.r), g: channel(from.g, to.g), b: channel(from.b, to.b), a: alpha }
+		}
+		Interpolation::Linear => {
+			let (from, to) = (LinearColor::from(from), LinearColor::from(to));
+			Color::from(LinearColor {
+				r: mix(from.r, to.r),
+				g: mix(from.g, to.g),
+				b: mix(from.b, to.b),
+				a: alpha as f32 / 255.0,
+			})
+		}
+		Interpolation::Hsl => {
+			let (from, to) = (Hsl::from(from), Hsl::from(to));
+			// The grays have no hue, so they take the one of the other color
+			let (from_h, to_h) = match (from.s == 0.0, to.s == 0.0) {
+				(true, false) => (to.h, to.h),
+				(false, true) => (from.h, from.h),
+				_ => (from.h, to.h),
+			};
+			let arc = (to_h - from_h + 180.0).rem_euclid(360.0) - 180.0;
+			Color::from(Hsl {
+				h: (from_h + arc * t).rem_euclid(360.0),
+				s: mix(from.s, to.s),
+				l: mix(from.l, to.l),
+				a: alpha,
+			})
+		}
+		Interpolation::Oklab => Color::from(Oklab::from(from).lerp(&Oklab::from(to), t)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, ColorRamp, Interpolation, palette};
+
+	#[test]
+	fn interpolations() {
+		let gray = Color { r: 128, g: 128, b: 128, a: 255 };
+		let (black, white) = (palette::BLACK, palette::WHITE);
+		assert_eq!(gray, black.lerp(&white, 0.5, Interpolation::Srgb));
+		assert_eq!(Color { r: 188, g: 188, b: 188, a: 255 }, black.lerp(&white, 0.5, Interpolation::Linear));
+		assert_eq!(gray, black.lerp(&white, 0.5, Interpolation::Hsl));
+		assert_eq!(Color { r: 99, g: 99, b: 99, a: 255 }, black.lerp(&white, 0.5, Interpolation::Oklab));
+		for interpolation in [Interpolation::Srgb, Interpolation::Linear, Interpolation::Hsl, Interpolation::Oklab] {
+			assert_eq!(palette::RED, palette::RED.lerp(&palette::BLUE, 0.0, interpolation));
+			assert_eq!(palette::BLUE, palette::RED.lerp(&palette::BLUE, 1.0, interpolation));
+		}
+		let transparent = Color { r: 255, g: 0, b: 0, a: 0 };
+		assert_eq!(Color { r: 255, g: 0, b: 0, a: 128 }, palette::RED.lerp(&transparent, 0.5, Interpolation::Linear));
+	}
+
+	#[test]
+	fn hue_interpolation() {
+		let magenta = Color { r: 255, g: 0, b: 255, a: 255 };
+		// Shortest arc from 300 to 0 degrees
+		assert_eq!(Color { r: 255, g: 0, b: 128, a: 255 }, magenta.lerp(&palette::RED, 0.5, Interpolation::Hsl));
+		assert_eq!(Color { r: 255, g: 0, b: 255, a: 255 }, palette::RED.lerp(&palette::BLUE, 0.5, Interpolation::Hsl));
+		// The gray keeps the hue of the other color
+		assert_eq!(Color { r: 191, g: 64, b: 64, a: 255 },
+		           palette::RED.lerp(&Color { r: 128, g: 128, b: 128, a: 255 }, 0.5, Interpolation::Hsl));
+	}
+
+	#[test]
+	fn ramps() {
+		let ramp = ColorRamp::from_stops(vec![(0.0, palette::RED), (0.5, palette::GREEN), (1.0, palette::BLUE)]);
+		assert_eq!(palette::RED, ramp.color_at(-1.0));
+		assert_eq!(palette::GREEN, ramp.color_at(0.5));
+		assert_eq!(palette::BLUE, ramp.color_at(2.0));
+		assert_eq!(Color { r: 0, g: 128, b: 128, a: 255 }, ramp.color_at(0.75));
+		assert_eq!(vec![palette::WHITE; 3], ColorRamp::new(vec![palette::WHITE]).colors(3));
+		assert_eq!(Color { r: 0, g: 0, b: 0, a: 0 }, ColorRamp::new(vec![]).color_at(0.5));
+		let oklab = ColorRamp::new(vec![palette::BLACK, palette::WHITE]).with_interpolation(Interpolation::Oklab);
+		assert_eq!(Color { r: 99, g: 99, b: 99, a: 255 }, oklab.color_at(0.5));
+	}
+
+	#[test]
+	fn colormaps() {
+		let hex = |rgb: u32| Color { r: (rgb >> 16) as u8, g: (rgb >> 8) as u8, b: rgb as u8, a: u8::MAX };
+		let references = [
+			(ColorRamp::viridis(), [0x440154, 0x21918c, 0xfde725]),
+			(ColorRamp::magma(), [0x000004, 0xb73779, 0xfcfdbf]),
+			(ColorRamp::inferno(), [0x000004, 0xbc3754, 0xfcffa4]),
+			(ColorRamp::plasma(), [0x0d0887, 0xcc4778, 0xf0f921]),
+			(ColorRamp::turbo(), [0x30123b, 0xa4fc3c, 0x7a0403]),
+		];
+		for (ramp, colors) in references {
+			for (position, color) in [0.0, 0.5, 1.0].into_iter().zip(colors) {
+				assert!(ramp.color_at(position).delta_e(&hex(color)) < 2.5);
+			}
+		}
+	}
+
+}
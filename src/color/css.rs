@@ -0,0 +1,349 @@
+//! Parsing of the CSS color syntax: hexadecimal colors, the `rgb()` and `hsl()` functions and the
+//! named colors, see the [specification](https://www.w3.org/TR/css-color-4/)
+use crate::color::{Color, Hsl};
+use crate::color::color_error::ColorError;
+
+/// Defines the named colors of CSS, both in the table used to parse them and as the constants
+/// exported by [crate::color::palette]. The colors without a constant already have one there.
+macro_rules! named_colors {
+	($(($name:literal, $rgb:literal $(, $constant:ident)?)),* $(,)?) => {
+		/// Named colors of CSS, sorted by name
+		pub(crate) const NAMED_COLORS: [(&str, u32); 148] = [$(($name, $rgb)),*];
+
+		/// Constants of the named colors
+		pub(crate) mod constants {
+			use crate::color::Color;
+			$($(
+				#[doc = concat!("CSS `", $name, "` color: ", stringify!($rgb))]
+				pub const $constant: Color = Color::from_rgba_u32($rgb << 8 | 0xff);
+			)?)*
+		}
+	};
+}
+
+named_colors! {
+	("aliceblue", 0xf0f8ff, ALICEBLUE), ("antiquewhite", 0xfaebd7, ANTIQUEWHITE),
+	("aqua", 0x00ffff, AQUA), ("aquamarine", 0x7fffd4, AQUAMARINE),
+	("azure", 0xf0ffff, AZURE), ("beige", 0xf5f5dc, BEIGE),
+	("bisque", 0xffe4c4, BISQUE), ("black", 0x000000),
+	("blanchedalmond", 0xffebcd, BLANCHEDALMOND), ("blue", 0x0000ff),
+	("blueviolet", 0x8a2be2, BLUEVIOLET), ("brown", 0xa52a2a, BROWN),
+	("burlywood", 0xdeb887, BURLYWOOD), ("cadetblue", 0x5f9ea0, CADETBLUE),
+	("chartreuse", 0x7fff00, CHARTREUSE), ("chocolate", 0xd2691e, CHOCOLATE),
+	("coral", 0xff7f50, CORAL), ("cornflowerblue", 0x6495ed, CORNFLOWERBLUE),
+	("cornsilk", 0xfff8dc, CORNSILK), ("crimson", 0xdc143c, CRIMSON),
+	("cyan", 0x00ffff, CYAN), ("darkblue", 0x00008b, DARKBLUE),
+	("darkcyan", 0x008b8b, DARKCYAN), ("darkgoldenrod", 0xb8860b, DARKGOLDENROD),
+	("darkgray", 0xa9a9a9, DARKGRAY), ("darkgreen", 0x006400, DARKGREEN),
+	("darkgrey", 0xa9a9a9, DARKGREY), ("darkkhaki", 0xbdb76b, DARKKHAKI),
+	("darkmagenta", 0x8b008b, DARKMAGENTA), ("darkolivegreen", 0x556b2f, DARKOLIVEGREEN),
+	("darkorange", 0xff8c00, DARKORANGE), ("darkorchid", 0x9932cc, DARKORCHID),
+	("darkred", 0x8b0000, DARKRED), ("darksalmon", 0xe9967a, DARKSALMON),
+	("darkseagreen", 0x8fbc8f, DARKSEAGREEN), ("darkslateblue", 0x483d8b, DARKSLATEBLUE),
+	("darkslategray", 0x2f4f4f, DARKSLATEGRAY), ("darkslategrey", 0x2f4f4f, DARKSLATEGREY),
+	("darkturquoise", 0x00ced1, DARKTURQUOISE), ("darkviolet", 0x9400d3, DARKVIOLET),
+	("deeppink", 0xff1493, DEEPPINK), ("deepskyblue", 0x00bfff, DEEPSKYBLUE),
+	("dimgray", 0x696969, DIMGRAY), ("dimgrey", 0x696969, DIMGREY),
+	("dodgerblue", 0x1e90ff, DODGERBLUE), ("firebrick", 0xb22222, FIREBRICK),
+	("floralwhite", 0xfffaf0, FLORALWHITE), ("forestgreen", 0x228b22, FORESTGREEN),
+	("fuchsia", 0xff00ff, FUCHSIA), ("gainsboro", 0xdcdcdc, GAINSBORO),
+	("ghostwhite", 0xf8f8ff, GHOSTWHITE), ("gold", 0xffd700, GOLD),
+	("goldenrod", 0xdaa520, GOLDENROD), ("gray", 0x808080, GRAY),
+	("green", 0x008000), ("greenyellow", 0xadff2f, GREENYELLOW),
+	("grey", 0x808080, GREY), ("honeydew", 0xf0fff0, HONEYDEW),
+	("hotpink", 0xff69b4, HOTPINK), ("indianred", 0xcd5c5c, INDIANRED),
+	("indigo", 0x4b0082, INDIGO), ("ivory", 0xfffff0, IVORY),
+	("khaki", 0xf0e68c, KHAKI), ("lavender", 0xe6e6fa, LAVENDER),
+	("lavenderblush", 0xfff0f5, LAVENDERBLUSH), ("lawngreen", 0x7cfc00, LAWNGREEN),
+	("lemonchiffon", 0xfffacd, LEMONCHIFFON), ("lightblue", 0xadd8e6, LIGHTBLUE),
+	("lightcoral", 0xf08080, LIGHTCORAL), ("lightcyan", 0xe0ffff, LIGHTCYAN),
+	("lightgoldenrodyellow", 0xfafad2, LIGHTGOLDENRODYELLOW), ("lightgray", 0xd3d3d3, LIGHTGRAY),
+	("lightgreen", 0x90ee90, LIGHTGREEN), ("lightgrey", 0xd3d3d3, LIGHTGREY),
+	("lightpink", 0xffb6c1, LIGHTPINK), ("lightsalmon", 0xffa07a, LIGHTSALMON),
+	("lightseagreen", 0x20b2aa, LIGHTSEAGREEN), ("lightskyblue", 0x87cefa, LIGHTSKYBLUE),
+	("lightslategray", 0x778899, LIGHTSLATEGRAY), ("lightslategrey", 0x778899, LIGHTSLATEGREY),
+	("lightsteelblue", 0xb0c4de, LIGHTSTEELBLUE), ("lightyellow", 0xffffe0, LIGHTYELLOW),
+	("lime", 0x00ff00, LIME), ("limegreen", 0x32cd32, LIMEGREEN),
+	("linen", 0xfaf0e6, LINEN), ("magenta", 0xff00ff, MAGENTA),
+	("maroon", 0x800000, MAROON), ("mediumaquamarine", 0x66cdaa, MEDIUMAQUAMARINE),
+	("mediumblue", 0x0000cd, MEDIUMBLUE), ("mediumorchid", 0xba55d3, MEDIUMORCHID),
+	("mediumpurple", 0x9370db, MEDIUMPURPLE), ("mediumseagreen", 0x3cb371, MEDIUMSEAGREEN),
+	("mediumslateblue", 0x7b68ee, MEDIUMSLATEBLUE), ("mediumspringgreen", 0x00fa9a, MEDIUMSPRINGGREEN),
+	("mediumturquoise", 0x48d1cc, MEDIUMTURQUOISE), ("mediumvioletred", 0xc71585, MEDIUMVIOLETRED),
+	("midnightblue", 0x191970, MIDNIGHTBLUE), ("mintcream", 0xf5fffa, MINTCREAM),
+	("mistyrose", 0xffe4e1, MISTYROSE), ("moccasin", 0xffe4b5, MOCCASIN),
+	("navajowhite", 0xffdead, NAVAJOWHITE), ("navy", 0x000080, NAVY),
+	("oldlace", 0xfdf5e6, OLDLACE), ("olive", 0x808000, OLIVE),
+	("olivedrab", 0x6b8e23, OLIVEDRAB), ("orange", 0xffa500, ORANGE),
+	("orangered", 0xff4500, ORANGERED), ("orchid", 0xda70d6, ORCHID),
+	("palegoldenrod", 0xeee8aa, PALEGOLDENROD), ("palegreen", 0x98fb98, PALEGREEN),
+	("paleturquoise", 0xafeeee, PALETURQUOISE), ("palevioletred", 0xdb7093, PALEVIOLETRED),
+	("papayawhip", 0xffefd5, PAPAYAWHIP), ("peachpuff", 0xffdab9, PEACHPUFF),
+	("peru", 0xcd853f, PERU), ("pink", 0xffc0cb, PINK),
+	("plum", 0xdda0dd, PLUM), ("powderblue", 0xb0e0e6, POWDERBLUE),
+	("purple", 0x800080, PURPLE), ("rebeccapurple", 0x663399, REBECCAPURPLE),
+	("red", 0xff0000), ("rosybrown", 0xbc8f8f, ROSYBROWN),
+	("royalblue", 0x4169e1, ROYALBLUE), ("saddlebrown", 0x8b4513, SADDLEBROWN),
+	("salmon", 0xfa8072, SALMON), ("sandybrown", 0xf4a460, SANDYBROWN),
+	("seagreen", 0x2e8b57, SEAGREEN), ("seashell", 0xfff5ee, SEASHELL),
+	("sienna", 0xa0522d, SIENNA), ("silver", 0xc0c0c0, SILVER),
+	("skyblue", 0x87ceeb, SKYBLUE), ("slateblue", 0x6a5acd, SLATEBLUE),
+	("slategray", 0x708090, SLATEGRAY), ("slategrey", 0x708090, SLATEGREY),
+	("snow", 0xfffafa, SNOW), ("springgreen", 0x00ff7f, SPRINGGREEN),
+	("steelblue", 0x4682b4, STEELBLUE), ("tan", 0xd2b48c, TAN),
+	("teal", 0x008080, TEAL), ("thistle", 0xd8bfd8, THISTLE),
+	("tomato", 0xff6347, TOMATO), ("turquoise", 0x40e0d0, TURQUOISE),
+	("violet", 0xee82ee, VIOLET), ("wheat", 0xf5deb3, WHEAT),
+	("white", 0xffffff), ("whitesmoke", 0xf5f5f5, WHITESMOKE),
+	("yellow", 0xffff00, YELLOW), ("yellowgreen", 0x9acd32, YELLOWGREEN),
+}
+
+/// Argument of a color function with its position in the parsed text
+type Argument<'a> = (&'a str, usize);
+
+/// Parses a color written in any of the CSS syntaxes
+pub(crate) fn parse(text: &str) -> Result<Color, ColorError> {
+	let start = text.len() - text.trim_start().len();
+	let color = text.trim();
+	if color.is_empty() {
+		return Err(ColorError::Empty);
+	}
+	if let Some(digits) = color.strip_prefix('#') {
+		return hex(digits, start + 1);
+	}
+	if let Some(open) = color.find('(') {
+		return function(color, open, start);
+	}
+	named(color)
+}
+
+/// Parses the digits of a hexadecimal color
+fn hex(digits: &str, start: usize) -> Result<Color, ColorError> {
+	if let Some((position, character)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+		return Err(ColorError::InvalidCharacter { character, position: start + position });
+	}
+	let digit = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).unwrap_or_default();
+	let pair = |i: usize| digit(i) * 16 + digit(i + 1);
+	match digits.len() {
+		3 | 4 => Ok(Color {
+			r: digit(0) * 17,
+			g: digit(1) * 17,
+			b: digit(2) * 17,
+			a: if digits.len() == 4 { digit(3) * 17 } else { u8::MAX },
+		}),
+		6 | 8 => Ok(Color {
+			r: pair(0),
+			g: pair(2),
+			b: pair(4),
+			a: if digits.len() == 8 { pair(6) } else { u8::MAX },
+		}),
+		length => Err(ColorError::InvalidHexLength(length)),
+	}
+}
+
+/// Parses a named color, ignoring the case
+fn named(name: &str) -> Result<Color, ColorError> {
+	let lowercase = name.to_ascii_lowercase();
+	if lowercase == "transparent" {
+		return Ok(Color { r: 0, g: 0, b: 0, a: 0 });
+	}
+	NAMED_COLORS.binary_search_by_key(&lowercase.as_str(), |(name, _)| name)
+		.map(|i| {
+			let [_, r, g, b] = NAMED_COLORS[i].1.to_be_bytes();
+			Color { r, g, b, a: u8::MAX }
+		})
+		.map_err(|_| ColorError::UnknownName(name.to_owned()))
+}
+
+/// Parses a call to a color function, with `open` being the position of its opening parenthesis
+fn function(color: &str, open: usize, start: usize) -> Result<Color, ColorError> {
+	let name = color[..open].trim_end().to_ascii_lowercase();
+	if !matches!(name.as_str(), "rgb" | "rgba" | "hsl" | "hsla") {
+		return Err(ColorError::UnknownFunction(name));
+	}
+	let Some(content) = color[open + 1..].strip_suffix(')') else {
+		return Err(match color[open + 1..].find(')') {
+			Some(close) => {
+				let position = open + 1 + close + 1;
+				let character = color[position..].chars().next().unwrap_or(')');
+				ColorError::InvalidCharacter { character, position: start + position }
+			}
+			None => ColorError::UnclosedFunction(name),
+		});
+	};
+	let arguments = match arguments(content, start + open + 1) {
+		Ok(arguments) if (3..=4).contains(&arguments.len()) => arguments,
+		Ok(arguments) => return Err(ColorError::ArgumentCount(name, arguments.len())),
+		Err(count) => return Err(ColorError::ArgumentCount(name, count)),
+	};
+	let alpha = arguments.get(3).map(|&argument| alpha(argument)).transpose()?.unwrap_or(u8::MAX);
+	if name.starts_with("rgb") {
+		Ok(Color { r: channel(arguments[0])?, g: channel(arguments[1])?, b: channel(arguments[2])?, a: alpha })
+	} else {
+		let (h, s, l) = (hue(arguments[0])?, percentage(arguments[1])?, percentage(arguments[2])?);
+		Ok(Color::from(Hsl { h, s, l, a: alpha }))
+	}
+}
+
+/// Splits the arguments of a function, either separated by commas or by whitespaces with the alpha
+/// after a slash. Returns the number of arguments found if the alpha is not the fourth one.
+fn arguments(content: &str, start: usize) -> Result<Vec<Argument<'_>>, usize> {
+	fn split(text: &str, offset: usize, separator: fn(char) -> bool) -> Vec<(&str, usize)> {
+		let mut arguments = Vec::new();
+		let mut position = 0;
+		for part in text.split(separator) {
+			let trimmed = part.trim();
+			let leading = part.len() - part.trim_start().len();
+			if !trimmed.is_empty() || separator(',') {
+				arguments.push((trimmed, offset + position + leading));
+			}
+			position += part.len() + 1;
+		}
+		arguments
+	}
+	if content.contains(',') {
+		return Ok(split(content, start, |c| c == ','));
+	}
+	match content.split_once('/') {
+		Some((values, alpha)) => {
+			let mut arguments = split(values, start, char::is_whitespace);
+			let alpha_start = start + values.len() + 1;
+			let mut alpha = split(alpha, alpha_start, char::is_whitespace);
+			if alpha.is_empty() {
+				alpha.push(("", alpha_start));
+			}
+			if arguments.len() != 3 || alpha.len() != 1 {
+				return Err(arguments.len() + alpha.len());
+			}
+			arguments.extend(alpha);
+			Ok(arguments)
+		}
+		None => Ok(split(content, start, char::is_whitespace)),
+	}
+}
+
+/// Parses a number or a percentage, returning the number and if it's a percentage
+fn number((argument, position): Argument) -> Result<(f32, bool), ColorError> {
+	let invalid = || ColorError::InvalidArgument { argument: argument.to_owned(), position };
+	let (digits, percent) = match argument.strip_suffix('%') {
+		Some(digits) => (digits, true),
+		None => (argument, false),
+	};
+	if !digits.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '.' | '+' | '-')) {
+		return Err(invalid());
+	}
+	let value = digits.parse::<f32>().map_err(|_| invalid())?;
+	match value.is_finite() {
+		true => Ok((value, percent)),
+		false => Err(invalid()),
+	}
+}
+
+/// Parses a red, green or blue channel, from 0 to 255 or as a percentage
+fn channel(argument: Argument) -> Result<u8, ColorError> {
+	let (value, percent) = number(argument)?;
+	let value = if percent { value * 2.55 } else { value };
+	Ok(value.round().clamp(0.0, 255.0) as u8)
+}
+
+/// Parses an alpha value, from 0 to 1 or as a percentage
+fn alpha(argument: Argument) -> Result<u8, ColorError> {
+	let (value, percent) = number(argument)?;
+	let value = if percent { value / 100.0 } else { value };
+	Ok((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// Parses a percentage of the saturation or lightness, from 0 to 1. Plain numbers are handled as
+/// percentages like in CSS Color 4.
+fn percentage(argument: Argument) -> Result<f32, ColorError> {
+	Ok((number(argument)?.0 / 100.0).clamp(0.0, 1.0))
+}
+
+/// Parses a hue in degrees, or in any of the CSS angle units
+fn hue((argument, position): Argument) -> Result<f32, ColorError> {
+	let lowercase = argument.to_ascii_lowercase();
+	let units = [("deg", 1.0), ("grad", 0.9), ("rad", 180.0 / std::f32::consts::PI), ("turn", 360.0)];
+	let (digits, scale) = units.iter()
+		.find_map(|(unit, scale)| lowercase.strip_suffix(unit).map(|digits| (digits, *scale)))
+		.unwrap_or((&lowercase, 1.0));
+	match number((digits, position)) {
+		Ok((value, false)) => Ok((value * scale).rem_euclid(360.0)),
+		_ => Err(ColorError::InvalidArgument { argument: argument.to_owned(), position }),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::color::color_error::ColorError;
+	use crate::color::css::{NAMED_COLORS, parse};
+
+	fn rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+		Color { r, g, b, a }
+	}
+
+	#[test]
+	fn hexadecimal_colors() {
+		assert_eq!(Ok(rgba(0xaa, 0xbb, 0xcc, 0xff)), parse("#abc"));
+		assert_eq!(Ok(rgba(0xaa, 0xbb, 0xcc, 0xdd)), parse("#ABCD"));
+		assert_eq!(Ok(rgba(0x0a, 0x2b, 0x3c, 0xff)), parse("  #0a2B3c "));
+		assert_eq!(Ok(rgba(0x0a, 0x2b, 0x3c, 0x4d)), parse("#0a2b3c4d"));
+		assert_eq!(Err(ColorError::InvalidHexLength(5)), parse("#12345"));
+		assert_eq!(Err(ColorError::InvalidHexLength(9)), parse("#0a2b3c4d5"));
+		assert_eq!(Err(ColorError::InvalidCharacter { character: 'g', position: 4 }), parse(" #12g"));
+	}
+
+	#[test]
+	fn named_colors() {
+		assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+		assert_eq!(Ok(palette::RED), parse("red"));
+		assert_eq!(Ok(rgba(0x66, 0x33, 0x99, 0xff)), parse("RebeccaPurple"));
+		assert_eq!(Ok(rgba(0, 0x80, 0, 0xff)), parse("green"));
+		assert_eq!(Ok(palette::CORAL), parse("coral"));
+		assert_eq!(Ok(palette::GREEN), parse("lime"));
+		assert_eq!(palette::LIME, palette::GREEN);
+		assert_eq!(Ok(rgba(0, 0, 0, 0)), parse("transparent"));
+		assert_eq!(Err(ColorError::UnknownName("reddish".to_owned())), parse("reddish"));
+		assert_eq!(Err(ColorError::Empty), parse("  "));
+	}
+
+	#[test]
+	fn rgb_functions() {
+		assert_eq!(Ok(rgba(255, 0, 128, 255)), parse("rgb(255, 0, 128)"));
+		assert_eq!(Ok(rgba(255, 0, 128, 128)), parse("rgba(255,0,128,0.5)"));
+		assert_eq!(Ok(rgba(255, 0, 128, 64)), parse("RGB(255 0 128 / 25%)"));
+		assert_eq!(Ok(rgba(255, 128, 0, 255)), parse("rgb(100% 50% 0%)"));
+		assert_eq!(Ok(rgba(255, 0, 0, 255)), parse("rgb(300, -20, 0.4, 2)"));
+		assert_eq!(Ok(rgba(1, 2, 3, 255)), parse("rgba(1 2 3)"));
+	}
+
+	#[test]
+	fn hsl_functions() {
+		assert_eq!(Ok(palette::RED), parse("hsl(0, 100%, 50%)"));
+		assert_eq!(Ok(rgba(0, 255, 0, 255)), parse("hsl(120deg 100% 50%)"));
+		assert_eq!(Ok(rgba(0, 0, 255, 128)), parse("hsla(240, 100%, 50%, .5)"));
+		assert_eq!(Ok(rgba(0, 0, 255, 255)), parse("hsl(-0.3333turn 100 50)"));
+		assert_eq!(Ok(rgba(0, 255, 255, 255)), parse("hsl(3.14159rad 100% 50%)"));
+		assert_eq!(Ok(rgba(191, 64, 64, 255)), parse("hsl(0 50% 50%)"));
+		assert_eq!(Ok(rgba(128, 128, 128, 255)), parse("hsl(200grad 0% 50%)"));
+	}
+
+	#[test]
+	fn invalid_functions() {
+		assert_eq!(Err(ColorError::UnknownFunction("lab".to_owned())), parse("lab(50% 40 59)"));
+		assert_eq!(Err(ColorError::UnclosedFunction("rgb".to_owned())), parse("rgb(1, 2, 3"));
+		assert_eq!(Err(ColorError::InvalidCharacter { character: 'x', position: 12 }), parse("rgb(1, 2, 3)x"));
+		assert_eq!(Err(ColorError::ArgumentCount("rgb".to_owned(), 2)), parse("rgb(1, 2)"));
+		assert_eq!(Err(ColorError::ArgumentCount("hsla".to_owned(), 5)), parse("hsla(1 2 3 / 4 5)"));
+		assert_eq!(Err(ColorError::ArgumentCount("rgb".to_owned(), 3)), parse("rgb(1 2 / 3)"));
+		assert_eq!(Err(ColorError::InvalidArgument { argument: "".to_owned(), position: 11 }), parse("rgb(1 2 3 /)"));
+		assert_eq!(Err(ColorError::InvalidArgument { argument: "red".to_owned(), position: 10 }),
+		           parse("rgb(1, 2, red)"));
+		assert_eq!(Err(ColorError::InvalidArgument { argument: "".to_owned(), position: 6 }), parse("rgb(1,,3)"));
+		assert_eq!(Err(ColorError::InvalidArgument { argument: "inf".to_owned(), position: 8 }),
+		           parse("rgb(1 2 inf)"));
+		assert_eq!(Err(ColorError::InvalidArgument { argument: "90%".to_owned(), position: 4 }),
+		           parse("hsl(90% 50% 50%)"));
+	}
+
+}
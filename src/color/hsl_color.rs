@@ -0,0 +1,208 @@
+//! Cylindrical representations of the RGB colors: HSL, HSV and HWB. The hue is always expressed in
+//! degrees, from 0 to 360, and the rest of the components go from 0 to 1. The alpha is kept as is.
+use crate::color::Color;
+
+/// Representation of a color by its hue, saturation and lightness
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::{Color, Hsl, palette};
+/// let hsl = Hsl::from(&palette::RED);
+/// assert_eq!(Hsl { h: 0.0, s: 1.0, l: 0.5, a: 255 }, hsl);
+/// assert_eq!(Color { r: 0, g: 255, b: 255, a: 255 }, Color::from(Hsl { h: 180.0, ..hsl }));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hsl {
+	pub h: f32,
+	pub s: f32,
+	pub l: f32,
+	pub a: u8,
+}
+
+/// Representation of a color by its hue, saturation and value
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::{Color, Hsv};
+/// let hsv = Hsv::from(&Color { r: 0, g: 0, b: 128, a: 255 });
+/// assert_eq!(240.0, hsv.h);
+/// assert_eq!(1.0, hsv.s);
+/// assert_eq!(Color { r: 0, g: 0, b: 255, a: 255 }, Color::from(Hsv { v: 1.0, ..hsv }));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hsv {
+	pub h: f32,
+	pub s: f32,
+	pub v: f32,
+	pub a: u8,
+}
+
+/// Representation of a color by its hue, whiteness and blackness
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::{Color, Hwb};
+/// let gray = Color::from(Hwb { h: 0.0, w: 0.5, b: 0.5, a: 255 });
+/// assert_eq!(Color { r: 128, g: 128, b: 128, a: 255 }, gray);
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hwb {
+	pub h: f32,
+	pub w: f32,
+	pub b: f32,
+	pub a: u8,
+}
+
+impl From<&Color> for Hsv {
+	fn from(color: &Color) -> Self {
+		let (r, g, b) = (unit(color.r), unit(color.g), unit(color.b));
+		let max = r.max(g).max(b);
+		let delta = max - r.min(g).min(b);
+		let h = if delta == 0.0 {
+			0.0
+		} else if max == r {
+			(g - b) / delta
+		} else if max == g {
+			(b - r) / delta + 2.0
+		} else {
+			(r - g) / delta + 4.0
+		};
+		Hsv {
+			h: (h * 60.0).rem_euclid(360.0),
+			s: if max == 0.0 { 0.0 } else { delta / max },
+			v: max,
+			a: color.a,
+		}
+	}
+}
+
+impl From<&Color> for Hsl {
+	fn from(color: &Color) -> Self {
+		Hsl::from(Hsv::from(color))
+	}
+}
+
+impl From<&Color> for Hwb {
+	fn from(color: &Color) -> Self {
+		Hwb::from(Hsv::from(color))
+	}
+}
+
+impl From<Hsv> for Color {
+	fn from(hsv: Hsv) -> Self {
+		let (h, s, v) = (hsv.h.rem_euclid(360.0), hsv.s.clamp(0.0, 1.0), hsv.v.clamp(0.0, 1.0));
+		let channel = |n: f32| {
+			let k = (n + h / 60.0) % 6.0;
+			byte(v - v * s * k.min(4.0 - k).clamp(0.0, 1.0))
+		};
+		Color { r: channel(5.0), g: channel(3.0), b: channel(1.0), a: hsv.a }
+	}
+}
+
+impl From<Hsl> for Color {
+	fn from(hsl: Hsl) -> Self {
+		let (h, s, l) = (hsl.h.rem_euclid(360.0), hsl.s.clamp(0.0, 1.0), hsl.l.clamp(0.0, 1.0));
+		let channel = |n: f32| {
+			let k = (n + h / 30.0) % 12.0;
+			byte(l - s * l.min(1.0 - l) * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0))
+		};
+		Color { r: channel(0.0), g: channel(8.0), b: channel(4.0), a: hsl.a }
+	}
+}
+
+impl From<Hwb> for Color {
+	fn from(hwb: Hwb) -> Self {
+		Color::from(Hsv::from(hwb))
+	}
+}
+
+impl From<Hsv> for Hsl {
+	fn from(hsv: Hsv) -> Self {
+		let l = hsv.v * (1.0 - hsv.s / 2.0);
+		let s = if l == 0.0 || l == 1.0 { 0.0 } else { (hsv.v - l) / l.min(1.0 - l) };
+		Hsl { h: hsv.h, s, l, a: hsv.a }
+	}
+}
+
+impl From<Hsl> for Hsv {
+	fn from(hsl: Hsl) -> Self {
+		let v = hsl.l + hsl.s * hsl.l.min(1.0 - hsl.l);
+		let s = if v == 0.0 { 0.0 } else { 2.0 * (1.0 - hsl.l / v) };
+		Hsv { h: hsl.h, s, v, a: hsl.a }
+	}
+}
+
+impl From<Hsv> for Hwb {
+	fn from(hsv: Hsv) -> Self {
+		Hwb { h: hsv.h, w: (1.0 - hsv.s) * hsv.v, b: 1.0 - hsv.v, a: hsv.a }
+	}
+}
+
+impl From<Hwb> for Hsv {
+	fn from(hwb: Hwb) -> Self {
+		let (w, b) = (hwb.w.clamp(0.0, 1.0), hwb.b.clamp(0.0, 1.0));
+		// Whiteness and blackness adding up to more than 1 are normalized into a gray
+		let (w, b) = if w + b > 1.0 { (w / (w + b), b / (w + b)) } else { (w, b) };
+		let v = 1.0 - b;
+		Hsv { h: hwb.h, s: if v == 0.0 { 0.0 } else { 1.0 - w / v }, v, a: hwb.a }
+	}
+}
+
+/// Scales a channel to the range from 0 to 1
+fn unit(channel: u8) -> f32 {
+	channel as f32 / 255.0
+}
+
+/// Scales a component from 0 to 1 back to a channel
+fn byte(value: f32) -> u8 {
+	(value * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, Hsl, Hsv, Hwb, palette};
+
+	#[test]
+	fn from_rgb() {
+		let coral = Color { r: 255, g: 127, b: 80, a: 200 };
+		let hsl = Hsl::from(&coral);
+		assert!((hsl.h - 16.11).abs() < 0.01);
+		assert_eq!(1.0, hsl.s);
+		assert!((hsl.l - 0.657).abs() < 0.001);
+		assert_eq!(200, hsl.a);
+		let hsv = Hsv::from(&coral);
+		assert!((hsv.s - 0.686).abs() < 0.001);
+		assert_eq!(1.0, hsv.v);
+		let hwb = Hwb::from(&coral);
+		assert!((hwb.w - 0.314).abs() < 0.001);
+		assert_eq!(0.0, hwb.b);
+		assert_eq!(Hsl { h: 0.0, s: 0.0, l: 1.0, a: 255 }, Hsl::from(&palette::WHITE));
+		assert_eq!(Hsv { h: 0.0, s: 0.0, v: 0.0, a: 255 }, Hsv::from(&palette::BLACK));
+		assert_eq!(240.0, Hsl::from(&palette::BLUE).h);
+	}
+
+	#[test]
+	fn round_trips() {
+		for r in (0..=255).step_by(17) {
+			for g in (0..=255).step_by(51) {
+				for b in (0..=255).step_by(85) {
+					let color = Color { r, g, b, a: r };
+					assert_eq!(color, Color::from(Hsl::from(&color)));
+					assert_eq!(color, Color::from(Hsv::from(&color)));
+					assert_eq!(color, Color::from(Hwb::from(&color)));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn out_of_range() {
+		assert_eq!(palette::RED, Color::from(Hsl { h: 720.0, s: 2.0, l: 0.5, a: 255 }));
+		assert_eq!(palette::BLUE, Color::from(Hsv { h: -120.0, s: 1.0, v: 1.0, a: 255 }));
+		assert_eq!(Color { r: 85, g: 85, b: 85, a: 255 }, Color::from(Hwb { h: 0.0, w: 0.5, b: 1.5, a: 255 }));
+	}
+
+}
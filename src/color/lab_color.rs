@@ -0,0 +1,305 @@
+//! Perceptual representations of the colors, where the distances between colors follow how
+//! different they look: CIELAB, its cylindrical form LCh, and OKLab. CIELAB is relative to the D65
+//! white point, the one of sRGB.
+// The conversion matrices are kept as published, even if some digits are beyond the f32 precision
+#![allow(clippy::excessive_precision)]
+use crate::color::{Color, LinearColor};
+
+/// White point D65 in the XYZ space
+const WHITE: [f32; 3] = [0.95047, 1.0, 1.08883];
+
+/// Threshold of the linear segment of the CIELAB lightness
+const EPSILON: f32 = 216.0 / 24389.0;
+
+/// Slope of the linear segment of the CIELAB lightness
+const KAPPA: f32 = 24389.0 / 27.0;
+
+/// Representation of a color in the CIELAB space, with its lightness from 0 to 100 and its green
+/// to red (`a`) and blue to yellow (`b`) components usually between -128 and 127
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::{Color, Lab, palette};
+/// let red = Lab::from(&palette::RED);
+/// assert_eq!((53, 80, 67), (red.l.round() as i32, red.a.round() as i32, red.b.round() as i32));
+/// assert_eq!(palette::RED, Color::from(red));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lab {
+	pub l: f32,
+	pub a: f32,
+	pub b: f32,
+	pub alpha: u8,
+}
+
+/// Representation of a color in the cylindrical form of CIELAB, with its lightness from 0 to 100,
+/// its chroma from 0 to around 150 and its hue in degrees
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lch {
+	pub l: f32,
+	pub c: f32,
+	pub h: f32,
+	pub alpha: u8,
+}
+
+/// Representation of a color in the OKLab space, with its lightness from 0 to 1 and its `a` and
+/// `b` components usually between -0.4 and 0.4. It predicts the lightness, chroma and hue better
+/// than CIELAB, which makes it the best choice to interpolate colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Oklab {
+	pub l: f32,
+	pub a: f32,
+	pub b: f32,
+	pub alpha: u8,
+}
+
+impl Lab {
+
+	/// Returns the CIE76 color difference with the other color, their euclidean distance. A
+	/// difference around 2.3 is the smallest noticeable one.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Lab;
+	/// let gray = Lab { l: 50.0, a: 0.0, b: 0.0, alpha: 255 };
+	/// assert_eq!(5.0, gray.delta_e76(&Lab { l: 53.0, a: 4.0, b: 0.0, alpha: 255 }));
+	/// ```
+	///
+	pub fn delta_e76(&self, other: &Lab) -> f32 {
+		((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2)).sqrt()
+	}
+
+	/// Returns the CIEDE2000 color difference with the other color, which corrects the
+	/// non-uniformities of CIELAB in the blues, the grays and the saturated colors. A difference
+	/// below 1 is not noticeable.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Lab;
+	/// let blue = Lab { l: 50.0, a: 2.6772, b: -79.7751, alpha: 255 };
+	/// let difference = blue.delta_e2000(&Lab { l: 50.0, a: 0.0, b: -82.7485, alpha: 255 });
+	/// assert!((difference - 2.0425).abs() < 0.001);
+	/// ```
+	///
+	pub fn delta_e2000(&self, other: &Lab) -> f32 {
+		let chroma = |lab: &Lab| (lab.a * lab.a + lab.b * lab.b).sqrt();
+		let mean_c7 = ((chroma(self) + chroma(other)) / 2.0).powi(7);
+		let g = 0.5 * (1.0 - (mean_c7 / (mean_c7 + 25f32.powi(7))).sqrt());
+		let prime = |lab: &Lab| {
+			let a = (1.0 + g) * lab.a;
+			let c = (a * a + lab.b * lab.b).sqrt();
+			let h = if c == 0.0 { 0.0 } else { lab.b.atan2(a).to_degrees().rem_euclid(360.0) };
+			(c, h)
+		};
+		let ((c1, h1), (c2, h2)) = (prime(self), prime(other));
+
+		let delta_l = other.l - self.l;
+		let delta_c = c2 - c1;
+		let delta_h = match h2 - h1 {
+			_ if c1 * c2 == 0.0 => 0.0,
+			d if d > 180.0 => d - 360.0,
+			d if d < -180.0 => d + 360.0,
+			d => d,
+		};
+		let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+		let mean_l = (self.l + other.l) / 2.0;
+		let mean_c = (c1 + c2) / 2.0;
+		let mean_h = match h1 + h2 {
+			sum if c1 * c2 == 0.0 => sum,
+			sum if (h1 - h2).abs() <= 180.0 => sum / 2.0,
+			sum if sum < 360.0 => (sum + 360.0) / 2.0,
+			sum => (sum - 360.0) / 2.0,
+		};
+
+		let cos = |degrees: f32| degrees.to_radians().cos();
+		let t = 1.0 - 0.17 * cos(mean_h - 30.0) + 0.24 * cos(2.0 * mean_h) + 0.32 * cos(3.0 * mean_h + 6.0)
+			- 0.20 * cos(4.0 * mean_h - 63.0);
+		let delta_theta = 30.0 * (-((mean_h - 275.0) / 25.0).powi(2)).exp();
+		let mean_c7 = mean_c.powi(7);
+		let r_c = 2.0 * (mean_c7 / (mean_c7 + 25f32.powi(7))).sqrt();
+		let s_l = 1.0 + 0.015 * (mean_l - 50.0).powi(2) / (20.0 + (mean_l - 50.0).powi(2)).sqrt();
+		let s_c = 1.0 + 0.045 * mean_c;
+		let s_h = 1.0 + 0.015 * mean_c * t;
+		let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+		let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+		(l * l + c * c + h * h + r_t * c * h).sqrt()
+	}
+
+}
+
+impl Oklab {
+
+	/// Returns the color at the given point, from 0 to 1, of the straight line between this color
+	/// and the other one. The perceived lightness and chroma change smoothly along the line,
+	/// without the dark or grayish middles of the sRGB interpolation.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, Oklab, palette};
+	/// let middle = Oklab::from(&palette::BLUE).lerp(&Oklab::from(&palette::WHITE), 0.5);
+	/// assert_eq!(Color { r: 116, g: 163, b: 255, a: 255 }, Color::from(middle));
+	/// ```
+	///
+	pub fn lerp(&self, other: &Oklab, t: f32) -> Oklab {
+		let mix = |from: f32, to: f32| from + (to - from) * t;
+		Oklab {
+			l: mix(self.l, other.l),
+			a: mix(self.a, other.a),
+			b: mix(self.b, other.b),
+			alpha: mix(self.alpha as f32, other.alpha as f32).round().clamp(0.0, 255.0) as u8,
+		}
+	}
+
+}
+
+impl From<&Color> for Lab {
+	fn from(color: &Color) -> Self {
+		let linear = LinearColor::from(color);
+		let x = 0.4124564 * linear.r + 0.3575761 * linear.g + 0.1804375 * linear.b;
+		let y = 0.2126729 * linear.r + 0.7151522 * linear.g + 0.0721750 * linear.b;
+		let z = 0.0193339 * linear.r + 0.1191920 * linear.g + 0.9503041 * linear.b;
+		let f = |value: f32| if value > EPSILON { value.cbrt() } else { (KAPPA * value + 16.0) / 116.0 };
+		let (fx, fy, fz) = (f(x / WHITE[0]), f(y / WHITE[1]), f(z / WHITE[2]));
+		Lab { l: 116.0 * fy - 16.0, a: 500.0 * (fx - fy), b: 200.0 * (fy - fz), alpha: color.a }
+	}
+}
+
+impl From<Lab> for Color {
+	fn from(lab: Lab) -> Self {
+		let fy = (lab.l + 16.0) / 116.0;
+		let (fx, fz) = (fy + lab.a / 500.0, fy - lab.b / 200.0);
+		let f = |value: f32| if value.powi(3) > EPSILON { value.powi(3) } else { (116.0 * value - 16.0) / KAPPA };
+		let (x, y, z) = (f(fx) * WHITE[0], f(fy) * WHITE[1], f(fz) * WHITE[2]);
+		Color::from(LinearColor {
+			r: 3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+			g: -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+			b: 0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+			a: lab.alpha as f32 / 255.0,
+		})
+	}
+}
+
+impl From<&Color> for Lch {
+	fn from(color: &Color) -> Self {
+		Lch::from(Lab::from(color))
+	}
+}
+
+impl From<Lch> for Color {
+	fn from(lch: Lch) -> Self {
+		Color::from(Lab::from(lch))
+	}
+}
+
+impl From<Lab> for Lch {
+	fn from(lab: Lab) -> Self {
+		let c = (lab.a * lab.a + lab.b * lab.b).sqrt();
+		let h = if c == 0.0 { 0.0 } else { lab.b.atan2(lab.a).to_degrees().rem_euclid(360.0) };
+		Lch { l: lab.l, c, h, alpha: lab.alpha }
+	}
+}
+
+impl From<Lch> for Lab {
+	fn from(lch: Lch) -> Self {
+		let (sin, cos) = lch.h.to_radians().sin_cos();
+		Lab { l: lch.l, a: lch.c * cos, b: lch.c * sin, alpha: lch.alpha }
+	}
+}
+
+impl From<&Color> for Oklab {
+	fn from(color: &Color) -> Self {
+		let linear = LinearColor::from(color);
+		let l = (0.4122214708 * linear.r + 0.5363325363 * linear.g + 0.0514459929 * linear.b).cbrt();
+		let m = (0.2119034982 * linear.r + 0.6806995451 * linear.g + 0.1073969566 * linear.b).cbrt();
+		let s = (0.0883024619 * linear.r + 0.2817188376 * linear.g + 0.6299787005 * linear.b).cbrt();
+		Oklab {
+			l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+			a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+			b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+			alpha: color.a,
+		}
+	}
+}
+
+impl From<Oklab> for Color {
+	fn from(oklab: Oklab) -> Self {
+		let l = (oklab.l + 0.3963377774 * oklab.a + 0.2158037573 * oklab.b).powi(3);
+		let m = (oklab.l - 0.1055613458 * oklab.a - 0.0638541728 * oklab.b).powi(3);
+		let s = (oklab.l - 0.0894841775 * oklab.a - 1.2914855480 * oklab.b).powi(3);
+		Color::from(LinearColor {
+			r: 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+			g: -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+			b: -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+			a: oklab.alpha as f32 / 255.0,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, Lab, Lch, Oklab, palette};
+
+	fn lab(l: f32, a: f32, b: f32) -> Lab {
+		Lab { l, a, b, alpha: 255 }
+	}
+
+	#[test]
+	fn conversions() {
+		let white = Lab::from(&palette::WHITE);
+		assert!((white.l - 100.0).abs() < 0.01 && white.a.abs() < 0.01 && white.b.abs() < 0.01);
+		assert_eq!(0.0, Lab::from(&palette::BLACK).l);
+		let blue = Lch::from(&palette::BLUE);
+		assert!((blue.l - 32.30).abs() < 0.01);
+		assert!((blue.c - 133.81).abs() < 0.01);
+		assert!((blue.h - 306.28).abs() < 0.01);
+		let orange = Oklab::from(&Color { r: 255, g: 165, b: 0, a: 255 });
+		assert!((orange.l - 0.7927).abs() < 0.001);
+		assert!((orange.a - 0.0566).abs() < 0.001);
+		assert!((orange.b - 0.1614).abs() < 0.001);
+	}
+
+	#[test]
+	fn round_trips() {
+		for r in (0..=255).step_by(51) {
+			for g in (0..=255).step_by(51) {
+				for b in (0..=255).step_by(51) {
+					let color = Color { r, g, b, a: g };
+					assert_eq!(color, Color::from(Lab::from(&color)));
+					assert_eq!(color, Color::from(Lch::from(&color)));
+					assert_eq!(color, Color::from(Oklab::from(&color)));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn differences() {
+		// Reference pairs of Sharma, Wu and Dalal
+		let pairs = [
+			(lab(50.0, 3.1571, -77.2803), lab(50.0, 0.0, -82.7485), 2.8615),
+			(lab(50.0, 2.5, 0.0), lab(50.0, 0.0, -2.5), 4.3065),
+			(lab(50.0, 2.5, 0.0), lab(73.0, 25.0, -18.0), 27.1492),
+			(lab(50.0, 2.5, 0.0), lab(50.0, 3.1736, 0.5854), 1.0),
+			(lab(2.0776, 0.0795, -1.135), lab(0.9033, -0.0636, -0.5514), 0.9082),
+		];
+		for (first, second, difference) in pairs {
+			assert!((first.delta_e2000(&second) - difference).abs() < 0.001);
+			assert!((second.delta_e2000(&first) - difference).abs() < 0.001);
+		}
+		assert_eq!(0.0, lab(40.0, 10.0, -5.0).delta_e2000(&lab(40.0, 10.0, -5.0)));
+		assert!((lab(0.0, 3.0, 4.0).delta_e76(&lab(0.0, 0.0, 0.0)) - 5.0).abs() < 1e-6);
+	}
+
+	#[test]
+	fn interpolation() {
+		let (red, green) = (Oklab::from(&palette::RED), Oklab::from(&palette::GREEN));
+		assert_eq!(palette::RED, Color::from(red.lerp(&green, 0.0)));
+		assert_eq!(palette::GREEN, Color::from(red.lerp(&green, 1.0)));
+		let transparent = Oklab { alpha: 0, ..red.clone() };
+		assert_eq!(Color { r: 255, g: 0, b: 0, a: 128 }, Color::from(red.lerp(&transparent, 0.5)));
+	}
+
+}
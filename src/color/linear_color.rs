@@ -0,0 +1,159 @@
+//! Linear-light representation of the colors. The channels of a [Color] are encoded with the sRGB
+//! gamma, so mixing them directly gives darker and muddier results than mixing the actual light.
+
+use crate::color::Color;
+
+/// Representation of a color by the intensity of light of each channel, from 0 to 1, without the
+/// sRGB gamma encoding. The alpha is kept from 0 to 1 too.
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::{Color, LinearColor, palette};
+/// let gray = LinearColor::from(&Color { r: 188, g: 188, b: 188, a: 255 });
+/// assert!((gray.r - 0.5).abs() < 0.01);
+/// assert_eq!(palette::WHITE, Color::from(LinearColor { r: 1.0, g: 1.0, b: 1.0, a: 1.0 }));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearColor {
+	pub r: f32,
+	pub g: f32,
+	pub b: f32,
+	pub a: f32,
+}
+
+impl LinearColor {
+
+	/// Returns the color resulting of painting this color over the background one, mixing their
+	/// light according to their alpha values
+	pub fn blend(&self, background: &LinearColor) -> LinearColor {
+		let dst_a = background.a * (1.0 - self.a);
+		let a = self.a + dst_a;
+		if a == 0.0 {
+			return LinearColor { r: 0.0, g: 0.0, b: 0.0, a: 0.0 };
+		}
+		let mix = |src: f32, dst: f32| (src * self.a + dst * dst_a) / a;
+		LinearColor {
+			r: mix(self.r, background.r),
+			g: mix(self.g, background.g),
+			b: mix(self.b, background.b),
+			a,
+		}
+	}
+
+}
+
+impl From<&Color> for LinearColor {
+	fn from(color: &Color) -> Self {
+		LinearColor {
+			r: srgb_to_linear(color.r as f32 / 255.0),
+			g: srgb_to_linear(color.g as f32 / 255.0),
+			b: srgb_to_linear(color.b as f32 / 255.0),
+			a: color.a as f32 / 255.0,
+		}
+	}
+}
+
+impl From<LinearColor> for Color {
+	fn from(color: LinearColor) -> Self {
+		let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+		Color {
+			r: byte(linear_to_srgb(color.r)),
+			g: byte(linear_to_srgb(color.g)),
+			b: byte(linear_to_srgb(color.b)),
+			a: byte(color.a),
+		}
+	}
+}
+
+/// Color space where a canvas mixes the colors when blending pixels, which includes the
+/// translucent colors and the anti-aliased edges of the text. sRGB is the default, the linear
+/// space gives brighter and smoother gradients and edges.
+///
+/// It only applies to painting the pixels over the frame. The [bilinear](crate::image::Filter)
+/// sampling of the images always interpolates their pixels in sRGB, and the canvases without a
+/// blend space setting, like [IndexedCanvas], blend in sRGB.
+///
+/// [IndexedCanvas]: crate::canvas::indexed::IndexedCanvas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendSpace {
+	/// Mixes the sRGB encoded channels, like most image editors and browsers do by default
+	#[default]
+	Srgb,
+	/// Mixes the light of the channels, giving physically correct gradients and edges
+	Linear,
+}
+
+impl BlendSpace {
+
+	/// Returns the color resulting of painting the color over the background one in this space
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{BlendSpace, Color, palette};
+	/// let translucent_white = Color { r: 255, g: 255, b: 255, a: 128 };
+	/// assert_eq!(Color { r: 128, g: 128, b: 128, a: 255 },
+	///            BlendSpace::Srgb.blend(&translucent_white, &palette::BLACK));
+	/// assert_eq!(Color { r: 188, g: 188, b: 188, a: 255 },
+	///            BlendSpace::Linear.blend(&translucent_white, &palette::BLACK));
+	/// ```
+	///
+	pub fn blend(&self, color: &Color, background: &Color) -> Color {
+		match self {
+			BlendSpace::Srgb => color.blend(background),
+			BlendSpace::Linear => Color::from(LinearColor::from(color).blend(&LinearColor::from(background))),
+		}
+	}
+
+}
+
+/// Decodes a sRGB channel, from 0 to 1, into its linear light intensity
+pub fn srgb_to_linear(value: f32) -> f32 {
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+/// Encodes a linear light intensity, from 0 to 1, into its sRGB channel
+pub fn linear_to_srgb(value: f32) -> f32 {
+	if value <= 0.0031308 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{BlendSpace, Color, LinearColor, palette};
+	use crate::color::linear_color::{linear_to_srgb, srgb_to_linear};
+
+	#[test]
+	fn gamma() {
+		assert_eq!(0.0, srgb_to_linear(0.0));
+		assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+		assert!((srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+		assert!((linear_to_srgb(0.214) - 0.5).abs() < 0.001);
+		assert!((srgb_to_linear(0.02) - 0.02 / 12.92).abs() < 1e-6);
+	}
+
+	#[test]
+	fn round_trips() {
+		for channel in 0..=255 {
+			let color = Color { r: channel, g: 255 - channel, b: channel / 2, a: channel };
+			assert_eq!(color, Color::from(LinearColor::from(&color)));
+		}
+	}
+
+	#[test]
+	fn linear_blending() {
+		let translucent = Color { r: 255, g: 0, b: 0, a: 128 };
+		assert_eq!(Color { r: 188, g: 0, b: 187, a: 255 }, BlendSpace::Linear.blend(&translucent, &palette::BLUE));
+		assert_eq!(palette::RED, BlendSpace::Linear.blend(&palette::RED, &palette::BLUE));
+		assert_eq!(palette::BLUE, BlendSpace::Linear.blend(&Color { r: 9, g: 9, b: 9, a: 0 }, &palette::BLUE));
+		assert_eq!(Color { r: 255, g: 0, b: 0, a: 192 }, BlendSpace::Linear.blend(&translucent, &translucent));
+	}
+
+}
@@ -1,8 +1,19 @@
 //! Color tools to draw on the canvas
 pub use rgba_color::Color;
 pub use color_builder::ColorBuilder;
+pub use hsl_color::{Hsl, Hsv, Hwb};
+pub use linear_color::{BlendSpace, LinearColor};
+pub use premultiplied_color::PremultipliedColor;
+pub use lab_color::{Lab, Lch, Oklab};
+pub use color_ramp::{ColorRamp, Interpolation};
 
 mod rgba_color;
-mod color_error;
+mod hsl_color;
+pub mod linear_color;
+pub(crate) mod premultiplied_color;
+mod lab_color;
+mod color_ramp;
+pub mod color_error;
+mod css;
 pub mod palette;
 mod color_builder;
\ No newline at end of file
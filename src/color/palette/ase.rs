@@ -0,0 +1,150 @@
+//! Decoding of the Adobe Swatch Exchange palettes
+use crate::color::{Color, Lab};
+use crate::color::palette::{Palette, PaletteFormat};
+use crate::color::palette::palette_error::PaletteError;
+
+const MAGIC: &[u8] = b"ASEF";
+const HEADER_SIZE: usize = 12;
+const COLOR_BLOCK: u16 = 0x0001;
+
+fn malformed(reason: &str) -> PaletteError {
+	PaletteError::Malformed(PaletteFormat::Ase, reason.to_owned())
+}
+
+/// Reader of the big-endian values of the file
+struct Reader<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+
+	fn take(&mut self, count: usize) -> Result<&'a [u8], PaletteError> {
+		if self.bytes.len() < count {
+			return Err(malformed("truncated block"));
+		}
+		let (taken, rest) = self.bytes.split_at(count);
+		self.bytes = rest;
+		Ok(taken)
+	}
+
+	fn u16(&mut self) -> Result<u16, PaletteError> {
+		self.take(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+	}
+
+	fn u32(&mut self) -> Result<u32, PaletteError> {
+		self.take(4).map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+	}
+
+	fn f32(&mut self) -> Result<f32, PaletteError> {
+		self.u32().map(f32::from_bits)
+	}
+
+}
+
+/// Decodes an ASE palette. The groups are flattened and their names are dropped, and the colors
+/// in CMYK, LAB and grayscale are converted to RGB.
+pub fn decode(bytes: &[u8]) -> Result<Palette, PaletteError> {
+	if bytes.len() < HEADER_SIZE || !bytes.starts_with(MAGIC) {
+		return Err(malformed("truncated header"));
+	}
+	let mut reader = Reader { bytes: &bytes[MAGIC.len() + 4..] };
+	let count = reader.u32()?;
+
+	let mut palette = Palette::new();
+	for _ in 0..count {
+		let kind = reader.u16()?;
+		let length = reader.u32()? as usize;
+		let mut block = Reader { bytes: reader.take(length)? };
+		if kind == COLOR_BLOCK {
+			let (color, name) = color(&mut block)?;
+			palette.push(color, Some(name).filter(|name| !name.is_empty()));
+		}
+	}
+	Ok(palette)
+}
+
+/// Reads the name and the color of a color block
+fn color(block: &mut Reader) -> Result<(Color, String), PaletteError> {
+	let length = block.u16()? as usize;
+	let units = block.take(length * 2)?
+		.chunks_exact(2)
+		.map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+		.take_while(|&unit| unit != 0)
+		.collect::<Vec<_>>();
+	let name = String::from_utf16(&units).map_err(|_| malformed("invalid color name"))?;
+
+	let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+	let color = match block.take(4)? {
+		b"RGB " => Color { r: byte(block.f32()?), g: byte(block.f32()?), b: byte(block.f32()?), a: u8::MAX },
+		b"CMYK" => {
+			let (c, m, y, k) = (block.f32()?, block.f32()?, block.f32()?, block.f32()?);
+			let ink = |value: f32| byte((1.0 - value) * (1.0 - k));
+			Color { r: ink(c), g: ink(m), b: ink(y), a: u8::MAX }
+		}
+		b"LAB " => Color::from(Lab { l: block.f32()? * 100.0, a: block.f32()?, b: block.f32()?, alpha: u8::MAX }),
+		b"Gray" => {
+			let gray = byte(block.f32()?);
+			Color { r: gray, g: gray, b: gray, a: u8::MAX }
+		}
+		_ => return Err(malformed("unknown color model")),
+	};
+	Ok((color, name))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::color::palette::ase::decode;
+
+	/// Returns a color block with the name and the values in the color model
+	fn block(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+		let mut content = (name.encode_utf16().count() as u16 + 1).to_be_bytes().to_vec();
+		content.extend(name.encode_utf16().chain([0]).flat_map(u16::to_be_bytes));
+		content.extend(model);
+		content.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+		content.extend(2u16.to_be_bytes());
+		let mut block = vec![0, 1];
+		block.extend((content.len() as u32).to_be_bytes());
+		block.extend(content);
+		block
+	}
+
+	fn file(blocks: &[Vec<u8>]) -> Vec<u8> {
+		let mut bytes = b"ASEF\0\x01\0\0".to_vec();
+		bytes.extend((blocks.len() as u32).to_be_bytes());
+		bytes.extend(blocks.concat());
+		bytes
+	}
+
+	#[test]
+	fn decode_palettes() {
+		let group_start = [vec![0xc0, 0x01, 0, 0, 0, 4], vec![0, 1, 0, 0]].concat();
+		let group_end = vec![0xc0, 0x02, 0, 0, 0, 0];
+		let bytes = file(&[
+			group_start,
+			block("Red", b"RGB ", &[1.0, 0.0, 0.0]),
+			block("Ínk", b"CMYK", &[0.0, 1.0, 1.0, 0.5]),
+			block("", b"Gray", &[0.5]),
+			block("White", b"LAB ", &[1.0, 0.0, 0.0]),
+			group_end,
+		]);
+		let palette = decode(&bytes).unwrap();
+		assert_eq!(&[palette::RED, Color { r: 128, g: 0, b: 0, a: 255 }, Color { r: 128, g: 128, b: 128, a: 255 },
+		             palette::WHITE], palette.colors());
+		assert_eq!(Some("Ínk"), palette.name(1));
+		assert_eq!(None, palette.name(2));
+	}
+
+	#[test]
+	fn malformed_palettes() {
+		assert!(decode(b"ASEF\0\x01").is_err());
+		let mut truncated = file(&[block("Red", b"RGB ", &[1.0, 0.0, 0.0])]);
+		truncated.pop();
+		assert!(decode(&truncated).is_err());
+		assert!(decode(&file(&[block("Red", b"HSV ", &[1.0, 0.0, 0.0])])).is_err());
+		let mut missing = file(&[]);
+		missing[11] = 1;
+		assert!(decode(&missing).is_err());
+	}
+
+}
@@ -0,0 +1,338 @@
+use std::ops::Range;
+use std::path::Path;
+use crate::color::Color;
+use crate::color::css::NAMED_COLORS;
+use crate::color::palette::{decode, PaletteFormat};
+use crate::color::palette::palette_error::PaletteError;
+
+/// Colors of the CGA, by index, with their usual names
+const CGA: [(u32, &str); 16] = [
+	(0x000000, "Black"), (0x0000aa, "Blue"), (0x00aa00, "Green"), (0x00aaaa, "Cyan"),
+	(0xaa0000, "Red"), (0xaa00aa, "Magenta"), (0xaa5500, "Brown"), (0xaaaaaa, "Light Gray"),
+	(0x555555, "Dark Gray"), (0x5555ff, "Light Blue"), (0x55ff55, "Light Green"), (0x55ffff, "Light Cyan"),
+	(0xff5555, "Light Red"), (0xff55ff, "Light Magenta"), (0xffff55, "Yellow"), (0xffffff, "White"),
+];
+
+/// Indices of the EGA colors used by default, matching the CGA ones
+const EGA_DEFAULTS: [usize; 16] = [0, 1, 2, 3, 4, 5, 20, 7, 56, 57, 58, 59, 60, 61, 62, 63];
+
+/// Colors of the PICO-8 fantasy console, by index, with their official names
+const PICO_8: [(u32, &str); 16] = [
+	(0x000000, "Black"), (0x1d2b53, "Dark Blue"), (0x7e2553, "Dark Purple"), (0x008751, "Dark Green"),
+	(0xab5236, "Brown"), (0x5f574f, "Dark Grey"), (0xc2c3c7, "Light Grey"), (0xfff1e8, "White"),
+	(0xff004d, "Red"), (0xffa300, "Orange"), (0xffec27, "Yellow"), (0x00e436, "Green"),
+	(0x29adff, "Blue"), (0x83769c, "Lavender"), (0xff77a8, "Pink"), (0xffccaa, "Light Peach"),
+];
+
+/// Shades of green of the original Game Boy screen, from the darkest to the lightest
+const GAME_BOY: [(u32, &str); 4] = [
+	(0x0f380f, "Darkest Green"), (0x306230, "Dark Green"), (0x8bac0f, "Light Green"), (0x9bbc0f, "Lightest Green"),
+];
+
+/// Ordered collection of colors, optionally named, like the palettes of the image editors and
+/// the retro systems. Besides building them, palettes can be loaded from the files listed in
+/// [PaletteFormat].
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::Color;
+/// # use ferrux_canvas::color::palette::Palette;
+/// let pico8 = Palette::pico8();
+/// assert_eq!(16, pico8.len());
+/// assert_eq!(Some(&Color { r: 255, g: 0, b: 77, a: 255 }), pico8.find("red"));
+/// let index = pico8.nearest(&Color { r: 250, g: 10, b: 70, a: 255 }).unwrap();
+/// assert_eq!(Some("Red"), pico8.name(index));
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+	colors: Vec<Color>,
+	names: Vec<Option<String>>,
+}
+
+impl Palette {
+
+	/// Returns a new empty palette
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns a palette with the given colors, without names
+	pub fn from_colors(colors: Vec<Color>) -> Self {
+		let names = vec![None; colors.len()];
+		Self { colors, names }
+	}
+
+	/// Returns the palette of the CSS named colors, sorted by name. They are the colors of the X11
+	/// window system, except for `gray`, `green`, `maroon` and `purple`, which are darker in CSS.
+	pub fn css() -> Self {
+		Self::from_hex(NAMED_COLORS.iter().map(|&(name, rgb)| (rgb, name)))
+	}
+
+	/// Returns the 16 colors of the CGA graphics cards
+	pub fn cga() -> Self {
+		Self::from_hex(CGA.iter().copied())
+	}
+
+	/// Returns the 64 colors the EGA graphics cards could display, by their index. The 16 ones
+	/// used by default are named like the CGA colors.
+	pub fn ega() -> Self {
+		let mut palette = Self::from_colors((0..64)
+			.map(|i| {
+				let channel = |primary: u8, secondary: u8| 0xaa * ((i >> primary) & 1) + 0x55 * ((i >> secondary) & 1);
+				Color { r: channel(2, 5), g: channel(1, 4), b: channel(0, 3), a: u8::MAX }
+			})
+			.collect());
+		for (index, (_, name)) in EGA_DEFAULTS.iter().zip(CGA) {
+			palette.names[*index] = Some(name.to_owned());
+		}
+		palette
+	}
+
+	/// Returns the 16 colors of the PICO-8 fantasy console
+	pub fn pico8() -> Self {
+		Self::from_hex(PICO_8.iter().copied())
+	}
+
+	/// Returns the 4 shades of green of the original Game Boy
+	pub fn game_boy() -> Self {
+		Self::from_hex(GAME_BOY.iter().copied())
+	}
+
+	/// Loads the palette of a file, detecting its format. The supported formats are listed in
+	/// [PaletteFormat].
+	///
+	/// # Errors
+	/// * [PaletteError::Io] if the file can't be read
+	/// * [PaletteError::UnknownFormat] if the format of the file is not supported
+	/// * [PaletteError::Malformed] if the palette is corrupted
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::color::palette::Palette;
+	/// let palette = Palette::open("assets/sunset.gpl").unwrap();
+	/// let sky = palette.find("sky").unwrap();
+	/// ```
+	///
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, PaletteError> {
+		Self::decode(&std::fs::read(path)?)
+	}
+
+	/// Decodes a palette from the content of a palette file, detecting its format
+	///
+	/// # Errors
+	/// * [PaletteError::UnknownFormat] if the format of the data is not supported
+	/// * [PaletteError::Malformed] if the palette is corrupted
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::color::palette::Palette;
+	/// let palette = Palette::decode(b"GIMP Palette\n255 0 0 Cherry\n0 0 255 Sea").unwrap();
+	/// assert_eq!(Some(&palette::BLUE), palette.find("sea"));
+	/// ```
+	///
+	pub fn decode(bytes: &[u8]) -> Result<Self, PaletteError> {
+		let format = PaletteFormat::detect(bytes).ok_or(PaletteError::UnknownFormat)?;
+		decode(bytes, format)
+	}
+
+	/// Decodes a palette in the specified format
+	///
+	/// # Errors
+	/// [PaletteError::Malformed] if the palette is corrupted or isn't in the specified format
+	///
+	pub fn decode_format(bytes: &[u8], format: PaletteFormat) -> Result<Self, PaletteError> {
+		decode(bytes, format)
+	}
+
+	/// Adds a color at the end of the palette
+	pub fn push(&mut self, color: Color, name: Option<String>) {
+		self.colors.push(color);
+		self.names.push(name);
+	}
+
+	/// Returns the number of colors of the palette
+	pub fn len(&self) -> usize {
+		self.colors.len()
+	}
+
+	/// Returns if the palette has no colors
+	pub fn is_empty(&self) -> bool {
+		self.colors.is_empty()
+	}
+
+	/// Returns the colors of the palette, in order
+	pub fn colors(&self) -> &[Color] {
+		&self.colors
+	}
+
+	/// Returns the color in the given index, if it exists
+	pub fn get(&self, index: usize) -> Option<&Color> {
+		self.colors.get(index)
+	}
+
+	/// Returns the color in the given index as mutable, if it exists
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut Color> {
+		self.colors.get_mut(index)
+	}
+
+	/// Rotates the colors in the range of indices by the given steps, moving each color to the
+	/// following index and the last one to the start, or backwards with negative steps. The names
+	/// move with their colors. Rotating the colors each frame animates the classic palette cycling
+	/// effects, like flowing water or fire.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::color::palette::Palette;
+	/// let mut cycle = Palette::from_colors(vec![palette::BLACK, palette::RED, palette::GREEN, palette::BLUE]);
+	/// cycle.cycle(1..4, 1);
+	/// assert_eq!(&[palette::BLACK, palette::BLUE, palette::RED, palette::GREEN], cycle.colors());
+	/// ```
+	///
+	pub fn cycle(&mut self, range: Range<usize>, steps: isize) {
+		let end = range.end.min(self.colors.len());
+		let start = range.start.min(end);
+		if start == end {
+			return;
+		}
+		let steps = steps.rem_euclid((end - start) as isize) as usize;
+		self.colors[start..end].rotate_right(steps);
+		self.names[start..end].rotate_right(steps);
+	}
+
+	/// Returns the name of the color in the given index, if it exists and has a name
+	pub fn name(&self, index: usize) -> Option<&str> {
+		self.names.get(index).and_then(|name| name.as_deref())
+	}
+
+	/// Returns the index of the first color with the given name. The names are compared ignoring
+	/// the case, the spaces, the hyphens and the underscores, so `Dark Blue` matches `dark_blue`.
+	pub fn index_of(&self, name: &str) -> Option<usize> {
+		let normalize = |name: &str| name.chars()
+			.filter(|c| !matches!(c, ' ' | '-' | '_'))
+			.flat_map(char::to_lowercase)
+			.collect::<String>();
+		let name = normalize(name);
+		self.names.iter().position(|other| other.as_deref().map(normalize).as_ref() == Some(&name))
+	}
+
+	/// Returns the first color with the given name, see [Palette::index_of]
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Color;
+	/// # use ferrux_canvas::color::palette::Palette;
+	/// let css = Palette::css();
+	/// assert_eq!(Some(&Color { r: 102, g: 51, b: 153, a: 255 }), css.find("Rebecca Purple"));
+	/// assert_eq!(None, css.find("ultraviolet"));
+	/// ```
+	///
+	pub fn find(&self, name: &str) -> Option<&Color> {
+		self.index_of(name).and_then(|index| self.colors.get(index))
+	}
+
+	/// Returns the index of the color of the palette closest to the given one, or none if the
+	/// palette is empty. The distance weights the RGB channels by how sensitive the eye is to
+	/// them and also compares the alpha.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Color;
+	/// # use ferrux_canvas::color::palette::Palette;
+	/// let game_boy = Palette::game_boy();
+	/// assert_eq!(Some(0), game_boy.nearest(&Color { r: 0, g: 0, b: 0, a: 255 }));
+	/// assert_eq!(Some(3), game_boy.nearest(&Color { r: 255, g: 255, b: 255, a: 255 }));
+	/// ```
+	///
+	pub fn nearest(&self, color: &Color) -> Option<usize> {
+		nearest(&self.colors, color)
+	}
+
+	/// Returns a palette from the hexadecimal RGB colors and their names
+	fn from_hex<'a, I: Iterator<Item = (u32, &'a str)>>(colors: I) -> Self {
+		let mut palette = Self::new();
+		for (rgb, name) in colors {
+			let [_, r, g, b] = rgb.to_be_bytes();
+			palette.push(Color { r, g, b, a: u8::MAX }, Some(name.to_owned()));
+		}
+		palette
+	}
+
+}
+
+/// Returns the index of the color closest to the given one, see [Palette::nearest]
+pub(crate) fn nearest(colors: &[Color], color: &Color) -> Option<usize> {
+	colors.iter()
+		.enumerate()
+		.min_by_key(|(_, other)| distance(color, other))
+		.map(|(index, _)| index)
+}
+
+/// Returns the squared "redmean" distance between two colors, a cheap approximation of the
+/// perceived difference, adding the squared difference of their alpha
+fn distance(a: &Color, b: &Color) -> u32 {
+	let mean = (a.r as u32 + b.r as u32) / 2;
+	let (dr, dg, db, da) = (a.r.abs_diff(b.r) as u32, a.g.abs_diff(b.g) as u32, a.b.abs_diff(b.b) as u32,
+	                        a.a.abs_diff(b.a) as u32);
+	(((512 + mean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - mean) * db * db) >> 8) + 3 * da * da
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::color::palette::Palette;
+
+	#[test]
+	fn presets() {
+		let css = Palette::css();
+		assert_eq!(148, css.len());
+		assert_eq!(Some(&palette::WHITE), css.find("white"));
+		assert_eq!(Some(&Color { r: 0, g: 128, b: 0, a: 255 }), css.find("GREEN"));
+		assert_eq!(Some("aliceblue"), css.name(0));
+		let cga = Palette::cga();
+		assert_eq!(Some(&Color { r: 170, g: 85, b: 0, a: 255 }), cga.find("brown"));
+		let ega = Palette::ega();
+		assert_eq!(64, ega.len());
+		assert_eq!(cga.colors(), [0, 1, 2, 3, 4, 5, 20, 7, 56, 57, 58, 59, 60, 61, 62, 63]
+			.map(|index| *ega.get(index).unwrap()));
+		assert_eq!(Some("Light Gray"), ega.name(7));
+		assert_eq!(None, ega.name(6));
+		assert_eq!(Some(&Color { r: 255, g: 204, b: 170, a: 255 }), Palette::pico8().find("light-peach"));
+		assert_eq!(Some(&Color { r: 15, g: 56, b: 15, a: 255 }), Palette::game_boy().get(0));
+	}
+
+	#[test]
+	fn custom_palettes() {
+		let mut custom = Palette::from_colors(vec![palette::RED, palette::GREEN]);
+		custom.push(palette::BLUE, Some("Deep_Blue".to_owned()));
+		assert_eq!(3, custom.len());
+		assert_eq!(None, custom.name(0));
+		assert_eq!(Some(2), custom.index_of("deep blue"));
+		assert_eq!(None, custom.find("red"));
+		assert!(Palette::new().is_empty());
+		*custom.get_mut(0).unwrap() = palette::WHITE;
+		custom.cycle(0..10, -1);
+		assert_eq!(&[palette::GREEN, palette::BLUE, palette::WHITE], custom.colors());
+		assert_eq!(Some("Deep_Blue"), custom.name(1));
+		custom.cycle(2..2, 1);
+		custom.cycle(5..9, 1);
+		assert_eq!(&[palette::GREEN, palette::BLUE, palette::WHITE], custom.colors());
+	}
+
+	#[test]
+	fn nearest_colors() {
+		let cga = Palette::cga();
+		let nearest = |r, g, b| cga.nearest(&Color { r, g, b, a: 255 }).and_then(|index| cga.name(index));
+		assert_eq!(Some("Black"), nearest(20, 20, 20));
+		assert_eq!(Some("Dark Gray"), nearest(90, 90, 90));
+		assert_eq!(Some("Brown"), nearest(160, 80, 10));
+		assert_eq!(Some("Yellow"), nearest(250, 240, 100));
+		assert_eq!(None, Palette::new().nearest(&palette::RED));
+		let mut translucent = Palette::from_colors(vec![palette::RED]);
+		translucent.push(Color { r: 255, g: 0, b: 0, a: 0 }, None);
+		assert_eq!(Some(1), translucent.nearest(&Color { r: 255, g: 0, b: 0, a: 10 }));
+	}
+
+}
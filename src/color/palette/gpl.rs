@@ -0,0 +1,62 @@
+//! Decoding of [GIMP palettes](https://developer.gimp.org/core/standards/gpl/), used by GIMP,
+//! Inkscape and Krita
+use crate::color::Color;
+use crate::color::palette::{lines, Palette, PaletteFormat};
+use crate::color::palette::palette_error::PaletteError;
+
+const HEADER: &str = "GIMP Palette";
+
+fn malformed(reason: String) -> PaletteError {
+	PaletteError::Malformed(PaletteFormat::Gpl, reason)
+}
+
+/// Decodes a GIMP palette. Each color is a line with its three channels followed by its optional
+/// name. The comments and the headers, like the name of the palette, are skipped.
+pub fn decode(bytes: &[u8]) -> Result<Palette, PaletteError> {
+	let mut lines = lines(bytes, PaletteFormat::Gpl)?.enumerate();
+	if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+		return Err(malformed("missing header".to_owned()));
+	}
+	let mut palette = Palette::new();
+	for (number, line) in lines {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+			continue;
+		}
+		let mut fields = line.split_whitespace();
+		let mut channel = || fields.next()
+			.and_then(|field| field.parse::<u8>().ok())
+			.ok_or_else(|| malformed(format!("invalid color in line {}", number + 1)));
+		let color = Color { r: channel()?, g: channel()?, b: channel()?, a: u8::MAX };
+		let name = fields.collect::<Vec<_>>().join(" ");
+		palette.push(color, Some(name).filter(|name| !name.is_empty()));
+	}
+	Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::color::palette::gpl::decode;
+
+	#[test]
+	fn decode_palettes() {
+		let gpl = "GIMP Palette\r\nName: Sunset\r\nColumns: 4\r\n# Warm colors\r\n\r\n255   0   0\tDeep  red\r\n  0 0 255\r\n";
+		let palette = decode(gpl.as_bytes()).unwrap();
+		assert_eq!(&[palette::RED, palette::BLUE], palette.colors());
+		assert_eq!(Some("Deep red"), palette.name(0));
+		assert_eq!(None, palette.name(1));
+		assert_eq!(0, decode(b"GIMP Palette").unwrap().len());
+	}
+
+	#[test]
+	fn malformed_palettes() {
+		assert!(decode(b"JASC-PAL\n0100\n0").is_err());
+		assert!(decode(b"GIMP Palette\n255 0").is_err());
+		assert!(decode(b"GIMP Palette\n10 20 256 Overflow").is_err());
+		assert!(decode(b"GIMP Palette\n\xff\xfe").is_err());
+		assert_eq!(Some(&Color { r: 10, g: 20, b: 30, a: 255 }),
+		           decode(b"GIMP Palette\n10 20 30 Dark").unwrap().find("dark"));
+	}
+
+}
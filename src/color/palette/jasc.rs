@@ -0,0 +1,64 @@
+//! Decoding of the JASC palettes of Paint Shop Pro
+use crate::color::Color;
+use crate::color::palette::{lines, Palette, PaletteFormat};
+use crate::color::palette::palette_error::PaletteError;
+
+const HEADER: &str = "JASC-PAL";
+const VERSION: &str = "0100";
+
+fn malformed(reason: String) -> PaletteError {
+	PaletteError::Malformed(PaletteFormat::Jasc, reason)
+}
+
+/// Decodes a JASC palette: the header, the version and the number of colors, followed by a line
+/// with the three channels of each color
+pub fn decode(bytes: &[u8]) -> Result<Palette, PaletteError> {
+	let mut lines = lines(bytes, PaletteFormat::Jasc)?.map(str::trim);
+	if lines.next() != Some(HEADER) {
+		return Err(malformed("missing header".to_owned()));
+	}
+	if lines.next() != Some(VERSION) {
+		return Err(malformed("unsupported version".to_owned()));
+	}
+	let count = lines.next()
+		.and_then(|line| line.parse::<usize>().ok())
+		.ok_or_else(|| malformed("invalid number of colors".to_owned()))?;
+
+	let mut palette = Palette::new();
+	for (index, line) in lines.take(count).enumerate() {
+		let channels = line.split_whitespace().map(|field| field.parse::<u8>().ok()).collect::<Vec<_>>();
+		match channels[..] {
+			[Some(r), Some(g), Some(b)] => palette.push(Color { r, g, b, a: u8::MAX }, None),
+			_ => return Err(malformed(format!("invalid color {}", index))),
+		}
+	}
+	if palette.len() < count {
+		return Err(malformed(format!("expected {} colors, found {}", count, palette.len())));
+	}
+	Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::color::palette::jasc::decode;
+
+	#[test]
+	fn decode_palettes() {
+		let palette = decode(b"JASC-PAL\r\n0100\r\n3\r\n255 0 0\r\n0 255 0\r\n1 2 3\r\n").unwrap();
+		assert_eq!(&[palette::RED, palette::GREEN, Color { r: 1, g: 2, b: 3, a: 255 }], palette.colors());
+		assert_eq!(None, palette.name(0));
+		assert_eq!(0, decode(b"JASC-PAL\n0100\n0\n").unwrap().len());
+	}
+
+	#[test]
+	fn malformed_palettes() {
+		assert!(decode(b"GIMP Palette\n0 0 0").is_err());
+		assert!(decode(b"JASC-PAL\n0200\n1\n0 0 0").is_err());
+		assert!(decode(b"JASC-PAL\n0100\nmany\n0 0 0").is_err());
+		assert!(decode(b"JASC-PAL\n0100\n2\n0 0 0").is_err());
+		assert!(decode(b"JASC-PAL\n0100\n1\n0 0 0 0").is_err());
+		assert!(decode(b"JASC-PAL\n0100\n1\n0 -1 0").is_err());
+	}
+
+}
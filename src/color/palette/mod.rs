@@ -0,0 +1,139 @@
+//! Collection of basic constants colors to use in the canvas:
+//! * **White:** [super::palette::WHITE]
+//! * **Black:** [super::palette::BLACK]
+//! * **Red:** [super::palette::RED]
+//! * **Green:** [super::palette::GREEN]
+//! * **Blue:** [super::palette::BLUE]
+//!
+//!
+//! The rest of the CSS named colors are constants too, like [CORAL] or [REBECCAPURPLE]. The only
+//! name with a different color is green: [GREEN] is the pure green that CSS calls lime, while the
+//! CSS green is the darker #008000.
+//!
+//! Bigger sets of colors, like the CSS named colors or the retro palettes, are available as
+//! [Palette] collections, which can also be loaded from GIMP, JASC and Adobe palette files.
+use std::fmt::{Display, Formatter};
+use crate::color::Color;
+use crate::color::palette::palette_error::PaletteError;
+
+pub use collection::Palette;
+pub use crate::color::css::constants::*;
+pub(crate) use collection::nearest;
+
+mod ase;
+mod collection;
+mod gpl;
+mod jasc;
+pub mod palette_error;
+
+/// White color: #ffffff
+pub const WHITE: Color = Color {
+	r: u8::MAX,
+	g: u8::MAX,
+	b: u8::MAX,
+	a: u8::MAX,
+};
+
+/// Black color: #000000
+pub const BLACK: Color = Color {
+	r: u8::MIN,
+	g: u8::MIN,
+	b: u8::MIN,
+	a: u8::MAX,
+};
+
+/// Red color: #ff0000
+pub const RED: Color = Color {
+	r: u8::MAX,
+	g: u8::MIN,
+	b: u8::MIN,
+	a: u8::MAX
+};
+
+/// Green color: #00ff00. It's the CSS `lime`, not the CSS `green`, which is #008000 and is the
+/// one returned by `Color::parse("green")` and the `green` entry of [Palette::css].
+pub const GREEN: Color = Color {
+	r: u8::MIN,
+	g: u8::MAX,
+	b: u8::MIN,
+	a: u8::MAX,
+};
+
+/// Blue color: #0000ff
+pub const BLUE: Color = Color {
+	r: u8::MIN,
+	g: u8::MIN,
+	b: u8::MAX,
+	a: u8::MAX,
+};
+
+/// Palette file formats supported by the crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PaletteFormat {
+	/// GIMP palette, a text file also used by Inkscape and Krita
+	Gpl,
+	/// JASC palette of Paint Shop Pro, a text file
+	Jasc,
+	/// Adobe Swatch Exchange, a binary file. The CMYK, LAB and grayscale colors are converted to
+	/// RGB, and the groups are flattened.
+	Ase,
+}
+
+impl PaletteFormat {
+
+	/// Detects the format of a palette from the first bytes of its content
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette::PaletteFormat;
+	/// assert_eq!(Some(PaletteFormat::Jasc), PaletteFormat::detect(b"JASC-PAL\r\n0100"));
+	/// assert_eq!(None, PaletteFormat::detect(b"RIFF"));
+	/// ```
+	///
+	pub fn detect(bytes: &[u8]) -> Option<PaletteFormat> {
+		match bytes {
+			[b'G', b'I', b'M', b'P', b' ', b'P', b'a', b'l', b'e', b't', b't', b'e', ..] => Some(PaletteFormat::Gpl),
+			[b'J', b'A', b'S', b'C', b'-', b'P', b'A', b'L', ..] => Some(PaletteFormat::Jasc),
+			[b'A', b'S', b'E', b'F', ..] => Some(PaletteFormat::Ase),
+			_ => None,
+		}
+	}
+
+	/// Returns the format matching the extension of a file name, ignoring the case
+	pub fn from_extension(extension: &str) -> Option<PaletteFormat> {
+		match extension.to_ascii_lowercase().as_str() {
+			"gpl" => Some(PaletteFormat::Gpl),
+			"pal" => Some(PaletteFormat::Jasc),
+			"ase" => Some(PaletteFormat::Ase),
+			_ => None,
+		}
+	}
+
+}
+
+impl Display for PaletteFormat {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			PaletteFormat::Gpl => "GIMP",
+			PaletteFormat::Jasc => "JASC",
+			PaletteFormat::Ase => "ASE",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+/// Decodes the palette in the specified format
+fn decode(bytes: &[u8], format: PaletteFormat) -> Result<Palette, PaletteError> {
+	match format {
+		PaletteFormat::Gpl => gpl::decode(bytes),
+		PaletteFormat::Jasc => jasc::decode(bytes),
+		PaletteFormat::Ase => ase::decode(bytes),
+	}
+}
+
+/// Returns the lines of a text palette, failing if it's not valid UTF-8
+fn lines(bytes: &[u8], format: PaletteFormat) -> Result<std::str::Lines<'_>, PaletteError> {
+	std::str::from_utf8(bytes)
+		.map(str::lines)
+		.map_err(|_| PaletteError::Malformed(format, "invalid UTF-8 text".to_owned()))
+}
@@ -0,0 +1,63 @@
+//! Throwable errors of the palette files
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use crate::color::palette::PaletteFormat;
+
+/// Errors than can occur loading a [`Palette`]
+///
+/// [`Palette`]: crate::color::palette::Palette
+pub enum PaletteError {
+	/// The palette file couldn't be read
+	Io(std::io::Error),
+	/// The data doesn't match any of the supported palette formats
+	UnknownFormat,
+	/// The palette is malformed. Contains the reason of the error.
+	Malformed(PaletteFormat, String),
+}
+
+impl PaletteError {
+	fn message(&self) -> String {
+		match self {
+			Self::Io(error) => format!("The palette could not be read: {}", error),
+			Self::UnknownFormat => "The palette format is not supported. It should be GIMP, JASC or ASE".to_owned(),
+			Self::Malformed(format, reason) => format!("The {} palette is malformed: {}", format, reason),
+		}
+	}
+}
+
+impl Error for PaletteError {}
+
+impl Debug for PaletteError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl Display for PaletteError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl From<std::io::Error> for PaletteError {
+	fn from(error: std::io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::palette::palette_error::PaletteError;
+
+	#[test]
+	fn test_send() {
+		fn assert_send<T: Send>() {}
+		assert_send::<PaletteError>();
+	}
+
+	#[test]
+	fn test_sync() {
+		fn assert_sync<T: Sync>() {}
+		assert_sync::<PaletteError>();
+	}
+}
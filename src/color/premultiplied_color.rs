@@ -0,0 +1,141 @@
+//! Colors with premultiplied alpha. Mixing the channels of straight colors lets the invisible
+//! color of the transparent pixels leak into the result, as the dark fringes around the scaled
+//! sprites, while the premultiplied channels of a transparent pixel are always zero.
+
+use crate::color::Color;
+
+/// Color whose red, green and blue channels are already multiplied by its alpha, so they are never
+/// greater than it. It's the usual layout of the pixel buffers of the compositors and the GPUs.
+///
+/// The conversions keep the opaque colors exactly, but a translucent color only has as many levels
+/// per channel as its alpha, so converting it back may change its channels slightly. The fully
+/// transparent colors become transparent black.
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::{Color, PremultipliedColor};
+/// let translucent_red = Color { r: 255, g: 0, b: 0, a: 128 };
+/// let premultiplied = PremultipliedColor::from(&translucent_red);
+/// assert_eq!(PremultipliedColor { r: 128, g: 0, b: 0, a: 128 }, premultiplied);
+/// assert_eq!(translucent_red, Color::from(premultiplied));
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PremultipliedColor {
+	pub r: u8,
+	pub g: u8,
+	pub b: u8,
+	pub a: u8,
+}
+
+impl PremultipliedColor {
+
+	/// Returns the color resulting of painting this color over the background one, the Porter-Duff
+	/// "source over" operator
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::PremultipliedColor;
+	/// let translucent_red = PremultipliedColor { r: 128, g: 0, b: 0, a: 128 };
+	/// let blue = PremultipliedColor { r: 0, g: 0, b: 255, a: 255 };
+	/// assert_eq!(PremultipliedColor { r: 128, g: 0, b: 127, a: 255 }, translucent_red.blend(&blue));
+	/// ```
+	///
+	pub fn blend(&self, background: &PremultipliedColor) -> PremultipliedColor {
+		let coverage = 1.0 - self.a as f32 / 255.0;
+		let mix = |src: u8, dst: u8| (src as f32 + dst as f32 * coverage).round().min(255.0) as u8;
+		PremultipliedColor {
+			r: mix(self.r, background.r),
+			g: mix(self.g, background.g),
+			b: mix(self.b, background.b),
+			a: mix(self.a, background.a),
+		}
+	}
+
+}
+
+impl From<&Color> for PremultipliedColor {
+	fn from(color: &Color) -> Self {
+		let multiply = |channel: u8| ((channel as u32 * color.a as u32 + 127) / 255) as u8;
+		PremultipliedColor {
+			r: multiply(color.r),
+			g: multiply(color.g),
+			b: multiply(color.b),
+			a: color.a,
+		}
+	}
+}
+
+impl From<PremultipliedColor> for Color {
+	fn from(color: PremultipliedColor) -> Self {
+		if color.a == 0 {
+			return Color { r: 0, g: 0, b: 0, a: 0 };
+		}
+		let divide = |channel: u8| ((channel as u32 * 255 + color.a as u32 / 2) / color.a as u32).min(255) as u8;
+		Color {
+			r: divide(color.r),
+			g: divide(color.g),
+			b: divide(color.b),
+			a: color.a,
+		}
+	}
+}
+
+/// Returns the weighted sum of the colors with premultiplied alpha, converted back to a straight
+/// color. The weights should add up to 1.
+pub(crate) fn mix<'a, I: IntoIterator<Item = (&'a Color, f32)>>(colors: I) -> Color {
+	let mut sums = [0.0f32; 4];
+	for (color, weight) in colors {
+		let alpha = color.a as f32 * weight;
+		sums[0] += color.r as f32 * alpha;
+		sums[1] += color.g as f32 * alpha;
+		sums[2] += color.b as f32 * alpha;
+		sums[3] += alpha;
+	}
+	let a = sums[3].round().clamp(0.0, 255.0) as u8;
+	if a == 0 {
+		return Color { r: 0, g: 0, b: 0, a: 0 };
+	}
+	let [r, g, b] = [sums[0], sums[1], sums[2]].map(|sum| (sum / sums[3]).round().clamp(0.0, 255.0) as u8);
+	Color { r, g, b, a }
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette, PremultipliedColor};
+	use crate::color::premultiplied_color::mix;
+
+	#[test]
+	fn conversions() {
+		assert_eq!(PremultipliedColor { r: 255, g: 0, b: 0, a: 255 }, PremultipliedColor::from(&palette::RED));
+		assert_eq!(palette::RED, Color::from(PremultipliedColor::from(&palette::RED)));
+		let faint = Color { r: 200, g: 100, b: 7, a: 3 };
+		assert_eq!(PremultipliedColor { r: 2, g: 1, b: 0, a: 3 }, PremultipliedColor::from(&faint));
+		assert_eq!(Color { r: 170, g: 85, b: 0, a: 3 }, Color::from(PremultipliedColor::from(&faint)));
+		assert_eq!(Color { r: 0, g: 0, b: 0, a: 0 }, Color::from(PremultipliedColor::from(&Color { r: 9, g: 9, b: 9, a: 0 })));
+		assert_eq!(Color { r: 255, g: 255, b: 255, a: 10 }, Color::from(PremultipliedColor { r: 200, g: 20, b: 11, a: 10 }));
+		for a in 1..=255 {
+			let color = Color { r: 255, g: 128, b: 0, a };
+			assert_eq!(PremultipliedColor::from(&color), PremultipliedColor::from(&Color::from(PremultipliedColor::from(&color))));
+		}
+	}
+
+	#[test]
+	fn blending() {
+		let background = PremultipliedColor::from(&Color { r: 200, g: 100, b: 0, a: 255 });
+		let green = PremultipliedColor::from(&Color { r: 0, g: 200, b: 0, a: 128 });
+		assert_eq!(Color { r: 100, g: 150, b: 0, a: 255 }, Color::from(green.blend(&background)));
+		let transparent = PremultipliedColor { r: 0, g: 0, b: 0, a: 0 };
+		assert_eq!(background, transparent.blend(&background));
+		assert_eq!(green, green.blend(&transparent));
+	}
+
+	#[test]
+	fn weighted_mix() {
+		let transparent = Color { r: 0, g: 0, b: 0, a: 0 };
+		assert_eq!(Color { r: 255, g: 255, b: 255, a: 128 }, mix([(&transparent, 0.5), (&palette::WHITE, 0.5)]));
+		assert_eq!(transparent, mix([(&Color { r: 9, g: 9, b: 9, a: 0 }, 1.0)]));
+		assert_eq!(Color { r: 191, g: 0, b: 64, a: 255 }, mix([(&palette::RED, 0.75), (&palette::BLUE, 0.25)]));
+	}
+
+}
@@ -1,11 +1,13 @@
+use std::str::FromStr;
 use regex::Regex;
 use crate::color::color_error::ColorError;
 use crate::color::color_error::ColorError::InvalidSyntax;
+use crate::color::{color_ramp, css, Hsl, Interpolation, Lab};
 
 /// Representation of a color to use in the canvas based on RGBA.
 /// It should be passed to the drawing operations to specify the color to use.
-/// It can be constructed with the [`from_rgba`] function passing to hexadecimal string representation
-/// or using the [`ColorBuilder`]. Some useful predefined colors are also available at [`palette`]
+/// It can be constructed with the [`from_rgba`] function passing to hexadecimal string representation,
+/// parsing any CSS color with [`parse`] or using the [`ColorBuilder`]. Some useful predefined colors are also available at [`palette`]
 ///
 /// # Example
 /// The following example draws a triangle using a line of each primary RGB color.
@@ -27,10 +29,19 @@ use crate::color::color_error::ColorError::InvalidSyntax;
 /// # Ok(()) }
 /// ```
 ///
+/// Colors are `Copy`, `Eq` and `Hash`, so they can be used as keys of maps, and they can be built
+/// in constants with [`rgb`] and [`rgba`]. They can be converted to and from the packed `u32`,
+/// arrays and tuples used by other crates, and with the `serde` feature they are serialized as a
+/// struct with the four channels.
+///
 /// [`from_rgba`]: super::Color::from_rgba
+/// [`parse`]: super::Color::parse
 /// [`ColorBuilder`]: super::ColorBuilder
 /// [`palette`]: super::palette
-#[derive(Debug, PartialEq)]
+/// [`rgb`]: super::Color::rgb
+/// [`rgba`]: super::Color::rgba
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
 	pub r: u8,
 	pub g: u8,
@@ -40,8 +51,73 @@ pub struct Color {
 
 impl Color {
 
-	/// Create a color from a RGBA hexadecimal representation. The syntax should be of type 'hhhhhhhh'
-	/// without the # (intended to be supported on future versions).
+	/// Creates an opaque color from its red, green and blue channels
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Color;
+	/// const CORAL: Color = Color::rgb(255, 127, 80);
+	/// assert_eq!(Color { r: 255, g: 127, b: 80, a: 255 }, CORAL);
+	/// ```
+	///
+	pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+		Color { r, g, b, a: u8::MAX }
+	}
+
+	/// Creates a color from its red, green, blue and alpha channels
+	pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+		Color { r, g, b, a }
+	}
+
+	/// Creates a color from a `u32` packed as `0xRRGGBBAA`
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Color;
+	/// let color = Color::from_rgba_u32(0x0a2b3c4d);
+	/// assert_eq!(Color { r: 10, g: 43, b: 60, a: 77 }, color);
+	/// assert_eq!(0x4d0a2b3c, color.to_argb_u32());
+	/// assert_eq!(0x3c2b0a4d, color.to_bgra_u32());
+	/// ```
+	///
+	pub const fn from_rgba_u32(rgba: u32) -> Color {
+		let [r, g, b, a] = rgba.to_be_bytes();
+		Color { r, g, b, a }
+	}
+
+	/// Creates a color from a `u32` packed as `0xAARRGGBB`, like the pixels of many windowing
+	/// systems
+	pub const fn from_argb_u32(argb: u32) -> Color {
+		let [a, r, g, b] = argb.to_be_bytes();
+		Color { r, g, b, a }
+	}
+
+	/// Creates a color from a `u32` packed as `0xBBGGRRAA`
+	pub const fn from_bgra_u32(bgra: u32) -> Color {
+		let [b, g, r, a] = bgra.to_be_bytes();
+		Color { r, g, b, a }
+	}
+
+	/// Returns the color packed in a `u32` as `0xRRGGBBAA`
+	pub const fn to_rgba_u32(&self) -> u32 {
+		u32::from_be_bytes([self.r, self.g, self.b, self.a])
+	}
+
+	/// Returns the color packed in a `u32` as `0xAARRGGBB`
+	pub const fn to_argb_u32(&self) -> u32 {
+		u32::from_be_bytes([self.a, self.r, self.g, self.b])
+	}
+
+	/// Returns the color packed in a `u32` as `0xBBGGRRAA`
+	pub const fn to_bgra_u32(&self) -> u32 {
+		u32::from_be_bytes([self.b, self.g, self.r, self.a])
+	}
+
+	/// Create a color from a RGBA hexadecimal representation. The syntax should be of type
+	/// 'hhhhhhhh', optionally starting with '#'. Use [Color::parse] for the rest of the CSS syntaxes.
+	///
+	/// # Errors
+	/// [ColorError::InvalidSyntax] if the text isn't exactly eight hexadecimal digits
 	///
 	/// # Example
 	/// ```rust
@@ -49,47 +125,266 @@ impl Color {
 	/// let color = Color::from_rgba("0a2B3c4d");
 	/// let expected = Color { r: 10, g: 43, b: 60, a: 77 };
 	/// assert_eq!(color.unwrap(), expected);
+	/// assert!(Color::from_rgba("0a2B3c4d5").is_err());
 	/// ```
 	///
 	pub fn from_rgba(rgba: &str) -> Result<Color, ColorError> {
-		match Regex::new(r"([a-fA-F\d]{2})([a-fA-F\d]{2})([a-fA-F\d]{2})([a-fA-F\d]{2})").unwrap()
+		match Regex::new(r"^#?([a-fA-F\d]{2})([a-fA-F\d]{2})([a-fA-F\d]{2})([a-fA-F\d]{2})$").unwrap()
 			.captures(rgba) {
 			Some(caps) => {
-				if caps.len() != 5 {
-					Err(InvalidSyntax("hhhhhhhh"))
-				} else {
-					let r = u8::from_str_radix(&caps[1], 16).unwrap();
-					let g = u8::from_str_radix(&caps[2], 16).unwrap();
-					let b = u8::from_str_radix(&caps[3], 16).unwrap();
-					let a = u8::from_str_radix(&caps[4], 16).unwrap();
-					Ok(Color { r, g, b, a })
-				}
+				let r = u8::from_str_radix(&caps[1], 16).unwrap();
+				let g = u8::from_str_radix(&caps[2], 16).unwrap();
+				let b = u8::from_str_radix(&caps[3], 16).unwrap();
+				let a = u8::from_str_radix(&caps[4], 16).unwrap();
+				Ok(Color { r, g, b, a })
 			}
 			None => Err(InvalidSyntax("hhhhhhhh"))
 		}
 	}
 
-	/// Returns the u8 array representation of the color to be passed to the pixels buffer
-	pub(crate) fn as_u8(&self) -> [u8; 4] {
+	/// Parses a color written in any of the CSS syntaxes, ignoring the case and the surrounding
+	/// whitespaces:
+	/// * Hexadecimal: `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`
+	/// * RGB functions: `rgb(255, 0, 0)`, `rgba(255, 0, 0, 0.5)` or `rgb(100% 0% 0% / 50%)`
+	/// * HSL functions: `hsl(0, 100%, 50%)`, `hsla(0, 100%, 50%, 0.5)` or `hsl(0.5turn 100% 50% / 50%)`
+	/// * The CSS named colors, like `rebeccapurple`, and `transparent`
+	///
+	/// The values out of range are clamped, like in CSS. It's also available through [str::parse].
+	///
+	/// # Errors
+	/// A [ColorError] describing the first error found, with its position in the text
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// # use ferrux_canvas::color::color_error::ColorError;
+	/// assert_eq!(palette::RED, Color::parse("#f00").unwrap());
+	/// assert_eq!(Color { r: 255, g: 127, b: 80, a: 255 }, "coral".parse().unwrap());
+	/// assert_eq!(Color { r: 0, g: 0, b: 255, a: 128 }, Color::parse("hsl(240deg 100% 50% / 0.5)").unwrap());
+	/// assert_eq!(Err(ColorError::InvalidCharacter { character: 'x', position: 3 }), Color::parse("#12x"));
+	/// ```
+	///
+	pub fn parse(text: &str) -> Result<Color, ColorError> {
+		css::parse(text)
+	}
+
+	/// Returns the color resulting of painting this color over the background one, mixing them
+	/// according to their alpha values.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// let translucent_red = Color { r: 255, g: 0, b: 0, a: 128 };
+	/// assert_eq!(Color { r: 128, g: 0, b: 127, a: 255 }, translucent_red.blend(&palette::BLUE));
+	/// ```
+	///
+	pub fn blend(&self, background: &Color) -> Color {
+		let src_a = self.a as f32 / 255.0;
+		let dst_a = background.a as f32 / 255.0 * (1.0 - src_a);
+		let a = src_a + dst_a;
+		if a == 0.0 {
+			return Color { r: 0, g: 0, b: 0, a: 0 };
+		}
+		let mix = |src: u8, dst: u8| ((src as f32 * src_a + dst as f32 * dst_a) / a).round() as u8;
+		Color {
+			r: mix(self.r, background.r),
+			g: mix(self.g, background.g),
+			b: mix(self.b, background.b),
+			a: (a * 255.0).round() as u8,
+		}
+	}
+
+	/// Returns the color with its HSL lightness increased by the given amount, from 0 to 1.
+	/// Negative amounts darken it.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Color;
+	/// let navy = Color { r: 0, g: 0, b: 128, a: 255 };
+	/// assert_eq!(Color { r: 0, g: 0, b: 230, a: 255 }, navy.lighten(0.2));
+	/// ```
+	///
+	pub fn lighten(&self, amount: f32) -> Color {
+		let hsl = Hsl::from(self);
+		Color::from(Hsl { l: (hsl.l + amount).clamp(0.0, 1.0), ..hsl })
+	}
+
+	/// Returns the color with its HSL lightness decreased by the given amount, from 0 to 1.
+	/// See [Color::lighten].
+	pub fn darken(&self, amount: f32) -> Color {
+		self.lighten(-amount)
+	}
+
+	/// Returns the color with its HSL saturation increased by the given amount, from 0 to 1.
+	/// Negative amounts desaturate it.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::Color;
+	/// let grayish = Color { r: 153, g: 102, b: 102, a: 255 };
+	/// assert_eq!(Color { r: 204, g: 51, b: 51, a: 255 }, grayish.saturate(0.4));
+	/// assert_eq!(Color { r: 128, g: 128, b: 128, a: 255 }, grayish.desaturate(1.0));
+	/// ```
+	///
+	pub fn saturate(&self, amount: f32) -> Color {
+		let hsl = Hsl::from(self);
+		Color::from(Hsl { s: (hsl.s + amount).clamp(0.0, 1.0), ..hsl })
+	}
+
+	/// Returns the color with its HSL saturation decreased by the given amount, from 0 to 1.
+	/// See [Color::saturate].
+	pub fn desaturate(&self, amount: f32) -> Color {
+		self.saturate(-amount)
+	}
+
+	/// Returns the color with its hue rotated by the given degrees, keeping its saturation and
+	/// lightness.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// assert_eq!(palette::GREEN, palette::RED.rotate_hue(120.0));
+	/// assert_eq!(palette::BLUE, palette::RED.rotate_hue(-120.0));
+	/// ```
+	///
+	pub fn rotate_hue(&self, degrees: f32) -> Color {
+		let hsl = Hsl::from(self);
+		Color::from(Hsl { h: (hsl.h + degrees).rem_euclid(360.0), ..hsl })
+	}
+
+	/// Returns the negative of the color, keeping its alpha
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// assert_eq!(palette::BLACK, palette::WHITE.invert());
+	/// assert_eq!(Color { r: 245, g: 235, b: 225, a: 40 }, Color { r: 10, g: 20, b: 30, a: 40 }.invert());
+	/// ```
+	///
+	pub fn invert(&self) -> Color {
+		Color { r: u8::MAX - self.r, g: u8::MAX - self.g, b: u8::MAX - self.b, a: self.a }
+	}
+
+	/// Returns the color at the given point, from 0 to 1, between this color and the other one,
+	/// interpolating them in the given color space. The alpha is always interpolated linearly.
+	///
+	/// # Arguments
+	/// * `other` - Color at the end of the interpolation
+	/// * `t` - Position between the colors, 0 being this color and 1 the other one
+	/// * `interpolation` - Color space where the colors are mixed
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, Interpolation, palette};
+	/// let (red, green) = (palette::RED, palette::GREEN);
+	/// assert_eq!(Color { r: 128, g: 128, b: 0, a: 255 }, red.lerp(&green, 0.5, Interpolation::Srgb));
+	/// assert_eq!(Color { r: 255, g: 255, b: 0, a: 255 }, red.lerp(&green, 0.5, Interpolation::Hsl));
+	/// ```
+	///
+	pub fn lerp(&self, other: &Color, t: f32, interpolation: Interpolation) -> Color {
+		color_ramp::lerp(self, other, t, interpolation)
+	}
+
+	/// Returns the perceived difference with the other color, using the CIEDE2000 formula.
+	/// Differences below 1 are not noticeable, which makes it useful to compare images with some
+	/// tolerance. The alpha is ignored.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// assert!(palette::RED.delta_e(&Color { r: 254, g: 1, b: 0, a: 255 }) < 1.0);
+	/// assert!(palette::RED.delta_e(&palette::BLUE) > 50.0);
+	/// ```
+	///
+	pub fn delta_e(&self, other: &Color) -> f32 {
+		Lab::from(self).delta_e2000(&Lab::from(other))
+	}
+
+	/// Returns the channels of the color in RGBA order, like the pixels buffer stores them
+	pub const fn as_u8(&self) -> [u8; 4] {
 		[self.r, self.g, self.b, self.a]
 	}
 
 }
 
-impl Clone for Color {
-	fn clone(&self) -> Self {
-		Self {
-			r: self.r,
-			g: self.g,
-			b: self.b,
-			a: self.a
-		}
+impl FromStr for Color {
+	type Err = ColorError;
+
+	/// Parses a color written in any of the CSS syntaxes, see [Color::parse]
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		Color::parse(text)
+	}
+}
+
+impl From<[u8; 4]> for Color {
+	/// Creates a color from its channels in RGBA order
+	fn from([r, g, b, a]: [u8; 4]) -> Self {
+		Color { r, g, b, a }
+	}
+}
+
+impl From<Color> for [u8; 4] {
+	/// Returns the channels of the color in RGBA order
+	fn from(color: Color) -> Self {
+		color.as_u8()
+	}
+}
+
+impl From<(u8, u8, u8)> for Color {
+	/// Creates an opaque color from its red, green and blue channels
+	fn from((r, g, b): (u8, u8, u8)) -> Self {
+		Color::rgb(r, g, b)
+	}
+}
+
+impl From<(u8, u8, u8, u8)> for Color {
+	/// Creates a color from its red, green, blue and alpha channels
+	fn from((r, g, b, a): (u8, u8, u8, u8)) -> Self {
+		Color { r, g, b, a }
+	}
+}
+
+impl From<Color> for (u8, u8, u8, u8) {
+	/// Returns the red, green, blue and alpha channels of the color
+	fn from(color: Color) -> Self {
+		(color.r, color.g, color.b, color.a)
+	}
+}
+
+impl From<[f32; 3]> for Color {
+	/// Creates an opaque color from its red, green and blue channels from 0 to 1, clamping them
+	fn from([r, g, b]: [f32; 3]) -> Self {
+		Color::from([r, g, b, 1.0])
+	}
+}
+
+impl From<[f32; 4]> for Color {
+	/// Creates a color from its channels from 0 to 1 in RGBA order, clamping them
+	fn from(channels: [f32; 4]) -> Self {
+		let [r, g, b, a] = channels.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+		Color { r, g, b, a }
+	}
+}
+
+impl From<Color> for [f32; 4] {
+	/// Returns the channels of the color from 0 to 1 in RGBA order
+	fn from(color: Color) -> Self {
+		color.as_u8().map(|channel| channel as f32 / 255.0)
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::color::Color;
+	use crate::color::{Color, palette};
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serde_round_trip() {
+		let color = Color { r: 10, g: 20, b: 30, a: 40 };
+		let json = serde_json::to_string(&color).unwrap();
+		assert_eq!(r#"{"r":10,"g":20,"b":30,"a":40}"#, json);
+		assert_eq!(color, serde_json::from_str::<Color>(&json).unwrap());
+	}
 
 	#[test]
 	fn invalid_rgba_parsing() {
@@ -97,6 +392,61 @@ mod tests {
 		assert!(Color::from_rgba("0a2B3c4").is_err());
 		// Wrong char
 		assert!(Color::from_rgba("0Z2B3c4d").is_err());
+		// Long
+		assert!(Color::from_rgba("0a2B3c4d5").is_err());
+		assert!(Color::from_rgba("garbage 0a2B3c4d").is_err());
+		// Hash
+		assert_eq!(Ok(Color { r: 10, g: 43, b: 60, a: 77 }), Color::from_rgba("#0a2B3c4d"));
+	}
+
+	#[test]
+	fn blending() {
+		let background = Color { r: 200, g: 100, b: 0, a: 255 };
+		assert_eq!(palette::RED, palette::RED.blend(&background));
+		assert_eq!(background, Color { r: 10, g: 20, b: 30, a: 0 }.blend(&background));
+		assert_eq!(Color { r: 100, g: 150, b: 0, a: 255 },
+		           Color { r: 0, g: 200, b: 0, a: 128 }.blend(&background));
+		let translucent = Color { r: 0, g: 0, b: 255, a: 128 };
+		assert_eq!(Color { r: 0, g: 0, b: 255, a: 192 }, translucent.blend(&translucent));
+	}
+
+	#[test]
+	fn conversions() {
+		let color = Color::rgba(10, 20, 30, 40);
+		assert_eq!(color, Color::from_rgba_u32(color.to_rgba_u32()));
+		assert_eq!(color, Color::from_argb_u32(color.to_argb_u32()));
+		assert_eq!(color, Color::from_bgra_u32(color.to_bgra_u32()));
+		assert_eq!(0xff0000ff, palette::RED.to_rgba_u32());
+		assert_eq!(0xffff0000, palette::RED.to_argb_u32());
+		assert_eq!(0x0000ffff, palette::RED.to_bgra_u32());
+		assert_eq!(color, Color::from(<[u8; 4]>::from(color)));
+		assert_eq!(color, Color::from(<(u8, u8, u8, u8)>::from(color)));
+		assert_eq!(Color::rgb(10, 20, 30), Color::from((10, 20, 30)));
+		assert_eq!(color, Color::from(<[f32; 4]>::from(color)));
+		assert_eq!(Color::rgb(255, 128, 0), Color::from([1.5, 0.5, -1.0]));
+	}
+
+	#[test]
+	fn hashing() {
+		let mut counts = std::collections::HashMap::new();
+		for color in [palette::RED, palette::BLUE, Color::rgb(255, 0, 0)] {
+			*counts.entry(color).or_insert(0) += 1;
+		}
+		assert_eq!(Some(&2), counts.get(&palette::RED));
+		assert_eq!(2, counts.len());
+	}
+
+	#[test]
+	fn adjustments() {
+		let teal = Color { r: 0, g: 128, b: 128, a: 100 };
+		assert_eq!(Color { r: 0, g: 26, b: 26, a: 100 }, teal.darken(0.2));
+		assert_eq!(palette::BLACK, palette::RED.darken(1.0));
+		assert_eq!(palette::WHITE, palette::RED.lighten(0.7));
+		assert_eq!(teal, teal.saturate(0.5));
+		assert_eq!(Color { r: 96, g: 32, b: 32, a: 255 }, Color { r: 64, g: 64, b: 64, a: 255 }.saturate(0.5));
+		assert_eq!(Color { r: 128, g: 0, b: 128, a: 100 }, teal.rotate_hue(120.0));
+		assert_eq!(teal, teal.rotate_hue(360.0));
+		assert_eq!(teal, teal.invert().invert());
 	}
 
 }
\ No newline at end of file
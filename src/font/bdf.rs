@@ -0,0 +1,228 @@
+//! Parser of the Glyph Bitmap Distribution Format (BDF) fonts
+use std::collections::HashMap;
+use crate::font::font_error::FontError;
+use crate::font::{bit_coverage, BitmapFont, Glyph};
+
+/// Bounding box of a BDF glyph: width, height and offset from the origin
+type BoundingBox = (u32, u32, i32, i32);
+
+/// Glyph being parsed
+#[derive(Default)]
+struct PendingGlyph {
+	encoding: Option<u32>,
+	advance: Option<u32>,
+	bbx: Option<BoundingBox>,
+	rows: Vec<Vec<u8>>,
+}
+
+/// Parses the source of a BDF font
+pub fn parse(source: &str) -> Result<BitmapFont, FontError> {
+	let mut font_bbx: Option<BoundingBox> = None;
+	let mut ascent: Option<i32> = None;
+	let mut descent: Option<i32> = None;
+	let mut default_char: Option<u32> = None;
+	let mut glyphs: Vec<(u32, Glyph)> = Vec::new();
+	let mut pending: Option<PendingGlyph> = None;
+	let mut in_bitmap = false;
+
+	for (i, line) in source.lines().enumerate() {
+		let number = i + 1;
+		let error = || FontError::InvalidBdf(number);
+		let mut words = line.split_whitespace();
+		let keyword = match words.next() {
+			Some(keyword) => keyword,
+			None => continue,
+		};
+		let values = words.collect::<Vec<_>>();
+
+		if in_bitmap {
+			let glyph = pending.as_mut().ok_or_else(error)?;
+			if keyword == "ENDCHAR" {
+				in_bitmap = false;
+				let glyph = pending.take().ok_or_else(error)?;
+				if let Some(encoding) = glyph.encoding {
+					let (ascent, _) = vertical_metrics(font_bbx, ascent, descent);
+					glyphs.push((encoding, build_glyph(glyph, font_bbx, ascent).ok_or_else(error)?));
+				}
+			} else {
+				glyph.rows.push(parse_hex_row(keyword).ok_or_else(error)?);
+			}
+			continue;
+		}
+
+		match keyword {
+			"FONTBOUNDINGBOX" => font_bbx = Some(parse_bbx(&values).ok_or_else(error)?),
+			"FONT_ASCENT" => ascent = Some(parse_number(&values, 0).ok_or_else(error)?),
+			"FONT_DESCENT" => descent = Some(parse_number(&values, 0).ok_or_else(error)?),
+			"DEFAULT_CHAR" => default_char = Some(parse_number(&values, 0).ok_or_else(error)?),
+			"STARTCHAR" => pending = Some(PendingGlyph::default()),
+			"ENCODING" => {
+				let glyph = pending.as_mut().ok_or_else(error)?;
+				let encoding: i64 = parse_number(&values, 0).ok_or_else(error)?;
+				glyph.encoding = u32::try_from(encoding).ok();
+			}
+			"DWIDTH" => {
+				let glyph = pending.as_mut().ok_or_else(error)?;
+				glyph.advance = Some(parse_number(&values, 0).ok_or_else(error)?);
+			}
+			"BBX" => {
+				let glyph = pending.as_mut().ok_or_else(error)?;
+				glyph.bbx = Some(parse_bbx(&values).ok_or_else(error)?);
+			}
+			"BITMAP" => {
+				pending.as_ref().ok_or_else(error)?;
+				in_bitmap = true;
+			}
+			"ENDFONT" => break,
+			_ => {}
+		}
+	}
+
+	if pending.is_some() {
+		return Err(FontError::InvalidBdf(source.lines().count()));
+	}
+
+	let (ascent, descent) = vertical_metrics(font_bbx, ascent, descent);
+	let default_glyph = default_char
+		.and_then(|default| glyphs.iter().find(|(encoding, _)| *encoding == default))
+		.map(|(_, glyph)| glyph.clone());
+	let glyphs = glyphs.into_iter()
+		.filter_map(|(encoding, glyph)| char::from_u32(encoding).map(|c| (c, glyph)))
+		.collect::<HashMap<_, _>>();
+
+	let line_height = ascent.checked_add(descent).ok_or(FontError::InvalidBdf(source.lines().count()))?;
+	Ok(BitmapFont::new(glyphs, line_height.max(0) as u32, default_glyph))
+}
+
+/// Returns the ascent and descent of the font, taking them from the bounding box if the font
+/// doesn't define the properties
+fn vertical_metrics(bbx: Option<BoundingBox>, ascent: Option<i32>, descent: Option<i32>) -> (i32, i32) {
+	let (_, height, _, y_offset) = bbx.unwrap_or_default();
+	(ascent.unwrap_or((height as i32).saturating_add(y_offset)), descent.unwrap_or(y_offset.saturating_neg()))
+}
+
+/// Builds the glyph with the parsed data, placing it inside the line according to the ascent
+fn build_glyph(pending: PendingGlyph, font_bbx: Option<BoundingBox>, ascent: i32) -> Option<Glyph> {
+	let (width, height, x_offset, y_offset) = pending.bbx.or(font_bbx)?;
+	// The width is bounded by the bytes of the rows, so a huge bounding box can't allocate more
+	if pending.rows.len() != height as usize || pending.rows.iter().any(|row| row.len() * 8 < width as usize) {
+		return None;
+	}
+	let mut coverage = Vec::with_capacity((width as usize).checked_mul(height as usize)?);
+	for row in &pending.rows {
+		for x in 0..width {
+			let byte = row.get((x / 8) as usize)?;
+			coverage.push(bit_coverage(*byte, x % 8));
+		}
+	}
+	Some(Glyph {
+		width,
+		height,
+		advance: pending.advance.unwrap_or(width),
+		x_offset,
+		y_offset: ascent.checked_sub(y_offset)?.checked_sub(i32::try_from(height).ok()?)?,
+		coverage,
+	})
+}
+
+/// Parses a bounding box definition: width, height, x offset and y offset
+fn parse_bbx(values: &[&str]) -> Option<BoundingBox> {
+	Some((parse_number(values, 0)?, parse_number(values, 1)?, parse_number(values, 2)?,
+	      parse_number(values, 3)?))
+}
+
+/// Parses the number in the given position of the values
+fn parse_number<T: std::str::FromStr>(values: &[&str], position: usize) -> Option<T> {
+	values.get(position)?.parse().ok()
+}
+
+/// Parses a row of the bitmap, written as an hexadecimal string
+fn parse_hex_row(row: &str) -> Option<Vec<u8>> {
+	if !row.len().is_multiple_of(2) {
+		return None;
+	}
+	(0..row.len()).step_by(2)
+		.map(|i| u8::from_str_radix(row.get(i..i + 2)?, 16).ok())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::font::{BitmapFont, Font};
+	use crate::font::font_error::FontError;
+
+	const FONT: &str = "STARTFONT 2.1
+FONT -Test-Fixed-Medium-R-Normal--4-40-75-75-C-40-ISO10646-1
+SIZE 4 75 75
+FONTBOUNDINGBOX 4 4 0 -1
+STARTPROPERTIES 3
+FONT_ASCENT 3
+FONT_DESCENT 1
+DEFAULT_CHAR 0
+ENDPROPERTIES
+CHARS 3
+STARTCHAR char0
+ENCODING 0
+DWIDTH 4 0
+BBX 4 4 0 -1
+BITMAP
+F0
+90
+90
+F0
+ENDCHAR
+STARTCHAR ntilde
+ENCODING 241
+DWIDTH 5 0
+BBX 3 2 1 0
+BITMAP
+A0
+E0
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+DWIDTH 4 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+	#[test]
+	fn parse_glyphs() {
+		let font = BitmapFont::from_bdf(FONT).unwrap();
+		assert_eq!(2, font.len());
+		assert_eq!(4, font.line_height());
+
+		let glyph = font.glyph('ñ').unwrap();
+		assert_eq!((3, 2, 5), (glyph.width, glyph.height, glyph.advance));
+		assert_eq!((1, 1), (glyph.x_offset, glyph.y_offset));
+		assert_eq!(vec![255, 0, 255, 255, 255, 255], glyph.coverage);
+	}
+
+	#[test]
+	fn default_char() {
+		let font = BitmapFont::from_bdf(FONT).unwrap();
+		let glyph = font.glyph('z').unwrap();
+		assert_eq!((4, 4, 0), (glyph.width, glyph.height, glyph.y_offset));
+		assert_eq!(font.glyph('\0'), Some(glyph));
+	}
+
+	#[test]
+	fn malformed_font() {
+		let truncated = FONT.replace("E0\n", "");
+		assert!(matches!(BitmapFont::from_bdf(&truncated), Err(FontError::InvalidBdf(27))));
+		let wrong_row = FONT.replace("A0\n", "ZZ\n");
+		assert!(matches!(BitmapFont::from_bdf(&wrong_row), Err(FontError::InvalidBdf(26))));
+		let huge = FONT.replace("BBX 3 2 1 0", "BBX 2147483648 2 0 0");
+		assert!(matches!(BitmapFont::from_bdf(&huge), Err(FontError::InvalidBdf(28))));
+		let wide = FONT.replace("BBX 3 2 1 0", "BBX 9 2 0 0");
+		assert!(matches!(BitmapFont::from_bdf(&wide), Err(FontError::InvalidBdf(28))));
+		let unfinished = FONT.replace("ENDCHAR\nENDFONT", "");
+		assert!(matches!(BitmapFont::from_bdf(&unfinished), Err(FontError::InvalidBdf(_))));
+		let tall = "STARTFONT 2.1\nFONT_ASCENT 2147483647\nFONT_DESCENT 1\nENDFONT\n";
+		assert!(matches!(BitmapFont::from_bdf(tall), Err(FontError::InvalidBdf(4))));
+	}
+
+}
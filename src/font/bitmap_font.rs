@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use crate::font::font_error::FontError;
+use crate::font::{bdf, psf, Font, Glyph};
+
+/// Bitmap font loaded at runtime from a font file. The supported formats are:
+/// * **BDF:** Glyph Bitmap Distribution Format, see [`BitmapFont::from_bdf`].
+/// * **PSF:** PC Screen Font, both versions 1 and 2, see [`BitmapFont::from_psf`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::font::BitmapFont;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new())?;
+/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window)?;
+/// let font = BitmapFont::open("/usr/share/consolefonts/Lat2-Terminus16.psf")?;
+/// canvas.draw_text_with_font((10, 10), "Hello world", &font, palette::WHITE);
+/// # Ok(()) }
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct BitmapFont {
+	glyphs: HashMap<char, Arc<Glyph>>,
+	line_height: u32,
+	default_glyph: Option<Arc<Glyph>>,
+}
+
+impl BitmapFont {
+
+	/// Builds a new font with the mapped glyphs
+	///
+	/// # Arguments
+	/// * `glyphs` - Glyph to use for each character
+	/// * `line_height` - Distance in pixels between the top of two consecutive lines
+	/// * `default_glyph` - Glyph to use for the characters without one of their own
+	///
+	pub fn new(glyphs: HashMap<char, Glyph>, line_height: u32, default_glyph: Option<Glyph>) -> Self {
+		Self {
+			glyphs: glyphs.into_iter().map(|(c, glyph)| (c, Arc::new(glyph))).collect(),
+			line_height,
+			default_glyph: default_glyph.map(Arc::new),
+		}
+	}
+
+	/// Loads the font stored in the specified file. The format is detected from the content.
+	///
+	/// # Errors
+	/// * [FontError::Io] if the file can't be read
+	/// * [FontError::UnknownFormat] if the file is neither a BDF nor a PSF font
+	/// * [FontError::InvalidBdf] or [FontError::InvalidPsf] if the font is malformed
+	///
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, FontError> {
+		Self::from_bytes(&std::fs::read(path)?)
+	}
+
+	/// Loads a font from its file content, detecting if it's a BDF or a PSF font
+	///
+	/// # Errors
+	/// * [FontError::UnknownFormat] if the data is neither a BDF nor a PSF font
+	/// * [FontError::InvalidBdf] or [FontError::InvalidPsf] if the font is malformed
+	///
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, FontError> {
+		if psf::is_psf(bytes) {
+			Self::from_psf(bytes)
+		} else if bytes.starts_with(b"STARTFONT") {
+			let source = std::str::from_utf8(bytes).map_err(|_| FontError::InvalidBdf(1))?;
+			Self::from_bdf(source)
+		} else {
+			Err(FontError::UnknownFormat)
+		}
+	}
+
+	/// Parses a font in the Glyph Bitmap Distribution Format. The encodings of the glyphs are
+	/// interpreted as Unicode code points and the glyph pointed by the `DEFAULT_CHAR` property, if
+	/// any, is used for the missing characters.
+	///
+	/// # Errors
+	/// [FontError::InvalidBdf] with the line of the first error found
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::font::{BitmapFont, Font};
+	/// let font = BitmapFont::from_bdf("STARTFONT 2.1
+	/// FONTBOUNDINGBOX 2 2 0 0
+	/// CHARS 1
+	/// STARTCHAR period
+	/// ENCODING 46
+	/// DWIDTH 2 0
+	/// BBX 1 1 0 0
+	/// BITMAP
+	/// 80
+	/// ENDCHAR
+	/// ENDFONT").unwrap();
+	/// assert_eq!(2, font.line_height());
+	/// assert!(font.glyph('.').is_some());
+	/// ```
+	///
+	pub fn from_bdf(source: &str) -> Result<Self, FontError> {
+		bdf::parse(source)
+	}
+
+	/// Parses a PC Screen Font, version 1 or 2. If the font has an Unicode table it's used to map
+	/// the characters to the glyphs, otherwise each glyph is mapped to the character with the code
+	/// point of its position.
+	///
+	/// # Errors
+	/// [FontError::InvalidPsf] if the header is unknown or the data is truncated
+	///
+	pub fn from_psf(bytes: &[u8]) -> Result<Self, FontError> {
+		psf::parse(bytes)
+	}
+
+	/// Returns the number of characters with a glyph in the font
+	pub fn len(&self) -> usize {
+		self.glyphs.len()
+	}
+
+	/// Returns if the font has no glyphs
+	pub fn is_empty(&self) -> bool {
+		self.glyphs.is_empty()
+	}
+
+}
+
+impl Font for BitmapFont {
+
+	fn line_height(&self) -> u32 {
+		self.line_height
+	}
+
+	fn glyph(&self, c: char) -> Option<Arc<Glyph>> {
+		self.glyphs.get(&c).or(self.default_glyph.as_ref()).cloned()
+	}
+
+}
@@ -0,0 +1,69 @@
+//! Throwable errors of the font API
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+/// Errors than can occur loading a [`BitmapFont`] or a [`TrueTypeFont`]
+///
+/// [`BitmapFont`]: crate::font::BitmapFont
+/// [`TrueTypeFont`]: crate::font::TrueTypeFont
+pub enum FontError {
+	/// The font file couldn't be read
+	Io(std::io::Error),
+	/// The data doesn't match any of the supported font formats
+	UnknownFormat,
+	/// The BDF font is malformed. Contains the number of the line where the error was found
+	InvalidBdf(usize),
+	/// The PC Screen Font is malformed. Contains the reason of the error
+	InvalidPsf(&'static str),
+	/// The data isn't a valid TrueType or OpenType font
+	InvalidTrueType,
+}
+
+impl FontError {
+	fn message(&self) -> String {
+		match self {
+			Self::Io(error) => format!("The font could not be read: {}", error),
+			Self::UnknownFormat => "The font format is not supported. It should be BDF or PSF".to_owned(),
+			Self::InvalidBdf(line) => format!("The BDF font is malformed at line {}", line),
+			Self::InvalidPsf(reason) => format!("The PSF font is malformed: {}", reason),
+			Self::InvalidTrueType => "The TrueType font is malformed".to_owned(),
+		}
+	}
+}
+
+impl Error for FontError {}
+
+impl Debug for FontError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl Display for FontError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl From<std::io::Error> for FontError {
+	fn from(error: std::io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::font::font_error::FontError;
+
+	#[test]
+	fn test_send() {
+		fn assert_send<T: Send>() {}
+		assert_send::<FontError>();
+	}
+
+	#[test]
+	fn test_sync() {
+		fn assert_sync<T: Sync>() {}
+		assert_sync::<FontError>();
+	}
+}
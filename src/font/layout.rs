@@ -0,0 +1,166 @@
+use crate::font::Font;
+
+/// Horizontal alignment of the lines of a text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+	/// Lines start at the left edge of the text box
+	#[default]
+	Left,
+	/// Lines are centered in the text box
+	Center,
+	/// Lines end at the right edge of the text box
+	Right,
+}
+
+/// Options to lay out a text in lines, used by [`Canvas::draw_text_layout`].
+///
+/// The text box, used to align the lines, is as wide as the maximum width when there's one and as
+/// the longest line otherwise.
+///
+/// # Example
+/// The following layout centers the text, wrapping it in lines of 200 pixels at most.
+/// ```rust
+/// # use ferrux_canvas::font::{Alignment, TextLayout};
+/// let layout = TextLayout::new().with_alignment(Alignment::Center).with_max_width(200);
+/// ```
+///
+/// [`Canvas::draw_text_layout`]: crate::canvas::Canvas::draw_text_layout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextLayout {
+	alignment: Alignment,
+	max_width: Option<u32>,
+}
+
+impl TextLayout {
+
+	/// Creates a new layout, aligned to the left and without wrapping
+	pub fn new() -> TextLayout {
+		TextLayout::default()
+	}
+
+	/// Sets the alignment of the lines
+	pub fn with_alignment(mut self, alignment: Alignment) -> TextLayout {
+		self.alignment = alignment;
+		self
+	}
+
+	/// Sets the maximum width in pixels of the lines. The lines exceeding it are wrapped between
+	/// words, a word wider than the maximum width is left alone in its line.
+	pub fn with_max_width(mut self, max_width: u32) -> TextLayout {
+		self.max_width = Some(max_width);
+		self
+	}
+
+	/// Returns the alignment of the lines
+	pub fn alignment(&self) -> Alignment {
+		self.alignment
+	}
+
+	/// Returns the maximum width of the lines, if any
+	pub fn max_width(&self) -> Option<u32> {
+		self.max_width
+	}
+
+	/// Splits the text in lines and returns each of them along with its horizontal offset inside
+	/// the text box. The width of the box is returned too.
+	pub(crate) fn lay_out(&self, font: &dyn Font, text: &str) -> (Vec<(String, u32)>, u32) {
+		let lines = text.split('\n')
+			.flat_map(|line| self.wrap(font, line))
+			.map(|line| {
+				let width = line_width(font, &line);
+				(line, width)
+			})
+			.collect::<Vec<_>>();
+		let box_width = self.max_width
+			.unwrap_or_else(|| lines.iter().map(|(_, width)| *width).max().unwrap_or(0));
+		let lines = lines.into_iter()
+			.map(|(line, width)| {
+				let free = box_width.saturating_sub(width);
+				let offset = match self.alignment {
+					Alignment::Left => 0,
+					Alignment::Center => free / 2,
+					Alignment::Right => free,
+				};
+				(line, offset)
+			})
+			.collect();
+		(lines, box_width)
+	}
+
+	/// Splits the line in the words fitting the maximum width
+	fn wrap(&self, font: &dyn Font, line: &str) -> Vec<String> {
+		let max_width = match self.max_width {
+			Some(max_width) => max_width,
+			None => return vec![line.to_owned()],
+		};
+		let mut lines = Vec::new();
+		let mut current = String::new();
+		// Width and last character of the current line, so only the new word has to be measured
+		let (mut width, mut previous) = (0, None);
+		for word in line.split(' ') {
+			let separator = if current.is_empty() { "" } else { " " };
+			let (joined, last) = advance(font, width, previous, separator);
+			let (candidate, last) = advance(font, joined, last, word);
+			if !current.is_empty() && candidate.max(0) as u32 > max_width {
+				lines.push(std::mem::replace(&mut current, word.to_owned()));
+				(width, previous) = advance(font, 0, None, word);
+			} else {
+				current.push_str(separator);
+				current.push_str(word);
+				(width, previous) = (candidate, last);
+			}
+		}
+		lines.push(current);
+		lines
+	}
+
+}
+
+/// Returns the width in pixels of a single line of text, applying the kerning of the font
+pub(crate) fn line_width(font: &dyn Font, line: &str) -> u32 {
+	advance(font, 0, None, line).0.max(0) as u32
+}
+
+/// Adds the advance of the characters of the text to the width of a line ending in the previous
+/// character, applying the kerning. Returns the new width and last character of the line.
+fn advance(font: &dyn Font, mut width: i64, mut previous: Option<char>, text: &str) -> (i64, Option<char>) {
+	for c in text.chars() {
+		if let Some(glyph) = font.glyph(c) {
+			width += glyph.advance as i64 + previous.map_or(0, |p| font.kerning(p, c)) as i64;
+			previous = Some(c);
+		}
+	}
+	(width, previous)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::font::{Alignment, DefaultFont, TextLayout};
+
+	fn lines(layout: TextLayout, text: &str) -> Vec<(String, u32)> {
+		layout.lay_out(&DefaultFont, text).0
+	}
+
+	#[test]
+	fn no_wrapping() {
+		let expected = vec![("lorem ipsum".to_owned(), 0), ("dolor".to_owned(), 0)];
+		assert_eq!(expected, lines(TextLayout::new(), "lorem ipsum\ndolor"));
+	}
+
+	#[test]
+	fn wrapping() {
+		let layout = TextLayout::new().with_max_width(90);
+		let expected = vec![("lorem ipsum".to_owned(), 0), ("dolor sit".to_owned(), 0),
+		                    ("consectetur".to_owned(), 0), ("".to_owned(), 0)];
+		assert_eq!(expected, lines(layout, "lorem ipsum dolor sit consectetur\n"));
+	}
+
+	#[test]
+	fn alignment() {
+		let centered = TextLayout::new().with_alignment(Alignment::Center);
+		assert_eq!(vec![("abcd".to_owned(), 0), ("ab".to_owned(), 8)], lines(centered, "abcd\nab"));
+		let right = centered.with_alignment(Alignment::Right).with_max_width(40);
+		assert_eq!(vec![("abcd".to_owned(), 8), ("ab".to_owned(), 24)], lines(right, "abcd\nab"));
+	}
+
+}
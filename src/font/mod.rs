@@ -0,0 +1,211 @@
+//! Fonts and text tools to write on the canvas
+pub use bitmap_font::BitmapFont;
+pub use builtin::DefaultFont;
+pub use layout::{Alignment, TextLayout};
+pub use truetype::TrueTypeFont;
+
+use std::sync::Arc;
+use crate::canvas::{Canvas, Point};
+use crate::color::Color;
+
+pub mod font_error;
+mod bdf;
+mod bitmap_font;
+mod builtin;
+mod layout;
+mod psf;
+mod truetype;
+
+/// Bitmap of a single character ready to be drawn on the canvas
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyph {
+	/// Width in pixels of the bitmap
+	pub width: u32,
+	/// Height in pixels of the bitmap
+	pub height: u32,
+	/// Horizontal distance in pixels from the start of this glyph to the start of the next one
+	pub advance: u32,
+	/// Horizontal displacement in pixels of the bitmap from the current drawing position
+	pub x_offset: i32,
+	/// Vertical displacement in pixels of the bitmap from the top of the line
+	pub y_offset: i32,
+	/// Coverage of each pixel of the glyph in row-major order, from `0` (empty) to `255` (full).
+	/// Bitmap fonts only use those two values while scalable fonts are anti-aliased.
+	pub coverage: Vec<u8>,
+}
+
+impl Glyph {
+
+	/// Returns the coverage of the pixel of the glyph in the given coordinates, zero if the
+	/// coordinates are out of the glyph
+	pub fn coverage(&self, x: u32, y: u32) -> u8 {
+		if x < self.width && y < self.height {
+			self.coverage[(y * self.width + x) as usize]
+		} else {
+			0
+		}
+	}
+
+	/// Returns if the pixel of the glyph in the given coordinates should be painted
+	pub fn is_set(&self, x: u32, y: u32) -> bool {
+		self.coverage(x, y) > 0
+	}
+
+}
+
+/// Source of the glyphs used to draw text
+pub trait Font {
+
+	/// Distance in pixels between the top of two consecutive lines of text
+	fn line_height(&self) -> u32;
+
+	/// Returns the glyph of the specified character or `None` if the font can't represent it. The
+	/// glyph is shared, so the fonts keeping their glyphs hand them out without copying them.
+	fn glyph(&self, c: char) -> Option<Arc<Glyph>>;
+
+	/// Returns the adjustment in pixels to apply to the distance between the pair of characters.
+	/// Fonts without kerning information can rely on the default implementation, returning zero.
+	fn kerning(&self, _left: char, _right: char) -> i32 {
+		0
+	}
+
+}
+
+/// Returns the size, as `(width, height)`, that the text would take drawn with the [`DefaultFont`].
+/// The width is the one of the longest line.
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::font::measure_text;
+/// assert_eq!((40, 13), measure_text("Hello"));
+/// assert_eq!((48, 26), measure_text("Hello\nWorld!"));
+/// ```
+///
+pub fn measure_text(text: &str) -> (u32, u32) {
+	measure_text_with_font(&DefaultFont, text)
+}
+
+/// Returns the size, as `(width, height)`, that the text would take drawn with the specified font.
+/// The width is the one of the longest line, using the advance of each glyph and the kerning.
+pub fn measure_text_with_font(font: &dyn Font, text: &str) -> (u32, u32) {
+	measure_text_layout(font, text, &TextLayout::default())
+}
+
+/// Returns the size, as `(width, height)`, that the text would take drawn with the specified font
+/// and layout. If the layout has a maximum width, that's the width of the text.
+pub fn measure_text_layout(font: &dyn Font, text: &str, layout: &TextLayout) -> (u32, u32) {
+	if text.is_empty() {
+		return (0, 0);
+	}
+	let (lines, width) = layout.lay_out(font, text);
+	(width, u32::try_from(lines.len()).unwrap_or(u32::MAX).saturating_mul(font.line_height()))
+}
+
+/// Returns the coverage of the bit of the byte, counting from the most significant one
+pub(crate) fn bit_coverage(byte: u8, bit: u32) -> u8 {
+	if byte & (0x80 >> bit) != 0 { u8::MAX } else { 0 }
+}
+
+/// Draws the text on the canvas using the specified font and layout, starting on the top-left
+/// position. The partially covered pixels of the glyphs are blended with the canvas.
+pub(crate) fn draw<C: Canvas + ?Sized>(canvas: &mut C, font: &dyn Font, position: Point, text: &str,
+                                       layout: &TextLayout, color: Color) {
+	let (x, y) = position;
+	let mut y = y as i64;
+	for (line, offset) in layout.lay_out(font, text).0 {
+		// A line starting a whole line below the bottom of the canvas can't reach it
+		if y >= canvas.height() as i64 + font.line_height() as i64 {
+			break;
+		}
+		let mut pen = x as i64 + offset as i64;
+		let mut previous = None;
+		for c in line.chars() {
+			let glyph = match font.glyph(c) {
+				Some(glyph) => glyph,
+				None => continue,
+			};
+			pen += previous.map_or(0, |p| font.kerning(p, c)) as i64;
+			draw_glyph(canvas, &glyph, (pen, y), &color);
+			pen += glyph.advance as i64;
+			previous = Some(c);
+		}
+		y += font.line_height() as i64;
+	}
+}
+
+/// Draws a single glyph with the pen in the given position
+fn draw_glyph<C: Canvas + ?Sized>(canvas: &mut C, glyph: &Glyph, pen: (i64, i64), color: &Color) {
+	for dy in 0..glyph.height {
+		for dx in 0..glyph.width {
+			let coverage = glyph.coverage(dx, dy);
+			let px = pen.0 + glyph.x_offset as i64 + dx as i64;
+			let py = pen.1 + glyph.y_offset as i64 + dy as i64;
+			let (px, py) = match (u32::try_from(px), u32::try_from(py)) {
+				(Ok(px), Ok(py)) => (px, py),
+				_ => continue,
+			};
+			match coverage {
+				0 => {}
+				u8::MAX => canvas.draw_pixel(px, py, *color),
+				_ => {
+					let a = (color.a as u32 * coverage as u32 / u8::MAX as u32) as u8;
+					canvas.blend_pixel(px, py, Color { a, ..*color });
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use crate::canvas::{Canvas, RasterCanvas};
+	use crate::canvas::headless::HeadlessCanvas;
+	use crate::color::palette;
+	use crate::font::{DefaultFont, Font, Glyph, measure_text, measure_text_with_font};
+
+	/// Font with lines as tall as possible and an empty glyph for every character
+	struct TallFont;
+
+	impl Font for TallFont {
+		fn line_height(&self) -> u32 {
+			u32::MAX
+		}
+
+		fn glyph(&self, _c: char) -> Option<Arc<Glyph>> {
+			Some(Arc::new(Glyph { width: 0, height: 0, advance: 1, x_offset: 0, y_offset: 0, coverage: Vec::new() }))
+		}
+	}
+
+	#[test]
+	fn measure_empty_text() {
+		assert_eq!((0, 0), measure_text(""));
+	}
+
+	#[test]
+	fn measure_multiline_text() {
+		assert_eq!((24, 39), measure_text("a\nabc\n"));
+		assert_eq!((16, 13), measure_text("ñá"));
+		assert_eq!((1, u32::MAX), measure_text_with_font(&TallFont, "a\nb\nc"));
+	}
+
+	#[test]
+	fn draw_text_at_the_bottom() {
+		let mut canvas = HeadlessCanvas::new(4, 4);
+		canvas.draw_text((0, u32::MAX - 5), "a\nb", palette::WHITE);
+		canvas.draw_text_with_font((0, 0), "a\nb\nc", &TallFont, palette::WHITE);
+		assert!(canvas.frame().pixels().iter().all(|pixel| *pixel == palette::BLACK));
+	}
+
+	#[test]
+	fn default_font_glyphs() {
+		let glyph = DefaultFont.glyph('!').unwrap();
+		assert_eq!((8, 13, 8), (glyph.width, glyph.height, glyph.advance));
+		assert!(glyph.is_set(3, 2));
+		assert!(!glyph.is_set(3, 9));
+		assert!(!glyph.is_set(8, 2));
+		assert_ne!(DefaultFont.glyph('A'), DefaultFont.glyph('\u{263a}'));
+		assert_eq!(DefaultFont.glyph('\u{263a}'), DefaultFont.glyph('\u{2603}'));
+	}
+
+}
@@ -0,0 +1,265 @@
+//! Parser of the PC Screen Font (PSF) fonts, versions 1 and 2
+use std::collections::HashMap;
+use crate::font::font_error::FontError;
+use crate::font::font_error::FontError::InvalidPsf;
+use crate::font::{bit_coverage, BitmapFont, Glyph};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE_512: u8 = 0x01;
+const PSF1_MODE_HAS_TAB: u8 = 0x02;
+const PSF1_MODE_HAS_SEQ: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xffff;
+const PSF1_START_SEQ: u16 = 0xfffe;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xff;
+const PSF2_START_SEQ: u8 = 0xfe;
+
+/// Returns if the data starts with the magic number of any PSF version
+pub fn is_psf(bytes: &[u8]) -> bool {
+	bytes.starts_with(&PSF1_MAGIC) || bytes.starts_with(&PSF2_MAGIC)
+}
+
+/// Parses the content of a PSF font
+pub fn parse(bytes: &[u8]) -> Result<BitmapFont, FontError> {
+	if bytes.starts_with(&PSF2_MAGIC) {
+		parse_psf2(bytes)
+	} else if bytes.starts_with(&PSF1_MAGIC) {
+		parse_psf1(bytes)
+	} else {
+		Err(InvalidPsf("unknown magic number"))
+	}
+}
+
+/// Parses a PSF1 font: 8 pixels wide glyphs with a four bytes header
+fn parse_psf1(bytes: &[u8]) -> Result<BitmapFont, FontError> {
+	let (mode, height) = match bytes.get(2..4) {
+		Some(&[mode, height]) => (mode, height as u32),
+		_ => return Err(InvalidPsf("truncated header")),
+	};
+	if height == 0 {
+		return Err(InvalidPsf("empty glyphs"));
+	}
+	let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+	let glyph_size = height as usize;
+	let bitmaps = glyph_bitmaps(&bytes[4..], count, glyph_size, 8, height)?;
+
+	let mappings = if mode & (PSF1_MODE_HAS_TAB | PSF1_MODE_HAS_SEQ) != 0 {
+		let start = glyphs_size(count, glyph_size)?.checked_add(4).ok_or(InvalidPsf("too many glyphs"))?;
+		let table = &bytes[start..];
+		let units = table.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+		psf1_unicode_table(units, count)
+	} else {
+		identity_mappings(count)
+	};
+
+	Ok(build_font(bitmaps, mappings, height))
+}
+
+/// Parses a PSF2 font, with a header of variable size and glyphs of any width
+fn parse_psf2(bytes: &[u8]) -> Result<BitmapFont, FontError> {
+	let field = |index: usize| bytes.get(4 * index..4 * index + 4)
+		.map(|field| u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+		.ok_or(InvalidPsf("truncated header"));
+	let header_size = field(2)? as usize;
+	let flags = field(3)?;
+	let count = field(4)? as usize;
+	let glyph_size = field(5)? as usize;
+	let height = field(6)?;
+	let width = field(7)?;
+
+	if width == 0 || height == 0 || glyph_size == 0 {
+		return Err(InvalidPsf("empty glyphs"));
+	}
+	if glyph_size < height as usize * (width as usize).div_ceil(8) {
+		return Err(InvalidPsf("glyph size too small for its dimensions"));
+	}
+	let data = bytes.get(header_size..).ok_or(InvalidPsf("truncated header"))?;
+	let bitmaps = glyph_bitmaps(data, count, glyph_size, width, height)?;
+
+	let mappings = if flags & PSF2_HAS_UNICODE_TABLE != 0 {
+		psf2_unicode_table(&data[glyphs_size(count, glyph_size)?..], count)
+	} else {
+		identity_mappings(count)
+	};
+
+	Ok(build_font(bitmaps, mappings, height))
+}
+
+/// Returns the size in bytes of the bitmaps of all the glyphs
+fn glyphs_size(count: usize, glyph_size: usize) -> Result<usize, FontError> {
+	count.checked_mul(glyph_size).ok_or(InvalidPsf("too many glyphs"))
+}
+
+/// Extracts the bitmaps of all the glyphs of the font. The sizes should be greater than zero.
+fn glyph_bitmaps(data: &[u8], count: usize, glyph_size: usize, width: u32, height: u32)
+                 -> Result<Vec<Glyph>, FontError> {
+	let data = data.get(..glyphs_size(count, glyph_size)?).ok_or(InvalidPsf("truncated glyph data"))?;
+	let row_size = (width as usize).div_ceil(8);
+	Ok(data.chunks_exact(glyph_size)
+		.map(|glyph| {
+			let coverage = glyph.chunks_exact(row_size)
+				.take(height as usize)
+				.flat_map(|row| (0..width as usize).map(move |x| bit_coverage(row[x / 8], x as u32 % 8)))
+				.collect();
+			Glyph {
+				width,
+				height,
+				advance: width,
+				x_offset: 0,
+				y_offset: 0,
+				coverage,
+			}
+		})
+		.collect())
+}
+
+/// Maps each glyph to the character with the code point of its position
+fn identity_mappings(count: usize) -> Vec<Vec<char>> {
+	(0..count as u32)
+		.map(|code| char::from_u32(code).into_iter().collect())
+		.collect()
+}
+
+/// Reads the PSF1 unicode table: a list of UCS-2 characters for each glyph terminated by 0xFFFF.
+/// The sequences of combining characters, started by 0xFFFE, are ignored.
+fn psf1_unicode_table(units: impl Iterator<Item = u16>, count: usize) -> Vec<Vec<char>> {
+	let mut mappings = vec![Vec::new(); count];
+	let mut glyph = 0;
+	let mut in_sequence = false;
+	for unit in units {
+		if glyph >= count {
+			break;
+		}
+		match unit {
+			PSF1_SEPARATOR => {
+				glyph += 1;
+				in_sequence = false;
+			}
+			PSF1_START_SEQ => in_sequence = true,
+			_ if in_sequence => {}
+			_ => mappings[glyph].extend(char::from_u32(unit as u32)),
+		}
+	}
+	mappings
+}
+
+/// Reads the PSF2 unicode table: a list of UTF-8 characters for each glyph terminated by 0xFF.
+/// The sequences of combining characters, started by 0xFE, are ignored.
+fn psf2_unicode_table(table: &[u8], count: usize) -> Vec<Vec<char>> {
+	let mut mappings = vec![Vec::new(); count];
+	for (glyph, entry) in table.split(|&byte| byte == PSF2_SEPARATOR).take(count).enumerate() {
+		let singles = entry.split(|&byte| byte == PSF2_START_SEQ).next().unwrap_or_default();
+		mappings[glyph].extend(String::from_utf8_lossy(singles).chars()
+			.filter(|&c| c != char::REPLACEMENT_CHARACTER));
+	}
+	mappings
+}
+
+/// Builds the font assigning each glyph to its characters
+fn build_font(bitmaps: Vec<Glyph>, mappings: Vec<Vec<char>>, height: u32) -> BitmapFont {
+	let default_glyph = bitmaps.first().cloned();
+	let glyphs = bitmaps.into_iter()
+		.zip(mappings)
+		.flat_map(|(glyph, chars)| chars.into_iter().map(move |c| (c, glyph.clone())))
+		.collect::<HashMap<_, _>>();
+	BitmapFont::new(glyphs, height, default_glyph)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::font::{BitmapFont, Font};
+	use crate::font::font_error::FontError;
+
+	fn psf1(mode: u8, table: &[u16]) -> Vec<u8> {
+		let count = if mode & 0x01 != 0 { 512 } else { 256 };
+		let mut bytes = vec![0x36, 0x04, mode, 2];
+		for i in 0..count {
+			bytes.extend([i as u8, 0x80]);
+		}
+		bytes.extend(table.iter().flat_map(|unit| unit.to_le_bytes()));
+		bytes
+	}
+
+	fn psf2(table: &[u8]) -> Vec<u8> {
+		let mut bytes = vec![0x72, 0xb5, 0x4a, 0x86];
+		let flags = if table.is_empty() { 0 } else { 1 };
+		for field in [0, 32, flags, 2, 6, 3, 10] {
+			bytes.extend(u32::to_le_bytes(field));
+		}
+		// Two glyphs of 10x3 pixels, each row taking two bytes
+		bytes.extend([0xff, 0xc0, 0x00, 0x00, 0x80, 0x40, 0x12, 0x00, 0x00, 0x00, 0x00, 0x00]);
+		bytes.extend(table);
+		bytes
+	}
+
+	#[test]
+	fn psf1_without_table() {
+		let font = BitmapFont::from_bytes(&psf1(0, &[])).unwrap();
+		assert_eq!(256, font.len());
+		assert_eq!(2, font.line_height());
+		let glyph = font.glyph('A').unwrap();
+		assert_eq!((8, 2, 8), (glyph.width, glyph.height, glyph.advance));
+		assert_eq!(vec![0, 255, 0, 0, 0, 0, 0, 255], glyph.coverage[..8]);
+	}
+
+	#[test]
+	fn psf1_with_table() {
+		let table = [0x41, 0x391, 0xffff, 0xfffe, 0x41, 0x301, 0xffff, 0x263a, 0xffff];
+		let font = BitmapFont::from_psf(&psf1(0x03, &table)).unwrap();
+		assert_eq!(3, font.len());
+		assert_eq!(font.glyph('A'), font.glyph('Α'));
+		assert!(font.glyph('☺').unwrap().is_set(0, 1));
+		assert_eq!(font.glyph('B'), font.glyph('\0'));
+	}
+
+	#[test]
+	fn psf2_with_table() {
+		let mut table = "a\u{e1}".as_bytes().to_vec();
+		table.extend([0xff, b'b', 0xfe]);
+		table.extend("b\u{301}".as_bytes());
+		table.push(0xff);
+		let font = BitmapFont::from_psf(&psf2(&table)).unwrap();
+		assert_eq!(3, font.len());
+		assert_eq!(3, font.line_height());
+
+		let glyph = font.glyph('\u{e1}').unwrap();
+		assert_eq!((10, 3, 10), (glyph.width, glyph.height, glyph.advance));
+		assert_eq!(Some(glyph), font.glyph('a'));
+		let set = font.glyph('b').unwrap().coverage.iter().enumerate()
+			.filter(|(_, &coverage)| coverage > 0).map(|(i, _)| i)
+			.collect::<Vec<_>>();
+		assert_eq!(vec![3, 6], set);
+	}
+
+	#[test]
+	fn psf2_without_table() {
+		let font = BitmapFont::from_psf(&psf2(&[])).unwrap();
+		assert_eq!(2, font.len());
+		let glyph = font.glyph('\0').unwrap();
+		assert!((0..10).all(|x| glyph.is_set(x, 0)));
+		assert!((0..10).all(|x| !glyph.is_set(x, 1)));
+		assert_eq!(vec![255, 0, 0, 0, 0, 0, 0, 0, 0, 255], glyph.coverage[20..]);
+	}
+
+	#[test]
+	fn invalid_psf() {
+		assert!(matches!(BitmapFont::from_psf(&[0x36, 0x04, 0x00]), Err(FontError::InvalidPsf(_))));
+		assert!(matches!(BitmapFont::from_psf(&psf2(&[])[..40]), Err(FontError::InvalidPsf(_))));
+		assert!(matches!(BitmapFont::from_psf(&[0x36, 0x04, 0x00, 0x00]), Err(FontError::InvalidPsf(_))));
+		let with_fields = |fields: &[(usize, u32)]| {
+			let mut bytes = psf2(&[]);
+			for &(index, value) in fields {
+				bytes[4 * index..4 * index + 4].copy_from_slice(&value.to_le_bytes());
+			}
+			BitmapFont::from_psf(&bytes)
+		};
+		assert!(matches!(with_fields(&[(7, 0), (5, 1)]), Err(FontError::InvalidPsf(_))));
+		assert!(matches!(with_fields(&[(6, 0)]), Err(FontError::InvalidPsf(_))));
+		assert!(matches!(with_fields(&[(5, 0), (6, 0), (7, 0)]), Err(FontError::InvalidPsf(_))));
+		assert!(matches!(with_fields(&[(4, u32::MAX), (5, u32::MAX)]), Err(FontError::InvalidPsf(_))));
+		assert!(matches!(BitmapFont::from_bytes(b"GIF89a"), Err(FontError::UnknownFormat)));
+	}
+
+}
@@ -0,0 +1,286 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use ab_glyph::{Font as _, FontArc, PxScale, ScaleFont};
+use crate::font::font_error::FontError;
+use crate::font::{Font, Glyph};
+
+/// Scalable TrueType or OpenType font. The glyphs are rasterized with anti-aliasing at the
+/// current pixel size and cached, so each character is only rasterized once per size. The cache
+/// keeps up to [TrueTypeFont::MAX_CACHED_GLYPHS] glyphs and is emptied when it gets full, so drawing many sizes
+/// or characters doesn't make it grow without bounds.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::font::{Alignment, TextLayout, TrueTypeFont};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new())?;
+/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window)?;
+/// let font = TrueTypeFont::from_bytes(std::fs::read("fonts/DejaVuSans.ttf")?, 24.0)?;
+/// let layout = TextLayout::new().with_alignment(Alignment::Center).with_max_width(300);
+/// canvas.draw_text_layout((10, 10), "Press any key to start", &font, &layout, palette::WHITE);
+/// # Ok(()) }
+/// ```
+///
+pub struct TrueTypeFont {
+	font: FontArc,
+	size: f32,
+	cache: RefCell<HashMap<(char, u32), Arc<Glyph>>>,
+}
+
+impl TrueTypeFont {
+
+	/// Maximum number of rasterized glyphs kept in the cache
+	pub const MAX_CACHED_GLYPHS: usize = 4096;
+
+	/// Loads a font from the content of a TrueType or OpenType file
+	///
+	/// # Arguments
+	/// * `bytes` - Content of the font file
+	/// * `size` - Size in pixels of the font, being the height of its lines without the gap
+	///
+	/// # Errors
+	/// [FontError::InvalidTrueType] if the data isn't a valid font
+	///
+	pub fn from_bytes(bytes: Vec<u8>, size: f32) -> Result<Self, FontError> {
+		let font = FontArc::try_from_vec(bytes).map_err(|_| FontError::InvalidTrueType)?;
+		Ok(Self {
+			font,
+			size,
+			cache: RefCell::new(HashMap::new()),
+		})
+	}
+
+	/// Returns the current size in pixels of the font
+	pub fn size(&self) -> f32 {
+		self.size
+	}
+
+	/// Changes the size in pixels of the font. The glyphs already rasterized in other sizes are
+	/// kept in the cache.
+	pub fn set_size(&mut self, size: f32) {
+		self.size = size;
+	}
+
+	/// Removes all the rasterized glyphs from the cache
+	pub fn clear_cache(&mut self) {
+		self.cache.get_mut().clear();
+	}
+
+	/// Rasterizes the glyph of the character at the current size, placing it in the line
+	fn rasterize(&self, c: char) -> Glyph {
+		let font = self.font.as_scaled(PxScale::from(self.size));
+		let mut glyph = font.scaled_glyph(c);
+		glyph.position = ab_glyph::point(0.0, font.ascent());
+		let advance = font.h_advance(glyph.id).round() as u32;
+
+		match self.font.outline_glyph(glyph) {
+			Some(outline) => {
+				let bounds = outline.px_bounds();
+				let width = bounds.width() as u32;
+				let height = bounds.height() as u32;
+				let mut coverage = vec![0; width as usize * height as usize];
+				outline.draw(|x, y, c| {
+					if x < width && y < height {
+						coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8;
+					}
+				});
+				Glyph {
+					width,
+					height,
+					advance,
+					x_offset: bounds.min.x as i32,
+					y_offset: bounds.min.y as i32,
+					coverage,
+				}
+			}
+			None => Glyph {
+				width: 0,
+				height: 0,
+				advance,
+				x_offset: 0,
+				y_offset: 0,
+				coverage: Vec::new(),
+			},
+		}
+	}
+
+}
+
+impl Font for TrueTypeFont {
+
+	fn line_height(&self) -> u32 {
+		let font = self.font.as_scaled(PxScale::from(self.size));
+		(font.height() + font.line_gap()).ceil() as u32
+	}
+
+	fn glyph(&self, c: char) -> Option<Arc<Glyph>> {
+		let key = (c, self.size.to_bits());
+		let cached = self.cache.borrow().get(&key).cloned();
+		cached.or_else(|| {
+			let glyph = Arc::new(self.rasterize(c));
+			let mut cache = self.cache.borrow_mut();
+			if cache.len() >= Self::MAX_CACHED_GLYPHS {
+				cache.clear();
+			}
+			cache.insert(key, glyph.clone());
+			Some(glyph)
+		})
+	}
+
+	fn kerning(&self, left: char, right: char) -> i32 {
+		let font = self.font.as_scaled(PxScale::from(self.size));
+		font.kern(font.glyph_id(left), font.glyph_id(right)).round() as i32
+	}
+
+}
+
+impl std::fmt::Debug for TrueTypeFont {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "[TrueTypeFont] Size: {}, Cached glyphs: {}", self.size, self.cache.borrow().len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use crate::font::font_error::FontError;
+	use crate::font::{Font, TrueTypeFont};
+	use crate::font::layout::line_width;
+
+	/// Appends the big-endian bytes of 16 bits values
+	fn words(bytes: &mut Vec<u8>, values: &[i32]) {
+		bytes.extend(values.iter().flat_map(|&value| (value as u16).to_be_bytes()));
+	}
+
+	/// Simple glyph made of a single contour of on-curve points
+	fn outline(points: &[(i32, i32)]) -> Vec<u8> {
+		let xs = points.iter().map(|point| point.0);
+		let ys = points.iter().map(|point| point.1);
+		let mut bytes = Vec::new();
+		words(&mut bytes, &[1, xs.clone().min().unwrap(), ys.clone().min().unwrap(), xs.clone().max().unwrap(),
+			ys.clone().max().unwrap(), points.len() as i32 - 1, 0]);
+		bytes.extend(std::iter::repeat_n(0x01, points.len()));
+		let deltas = |values: Vec<i32>| (0..values.len())
+			.map(|i| values[i] - if i == 0 { 0 } else { values[i - 1] })
+			.collect::<Vec<_>>();
+		words(&mut bytes, &deltas(xs.collect()));
+		words(&mut bytes, &deltas(ys.collect()));
+		bytes
+	}
+
+	/// Font of 1000 units per em with an ascent of 800, so at 10 pixels a unit is 0.01 pixels.
+	/// It has a rectangle for 'I', a triangle for 'V', an empty space and a kerning of -100
+	/// units between 'I' and 'V'.
+	fn font(size: f32) -> TrueTypeFont {
+		let glyphs = [Vec::new(), outline(&[(100, 0), (100, 700), (400, 700), (400, 0)]),
+			outline(&[(0, 700), (500, 700), (250, 0)]), Vec::new()];
+		let advances = [500, 500, 500, 300];
+
+		let mut glyf = Vec::new();
+		let mut loca = Vec::new();
+		for glyph in &glyphs {
+			words(&mut loca, &[glyf.len() as i32 / 2]);
+			glyf.extend(glyph);
+			// The short offsets of the location table count words
+			glyf.resize(glyf.len().next_multiple_of(2), 0);
+		}
+		words(&mut loca, &[glyf.len() as i32 / 2]);
+
+		let mut head = Vec::new();
+		words(&mut head, &[1, 0, 1, 0, 0, 0, 0x5f0f, 0x3cf5, 0, 1000]);
+		head.extend([0; 16]);
+		words(&mut head, &[0, -200, 500, 800, 0, 8, 2, 0, 0]);
+		let mut hhea = Vec::new();
+		words(&mut hhea, &[1, 0, 800, -200, 0, 500, 0, 0, 500, 1, 0, 0, 0, 0, 0, 0, 0, glyphs.len() as i32]);
+		let mut hmtx = Vec::new();
+		words(&mut hmtx, &advances.iter().flat_map(|&advance| [advance, 0]).collect::<Vec<_>>());
+		let mut maxp = vec![0, 0, 0x50, 0];
+		words(&mut maxp, &[glyphs.len() as i32]);
+		// Format 12 subtable mapping ' ', 'I' and 'V'
+		let mut cmap = Vec::new();
+		words(&mut cmap, &[0, 1, 0, 4, 0, 12, 12, 0, 0, 52, 0, 0, 0, 3]);
+		for (c, glyph) in [(' ', 3), ('I', 1), ('V', 2)] {
+			words(&mut cmap, &[0, c as i32, 0, c as i32, 0, glyph]);
+		}
+		let mut kern = Vec::new();
+		words(&mut kern, &[0, 1, 0, 20, 0x0001, 1, 6, 0, 0, 1, 2, -100]);
+
+		let tables = [(b"cmap", cmap), (b"glyf", glyf), (b"head", head), (b"hhea", hhea), (b"hmtx", hmtx),
+			(b"kern", kern), (b"loca", loca), (b"maxp", maxp)];
+		let mut bytes = Vec::new();
+		words(&mut bytes, &[1, 0, tables.len() as i32, 128, 3, 0]);
+		let mut offset = 12 + 16 * tables.len();
+		for (tag, table) in &tables {
+			bytes.extend(*tag);
+			bytes.extend([0; 4]);
+			bytes.extend((offset as u32).to_be_bytes());
+			bytes.extend((table.len() as u32).to_be_bytes());
+			offset += table.len().next_multiple_of(4);
+		}
+		for (_, table) in &tables {
+			bytes.extend(table);
+			bytes.resize(bytes.len().next_multiple_of(4), 0);
+		}
+		TrueTypeFont::from_bytes(bytes, size).unwrap()
+	}
+
+	#[test]
+	fn rasterize() {
+		let font = font(10.0);
+		assert_eq!(10, font.line_height());
+		// The rectangle covers whole pixels: from 1 to 4 horizontally and up to 7 over the baseline
+		let glyph = font.glyph('I').unwrap();
+		assert_eq!((3, 7, 5), (glyph.width, glyph.height, glyph.advance));
+		assert_eq!((1, 1), (glyph.x_offset, glyph.y_offset));
+		assert!(glyph.coverage.iter().all(|&coverage| coverage == u8::MAX));
+		let space = font.glyph(' ').unwrap();
+		assert_eq!((0, 0, 3), (space.width, space.height, space.advance));
+	}
+
+	#[test]
+	fn antialiasing() {
+		let glyph = font(10.0).glyph('V').unwrap();
+		assert_eq!((5, 7), (glyph.width, glyph.height));
+		// The slanted edges cover pixels partially, while the middle of the top row is solid
+		assert!(glyph.coverage.iter().any(|&coverage| coverage > 0 && coverage < u8::MAX));
+		assert_eq!(u8::MAX, glyph.coverage[2]);
+		assert_eq!(0, glyph.coverage[6 * 5]);
+	}
+
+	#[test]
+	fn cache() {
+		let mut font = font(10.0);
+		assert!(Arc::ptr_eq(&font.glyph('I').unwrap(), &font.glyph('I').unwrap()));
+		font.set_size(20.0);
+		let glyph = font.glyph('I').unwrap();
+		assert_eq!((6, 14, 10), (glyph.width, glyph.height, glyph.advance));
+		assert_eq!(2, font.cache.borrow().len());
+		font.clear_cache();
+		assert!(font.cache.borrow().is_empty());
+
+		for c in ('\u{100}'..).take(TrueTypeFont::MAX_CACHED_GLYPHS + 1) {
+			font.glyph(c);
+		}
+		assert_eq!(1, font.cache.borrow().len());
+	}
+
+	#[test]
+	fn kerning() {
+		let mut font = font(10.0);
+		assert_eq!(-1, font.kerning('I', 'V'));
+		assert_eq!(0, font.kerning('V', 'I'));
+		assert_eq!(5 + 5 - 1, line_width(&font, "IV"));
+		font.set_size(20.0);
+		assert_eq!(-2, font.kerning('I', 'V'));
+	}
+
+	#[test]
+	fn invalid_font() {
+		assert!(matches!(TrueTypeFont::from_bytes(vec![0; 16], 10.0), Err(FontError::InvalidTrueType)));
+	}
+
+}
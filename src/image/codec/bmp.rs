@@ -0,0 +1,274 @@
+//! Decoding and encoding of Windows bitmaps
+use std::io::Write;
+use crate::color::Color;
+use crate::image::codec::pixel_count;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+const FILE_HEADER_SIZE: usize = 14;
+const CORE_HEADER_SIZE: u32 = 12;
+const INFO_HEADER_SIZE: u32 = 40;
+const V4_HEADER_SIZE: u32 = 108;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Color channel defined by a bit mask over the value of a pixel
+#[derive(Clone, Copy)]
+struct Channel {
+	mask: u32,
+	shift: u32,
+	max: u32,
+}
+
+impl Channel {
+
+	fn new(mask: u32) -> Self {
+		let shift = if mask == 0 { 0 } else { mask.trailing_zeros() };
+		Self { mask, shift, max: mask.checked_shr(shift).unwrap_or(0) }
+	}
+
+	/// Returns the value of the channel scaled to eight bits. The masks can take the whole 32 bits,
+	/// so it's scaled in 64 bits.
+	fn read(&self, value: u32) -> Option<u8> {
+		if self.max == 0 {
+			return None;
+		}
+		let (channel, max) = (((value & self.mask) >> self.shift) as u64, self.max as u64);
+		Some(((channel * 255 + max / 2) / max) as u8)
+	}
+
+}
+
+/// Decodes an uncompressed bitmap of 1, 4, 8, 16, 24 or 32 bits per pixel, including the ones
+/// with bit fields
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+	let malformed = |reason: &str| ImageError::Malformed(ImageFormat::Bmp, reason.to_owned());
+	let truncated = || malformed("truncated file");
+	let u16_at = |offset: usize| bytes.get(offset..offset + 2)
+		.map(|b| u16::from_le_bytes([b[0], b[1]]))
+		.ok_or_else(truncated);
+	let u32_at = |offset: usize| bytes.get(offset..offset + 4)
+		.map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+		.ok_or_else(truncated);
+
+	if !bytes.starts_with(b"BM") {
+		return Err(malformed("missing BM signature"));
+	}
+	let data_offset = u32_at(10)? as usize;
+	let header_size = u32_at(FILE_HEADER_SIZE)?;
+
+	let (width, height, bpp, compression) = if header_size == CORE_HEADER_SIZE {
+		(u16_at(18)? as i32, u16_at(20)? as i32, u16_at(24)?, BI_RGB)
+	} else if header_size >= INFO_HEADER_SIZE {
+		(u32_at(18)? as i32, u32_at(22)? as i32, u16_at(28)?, u32_at(30)?)
+	} else {
+		return Err(malformed("unknown header size"));
+	};
+	if width <= 0 || height == 0 {
+		return Err(malformed("invalid dimensions"));
+	}
+	let top_down = height < 0;
+	let (width, height) = (width as u32, height.unsigned_abs());
+	let count = pixel_count(ImageFormat::Bmp, width, height)?;
+
+	let header_end = FILE_HEADER_SIZE + header_size as usize;
+	let channels = match (compression, bpp) {
+		(BI_RGB, 16) => [Channel::new(0x7c00), Channel::new(0x03e0), Channel::new(0x001f), Channel::new(0)],
+		(BI_RGB, 32) => [Channel::new(0xff0000), Channel::new(0xff00), Channel::new(0xff), Channel::new(0)],
+		(BI_RGB, _) => [Channel::new(0); 4],
+		(BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => {
+			// The masks are part of the newer headers or follow the older ones
+			let alpha = if header_size >= INFO_HEADER_SIZE + 16 || compression == BI_ALPHABITFIELDS {
+				u32_at(FILE_HEADER_SIZE + 52)?
+			} else {
+				0
+			};
+			[Channel::new(u32_at(54)?), Channel::new(u32_at(58)?), Channel::new(u32_at(62)?),
+			 Channel::new(alpha)]
+		}
+		_ => return Err(ImageError::Unsupported(ImageFormat::Bmp, "compression")),
+	};
+
+	let palette = if bpp <= 8 {
+		let entry_size = if header_size == CORE_HEADER_SIZE { 3 } else { 4 };
+		let colors = match header_size {
+			CORE_HEADER_SIZE => 0,
+			_ => u32_at(46)? as usize,
+		};
+		let colors = if colors == 0 { 1 << bpp } else { colors };
+		let masks_size = if compression == BI_RGB || header_size > INFO_HEADER_SIZE { 0 } else { 12 };
+		let start = header_end + masks_size;
+		bytes.get(start..start + colors * entry_size)
+			.ok_or_else(truncated)?
+			.chunks_exact(entry_size)
+			.map(|bgr| Color { r: bgr[2], g: bgr[1], b: bgr[0], a: u8::MAX })
+			.collect::<Vec<_>>()
+	} else {
+		Vec::new()
+	};
+
+	let row_size = (width as usize * bpp as usize).div_ceil(32) * 4;
+	let data = bytes.get(data_offset..).ok_or_else(truncated)?;
+	if data.len() < row_size * height as usize {
+		return Err(truncated());
+	}
+
+	let mut pixels = Vec::with_capacity(count);
+	for y in 0..height as usize {
+		let row = if top_down { y } else { height as usize - 1 - y };
+		let row = &data[row * row_size..(row + 1) * row_size];
+		for x in 0..width as usize {
+			let color = match bpp {
+				1 | 4 | 8 => {
+					let bit = x * bpp as usize;
+					let shift = 8 - bpp as usize - bit % 8;
+					let index = (row[bit / 8] >> shift) as usize & ((1 << bpp) - 1);
+					palette.get(index).copied().ok_or_else(|| malformed("palette index out of range"))?
+				}
+				24 => Color { r: row[x * 3 + 2], g: row[x * 3 + 1], b: row[x * 3], a: u8::MAX },
+				16 | 32 => {
+					let value = if bpp == 16 {
+						u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32
+					} else {
+						u32::from_le_bytes([row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]])
+					};
+					Color {
+						r: channels[0].read(value).unwrap_or(0),
+						g: channels[1].read(value).unwrap_or(0),
+						b: channels[2].read(value).unwrap_or(0),
+						a: channels[3].read(value).unwrap_or(u8::MAX),
+					}
+				}
+				_ => return Err(ImageError::Unsupported(ImageFormat::Bmp, "bit depth")),
+			};
+			pixels.push(color);
+		}
+	}
+
+	Image::from_pixels(width, height, pixels).ok_or_else(truncated)
+}
+
+/// Encodes the image as a bottom-up bitmap of 32 bits per pixel, using a BITMAPV4HEADER with bit
+/// fields to keep the alpha channel
+pub fn encode<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	let too_big = || ImageError::Unsupported(ImageFormat::Bmp, "dimensions over 2147483647 pixels");
+	let width = i32::try_from(image.width()).map_err(|_| too_big())?;
+	let height = i32::try_from(image.height()).map_err(|_| too_big())?;
+	let offset = FILE_HEADER_SIZE as u32 + V4_HEADER_SIZE;
+	let data_size = image.width().checked_mul(image.height())
+		.and_then(|count| count.checked_mul(4))
+		.filter(|size| size.checked_add(offset).is_some())
+		.ok_or_else(too_big)?;
+
+	let mut header = Vec::with_capacity(offset as usize);
+	header.extend(b"BM");
+	header.extend((offset + data_size).to_le_bytes());
+	header.extend([0; 4]);
+	header.extend(offset.to_le_bytes());
+	header.extend(V4_HEADER_SIZE.to_le_bytes());
+	header.extend(width.to_le_bytes());
+	header.extend(height.to_le_bytes());
+	header.extend(1u16.to_le_bytes());
+	header.extend(32u16.to_le_bytes());
+	header.extend(BI_BITFIELDS.to_le_bytes());
+	header.extend(data_size.to_le_bytes());
+	header.extend([0; 16]);
+	for mask in [0x00ff0000u32, 0x0000ff00, 0x000000ff, 0xff000000] {
+		header.extend(mask.to_le_bytes());
+	}
+	header.extend(b"BGRs");
+	header.extend([0; 48]);
+	writer.write_all(&header)?;
+
+	let mut row = Vec::with_capacity(image.width() as usize * 4);
+	for y in (0..image.height()).rev() {
+		row.clear();
+		for x in 0..image.width() {
+			let [r, g, b, a] = image.pixel(x, y).map(Color::as_u8).unwrap_or_default();
+			row.extend([b, g, r, a]);
+		}
+		writer.write_all(&row)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+
+	/// Builds a bitmap with a BITMAPINFOHEADER
+	fn bitmap(width: i32, height: i32, bpp: u16, compression: u32, extra: &[u8], data: &[u8]) -> Vec<u8> {
+		let offset = 54 + extra.len() as u32;
+		let mut bytes = b"BM".to_vec();
+		bytes.extend((offset + data.len() as u32).to_le_bytes());
+		bytes.extend([0; 4]);
+		bytes.extend(offset.to_le_bytes());
+		bytes.extend(40u32.to_le_bytes());
+		bytes.extend(width.to_le_bytes());
+		bytes.extend(height.to_le_bytes());
+		bytes.extend(1u16.to_le_bytes());
+		bytes.extend(bpp.to_le_bytes());
+		bytes.extend(compression.to_le_bytes());
+		bytes.extend([0; 20]);
+		bytes.extend(extra);
+		bytes.extend(data);
+		bytes
+	}
+
+	#[test]
+	fn decode_24_bits() {
+		// Bottom-up: the first row in the file is the bottom one
+		let data = [255, 0, 0, 0, 0, 255, 0, 0, 0, 0, 255, 255, 255, 255, 0, 0];
+		let image = Image::decode(&bitmap(2, 2, 24, 0, &[], &data)).unwrap();
+		assert_eq!(vec![palette::RED, palette::WHITE, palette::BLUE, palette::RED], image.pixels());
+		let image = Image::decode(&bitmap(2, -2, 24, 0, &[], &data)).unwrap();
+		assert_eq!(vec![palette::BLUE, palette::RED, palette::RED, palette::WHITE], image.pixels());
+	}
+
+	#[test]
+	fn decode_paletted() {
+		let palette = [0, 0, 0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 255, 0, 0, 0];
+		let data = [0b0001_1011, 0, 0, 0];
+		let image = Image::decode(&bitmap(4, 1, 2, 0, &palette, &data));
+		assert!(matches!(image, Err(ImageError::Unsupported(ImageFormat::Bmp, _))));
+		let mut bytes = bitmap(3, 1, 4, 0, &palette, &[0x01, 0x23, 0, 0]);
+		bytes[46] = 4;
+		let image = Image::decode(&bytes).unwrap();
+		assert_eq!(vec![palette::BLACK, palette::GREEN, palette::WHITE], image.pixels());
+	}
+
+	#[test]
+	fn decode_bitfields() {
+		let masks = [0x00, 0xf8, 0x00, 0x00, 0xe0, 0x07, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00];
+		let data = [0x00, 0xf8, 0x1f, 0x00];
+		let image = Image::decode(&bitmap(2, 1, 16, 3, &masks, &data)).unwrap();
+		assert_eq!(vec![palette::RED, palette::BLUE], image.pixels());
+		let masks = [0xff, 0xff, 0xff, 0xff, 0x00, 0xff, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00];
+		let data = [0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00];
+		let image = Image::decode(&bitmap(2, 1, 32, 3, &masks, &data)).unwrap();
+		assert_eq!(vec![palette::WHITE, palette::BLACK], image.pixels());
+	}
+
+	#[test]
+	fn decode_truncated() {
+		let data = [255, 0, 0, 0, 0, 255];
+		let image = Image::decode(&bitmap(2, 2, 24, 0, &[], &data));
+		assert!(matches!(image, Err(ImageError::Malformed(ImageFormat::Bmp, _))));
+		assert!(matches!(Image::decode(b"BM\0\0"), Err(ImageError::Malformed(ImageFormat::Bmp, _))));
+	}
+
+	#[test]
+	fn encode_with_alpha() {
+		let mut image = Image::filled(3, 2, palette::GREEN);
+		image.set_pixel(2, 0, Color { r: 10, g: 20, b: 30, a: 40 });
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Bmp, &mut bytes).unwrap();
+		assert_eq!(122 + 3 * 2 * 4, bytes.len());
+		assert_eq!(image, Image::decode(&bytes).unwrap());
+	}
+
+}
@@ -0,0 +1,76 @@
+//! Decoding and encoding of [farbfeld](https://tools.suckless.org/farbfeld/) images
+use std::io::Write;
+use crate::color::Color;
+use crate::image::codec::pixel_count;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+const MAGIC: &[u8] = b"farbfeld";
+const HEADER_SIZE: usize = 16;
+
+fn malformed(reason: &str) -> ImageError {
+	ImageError::Malformed(ImageFormat::Farbfeld, reason.to_owned())
+}
+
+/// Decodes a farbfeld image, scaling its channels down to eight bits
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+	if bytes.len() < HEADER_SIZE || !bytes.starts_with(MAGIC) {
+		return Err(malformed("truncated header"));
+	}
+	let width = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+	let height = u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+	let count = pixel_count(ImageFormat::Farbfeld, width, height)?;
+
+	let data = bytes.get(HEADER_SIZE..HEADER_SIZE + count * 8).ok_or_else(|| malformed("truncated image data"))?;
+	let channel = |bytes: &[u8]| ((u16::from_be_bytes([bytes[0], bytes[1]]) as u32 * 255 + 32767) / 65535) as u8;
+	let pixels = data.chunks_exact(8)
+		.map(|pixel| Color {
+			r: channel(&pixel[0..2]),
+			g: channel(&pixel[2..4]),
+			b: channel(&pixel[4..6]),
+			a: channel(&pixel[6..8]),
+		})
+		.collect();
+
+	Image::from_pixels(width, height, pixels).ok_or_else(|| malformed("truncated image data"))
+}
+
+/// Encodes the image as farbfeld, scaling its channels up to sixteen bits
+pub fn encode<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	let mut bytes = MAGIC.to_vec();
+	bytes.extend(image.width().to_be_bytes());
+	bytes.extend(image.height().to_be_bytes());
+	bytes.extend(image.pixels().iter()
+		.flat_map(|color| color.as_u8())
+		.flat_map(|channel| (channel as u16 * 257).to_be_bytes()));
+
+	writer.write_all(&bytes)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+
+	#[test]
+	fn decode_sixteen_bits() {
+		let mut bytes = b"farbfeld\0\0\0\x02\0\0\0\x01".to_vec();
+		bytes.extend([0xff, 0xff, 0, 0, 0, 0, 0xff, 0xff]);
+		bytes.extend([0x80, 0x00, 0x00, 0x7f, 0x01, 0x00, 0x80, 0x80]);
+		let image = Image::decode(&bytes).unwrap();
+		assert_eq!(vec![palette::RED, Color { r: 128, g: 0, b: 1, a: 128 }], image.pixels());
+		assert!(matches!(Image::decode(&bytes[..30]), Err(ImageError::Malformed(ImageFormat::Farbfeld, _))));
+	}
+
+	#[test]
+	fn encode_sixteen_bits() {
+		let image = Image::from_pixels(1, 1, vec![Color { r: 255, g: 128, b: 1, a: 0 }]).unwrap();
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Farbfeld, &mut bytes).unwrap();
+		assert_eq!(b"farbfeld\0\0\0\x01\0\0\0\x01\xff\xff\x80\x80\x01\x01\0\0".to_vec(), bytes);
+		assert_eq!(image, Image::decode(&bytes).unwrap());
+	}
+
+}
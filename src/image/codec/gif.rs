@@ -0,0 +1,95 @@
+//! GIF decoding and encoding, relying on the [gif](https://crates.io/crates/gif) crate
+use std::io::Write;
+use ::gif::{ColorOutput, DecodeOptions, Encoder, EncodingError, Frame};
+use crate::color::Color;
+use crate::image::codec::pixel_count;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+/// Quantization speed used by default, from 1 (best quality) to 30 (fastest)
+pub(crate) const DEFAULT_SPEED: i32 = 10;
+
+/// Decodes the first frame of a GIF image
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+	let malformed = |error: ::gif::DecodingError| ImageError::Malformed(ImageFormat::Gif, error.to_string());
+
+	let mut options = DecodeOptions::new();
+	options.set_color_output(ColorOutput::RGBA);
+	let mut decoder = options.read_info(bytes).map_err(malformed)?;
+	let (width, height) = (decoder.width() as u32, decoder.height() as u32);
+	pixel_count(ImageFormat::Gif, width, height)?;
+	let frame = decoder.read_next_frame().map_err(malformed)?
+		.ok_or_else(|| ImageError::Malformed(ImageFormat::Gif, "no frames".to_owned()))?;
+	if frame.width == 0 {
+		return Err(ImageError::Malformed(ImageFormat::Gif, "frame with no width".to_owned()));
+	}
+
+	let mut image = Image::new(width, height);
+	let rows = frame.buffer.chunks_exact(frame.width as usize * 4);
+	for (y, row) in (frame.top as u32..).zip(rows) {
+		for (x, pixel) in (frame.left as u32..).zip(row.chunks_exact(4)) {
+			image.set_pixel(x, y, Color { r: pixel[0], g: pixel[1], b: pixel[2], a: pixel[3] });
+		}
+	}
+	Ok(image)
+}
+
+/// Encodes the image as a single frame GIF, quantizing its colors to a palette of 256 colors
+pub fn encode<W: Write>(image: &Image, writer: W) -> Result<(), ImageError> {
+	let frame = frame(image, DEFAULT_SPEED)?;
+	let mut encoder = Encoder::new(writer, frame.width, frame.height, &[]).map_err(encoding_error)?;
+	encoder.write_frame(&frame).map_err(encoding_error)
+}
+
+/// Builds a GIF frame from the image, quantizing its colors with the given speed. The fully
+/// transparent pixels are kept transparent.
+pub(crate) fn frame(image: &Image, speed: i32) -> Result<Frame<'static>, ImageError> {
+	let too_big = || ImageError::Unsupported(ImageFormat::Gif, "dimensions over 65535 pixels");
+	let width = u16::try_from(image.width()).map_err(|_| too_big())?;
+	let height = u16::try_from(image.height()).map_err(|_| too_big())?;
+	let mut data = image.pixels().iter().flat_map(|color| color.as_u8()).collect::<Vec<_>>();
+	Ok(Frame::from_rgba_speed(width, height, &mut data, speed))
+}
+
+/// Converts the errors of the encoder
+pub(crate) fn encoding_error(error: EncodingError) -> ImageError {
+	match error {
+		EncodingError::Io(error) => ImageError::Io(error),
+		error => ImageError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, error)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+
+	#[test]
+	fn encode_and_decode() {
+		let mut image = Image::filled(4, 2, palette::RED);
+		image.set_pixel(1, 1, palette::BLUE);
+		image.set_pixel(3, 0, Color { r: 0, g: 0, b: 0, a: 0 });
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Gif, &mut bytes).unwrap();
+		assert_eq!(Some(ImageFormat::Gif), ImageFormat::detect(&bytes));
+
+		let decoded = Image::decode(&bytes).unwrap();
+		assert_eq!((4, 2), (decoded.width(), decoded.height()));
+		assert_eq!(Some(&palette::RED), decoded.pixel(0, 0));
+		assert_eq!(Some(&palette::BLUE), decoded.pixel(1, 1));
+		assert_eq!(0, decoded.pixel(3, 0).unwrap().a);
+	}
+
+	#[test]
+	fn decode_malformed() {
+		let is_malformed = |bytes: &[u8]| matches!(Image::decode(bytes),
+			Err(ImageError::Malformed(ImageFormat::Gif, _)));
+		assert!(is_malformed(b"GIF89a\x01\x00"));
+		assert!(is_malformed(b"GIF89a\x01\x00\x01\x00\x00\x00\x00\x3b"));
+		// Image descriptor of a frame 0 pixels wide and 1 pixel high, followed by its empty data
+		assert!(is_malformed(b"GIF89a\x01\x00\x01\x00\x80\x00\x00\0\0\0\xff\xff\xff\
+			\x2c\0\0\0\0\x00\x00\x01\x00\x00\x02\x02\x44\x01\x00\x3b"));
+	}
+
+}
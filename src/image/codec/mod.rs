@@ -0,0 +1,141 @@
+//! Decoders and encoders of the supported image formats
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use crate::image::image_error::ImageError;
+use crate::image::Image;
+
+mod bmp;
+mod farbfeld;
+pub(crate) mod gif;
+mod netpbm;
+pub(crate) mod png;
+mod qoi;
+mod tga;
+
+/// Image file formats supported by the crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+	/// Portable Network Graphics
+	Png,
+	/// Windows bitmap
+	Bmp,
+	/// Netpbm formats: PBM, PGM and PPM, both in plain and raw variants. Images are encoded as
+	/// raw PPM, dropping the alpha channel.
+	Pnm,
+	/// Quite OK Image format
+	Qoi,
+	/// Truevision TGA, also known as TARGA. Images can be encoded, but not decoded.
+	Tga,
+	/// Lossless image format of the suckless project, with 16 bits per channel
+	Farbfeld,
+	/// Graphics Interchange Format. Only the first frame is decoded and the colors are quantized
+	/// to a palette of 256 colors when encoding.
+	Gif,
+}
+
+impl ImageFormat {
+
+	/// Detects the format of an image from the first bytes of its content
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::image::ImageFormat;
+	/// assert_eq!(Some(ImageFormat::Qoi), ImageFormat::detect(b"qoif\0\0\0\x01"));
+	/// assert_eq!(None, ImageFormat::detect(b"RIFF"));
+	/// ```
+	///
+	pub fn detect(bytes: &[u8]) -> Option<ImageFormat> {
+		match bytes {
+			[0x89, b'P', b'N', b'G', ..] => Some(ImageFormat::Png),
+			[b'B', b'M', ..] => Some(ImageFormat::Bmp),
+			[b'P', b'1'..=b'6', ..] => Some(ImageFormat::Pnm),
+			[b'q', b'o', b'i', b'f', ..] => Some(ImageFormat::Qoi),
+			[b'f', b'a', b'r', b'b', b'f', b'e', b'l', b'd', ..] => Some(ImageFormat::Farbfeld),
+			[b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(ImageFormat::Gif),
+			_ => None,
+		}
+	}
+
+	/// Returns the format matching the extension of a file name, ignoring the case
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::image::ImageFormat;
+	/// assert_eq!(Some(ImageFormat::Pnm), ImageFormat::from_extension("PPM"));
+	/// assert_eq!(None, ImageFormat::from_extension("webp"));
+	/// ```
+	///
+	pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+		match extension.to_ascii_lowercase().as_str() {
+			"png" => Some(ImageFormat::Png),
+			"bmp" | "dib" => Some(ImageFormat::Bmp),
+			"pbm" | "pgm" | "ppm" | "pnm" => Some(ImageFormat::Pnm),
+			"qoi" => Some(ImageFormat::Qoi),
+			"tga" | "icb" | "vda" | "vst" => Some(ImageFormat::Tga),
+			"ff" | "farbfeld" => Some(ImageFormat::Farbfeld),
+			"gif" => Some(ImageFormat::Gif),
+			_ => None,
+		}
+	}
+
+	/// Returns the usual extension of the files of the format
+	pub fn extension(&self) -> &'static str {
+		match self {
+			ImageFormat::Png => "png",
+			ImageFormat::Bmp => "bmp",
+			ImageFormat::Pnm => "ppm",
+			ImageFormat::Qoi => "qoi",
+			ImageFormat::Tga => "tga",
+			ImageFormat::Farbfeld => "ff",
+			ImageFormat::Gif => "gif",
+		}
+	}
+
+}
+
+impl Display for ImageFormat {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			ImageFormat::Png => "PNG",
+			ImageFormat::Bmp => "BMP",
+			ImageFormat::Pnm => "PNM",
+			ImageFormat::Qoi => "QOI",
+			ImageFormat::Tga => "TGA",
+			ImageFormat::Farbfeld => "farbfeld",
+			ImageFormat::Gif => "GIF",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+/// Decodes the image in the specified format
+pub fn decode(bytes: &[u8], format: ImageFormat) -> Result<Image, ImageError> {
+	match format {
+		ImageFormat::Png => png::decode(bytes),
+		ImageFormat::Bmp => bmp::decode(bytes),
+		ImageFormat::Pnm => netpbm::decode(bytes),
+		ImageFormat::Qoi => qoi::decode(bytes),
+		ImageFormat::Tga => Err(ImageError::Unsupported(ImageFormat::Tga, "decoding")),
+		ImageFormat::Farbfeld => farbfeld::decode(bytes),
+		ImageFormat::Gif => gif::decode(bytes),
+	}
+}
+
+/// Encodes the image in the specified format and writes it
+pub fn encode<W: Write>(image: &Image, format: ImageFormat, writer: W) -> Result<(), ImageError> {
+	match format {
+		ImageFormat::Png => png::encode(image, writer),
+		ImageFormat::Bmp => bmp::encode(image, writer),
+		ImageFormat::Pnm => netpbm::encode(image, writer),
+		ImageFormat::Qoi => qoi::encode(image, writer),
+		ImageFormat::Tga => tga::encode(image, writer),
+		ImageFormat::Farbfeld => farbfeld::encode(image, writer),
+		ImageFormat::Gif => gif::encode(image, writer),
+	}
+}
+
+/// Returns the number of pixels of an image of the given size, failing if it's too big to handle
+fn pixel_count(format: ImageFormat, width: u32, height: u32) -> Result<usize, ImageError> {
+	Image::pixel_count(width, height)
+		.ok_or_else(|| ImageError::Malformed(format, format!("size {}x{} is too big", width, height)))
+}
@@ -0,0 +1,193 @@
+//! Decoding of the Netpbm formats: PBM (P1, P4), PGM (P2, P5) and PPM (P3, P6), and encoding as
+//! raw PPM
+use std::io::Write;
+use crate::color::Color;
+use crate::image::codec::pixel_count;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+/// Reader of the whitespace separated tokens of the header and the plain formats, skipping the
+/// comments
+struct Tokens<'a> {
+	bytes: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Tokens<'a> {
+
+	/// Returns the next token, skipping the whitespaces and comments before it
+	fn next(&mut self) -> Option<&'a [u8]> {
+		loop {
+			match self.bytes.get(self.position)? {
+				b'#' => while !matches!(self.bytes.get(self.position), Some(b'\n' | b'\r') | None) {
+					self.position += 1;
+				},
+				byte if byte.is_ascii_whitespace() => self.position += 1,
+				_ => break,
+			}
+		}
+		let start = self.position;
+		while self.bytes.get(self.position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+			self.position += 1;
+		}
+		Some(&self.bytes[start..self.position])
+	}
+
+	/// Returns the next token parsed as a number
+	fn number(&mut self) -> Result<u32, ImageError> {
+		self.next()
+			.and_then(|token| std::str::from_utf8(token).ok())
+			.and_then(|token| token.parse().ok())
+			.ok_or_else(|| malformed("expected a number"))
+	}
+
+	/// Returns the next bit of a plain PBM image, which doesn't need whitespaces between them
+	fn bit(&mut self) -> Result<bool, ImageError> {
+		let token = self.next().ok_or_else(|| malformed("truncated image data"))?;
+		self.position -= token.len() - 1;
+		match token[0] {
+			b'0' => Ok(false),
+			b'1' => Ok(true),
+			_ => Err(malformed("expected a bit")),
+		}
+	}
+
+}
+
+fn malformed(reason: &str) -> ImageError {
+	ImageError::Malformed(ImageFormat::Pnm, reason.to_owned())
+}
+
+/// Decodes a PBM, PGM or PPM image in plain or raw format. Values with a maximum above 255 are
+/// scaled down to eight bits.
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+	let kind = match bytes {
+		[b'P', kind @ b'1'..=b'6', ..] => *kind,
+		[b'P', b'7', ..] => return Err(ImageError::Unsupported(ImageFormat::Pnm, "PAM")),
+		_ => return Err(malformed("unknown magic number")),
+	};
+	let mut tokens = Tokens { bytes, position: 2 };
+	let width = tokens.number()?;
+	let height = tokens.number()?;
+	if width == 0 || height == 0 {
+		return Err(malformed("empty image"));
+	}
+	let max = if matches!(kind, b'1' | b'4') { 1 } else { tokens.number()? };
+	if max == 0 || max > u16::MAX as u32 {
+		return Err(malformed("invalid maximum value"));
+	}
+	let count = pixel_count(ImageFormat::Pnm, width, height)?;
+	let scale = |value: u32| -> Result<u8, ImageError> {
+		if value > max {
+			return Err(malformed("value over the maximum"));
+		}
+		Ok(((value * 255 + max / 2) / max) as u8)
+	};
+	let gray = |level: u8| Color { r: level, g: level, b: level, a: u8::MAX };
+	let bit = |set: bool| if set { gray(0) } else { gray(u8::MAX) };
+
+	let pixels = match kind {
+		b'1' => (0..count).map(|_| tokens.bit().map(bit)).collect::<Result<Vec<_>, ImageError>>()?,
+		b'2' => (0..count).map(|_| Ok(gray(scale(tokens.number()?)?))).collect::<Result<Vec<_>, ImageError>>()?,
+		b'3' => (0..count)
+			.map(|_| Ok(Color {
+				r: scale(tokens.number()?)?,
+				g: scale(tokens.number()?)?,
+				b: scale(tokens.number()?)?,
+				a: u8::MAX,
+			}))
+			.collect::<Result<Vec<_>, ImageError>>()?,
+		_ => {
+			// A single whitespace separates the header from the raw data
+			let data = bytes.get(tokens.position + 1..).ok_or_else(|| malformed("truncated image data"))?;
+			match kind {
+				b'4' => {
+					let row_size = (width as usize).div_ceil(8);
+					let data = data.get(..row_size * height as usize)
+						.ok_or_else(|| malformed("truncated image data"))?;
+					data.chunks_exact(row_size)
+						.flat_map(|row| (0..width as usize).map(move |x| row[x / 8] & (0x80 >> (x % 8)) != 0))
+						.map(bit)
+						.collect()
+				}
+				_ => {
+					let channels = if kind == b'5' { 1 } else { 3 };
+					let sample_size = if max > u8::MAX as u32 { 2 } else { 1 };
+					let data = data.get(..count * channels * sample_size)
+						.ok_or_else(|| malformed("truncated image data"))?;
+					let samples = data.chunks_exact(sample_size)
+						.map(|sample| scale(sample.iter().fold(0, |value, &byte| value << 8 | byte as u32)))
+						.collect::<Result<Vec<_>, _>>()?;
+					samples.chunks_exact(channels)
+						.map(|pixel| match pixel {
+							[level] => gray(*level),
+							rgb => Color { r: rgb[0], g: rgb[1], b: rgb[2], a: u8::MAX },
+						})
+						.collect()
+				}
+			}
+		}
+	};
+
+	Image::from_pixels(width, height, pixels).ok_or_else(|| malformed("truncated image data"))
+}
+
+/// Encodes the image as a raw PPM of eight bits per channel. The alpha channel is dropped.
+pub fn encode<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	write!(writer, "P6\n{} {}\n255\n", image.width(), image.height())?;
+	let data = image.pixels().iter().flat_map(|color| [color.r, color.g, color.b]).collect::<Vec<_>>();
+	writer.write_all(&data)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+
+	#[test]
+	fn decode_plain() {
+		let image = Image::decode(b"P1\n# comment\n3 1\n010").unwrap();
+		assert_eq!(vec![palette::WHITE, palette::BLACK, palette::WHITE], image.pixels());
+		let image = Image::decode(b"P2 2 1 10\n0 5").unwrap();
+		assert_eq!(vec![palette::BLACK, Color { r: 128, g: 128, b: 128, a: 255 }], image.pixels());
+		let image = Image::decode(b"P3\n1 2\n255\n255 0 0\n0 0 255\n").unwrap();
+		assert_eq!(vec![palette::RED, palette::BLUE], image.pixels());
+	}
+
+	#[test]
+	fn decode_raw() {
+		let image = Image::decode(b"P4\n9 1\n\x80\x80").unwrap();
+		assert_eq!(Some(&palette::BLACK), image.pixel(0, 0));
+		assert_eq!(Some(&palette::BLACK), image.pixel(8, 0));
+		assert_eq!(7, image.pixels().iter().filter(|&color| *color == palette::WHITE).count());
+		let image = Image::decode(b"P5 1 1 65535\n\xff\xff").unwrap();
+		assert_eq!(vec![palette::WHITE], image.pixels());
+		let image = Image::decode(b"P6 2 1 255\n\x00\xff\x00\x00\x00\xff").unwrap();
+		assert_eq!(vec![palette::GREEN, palette::BLUE], image.pixels());
+	}
+
+	#[test]
+	fn decode_malformed() {
+		let is_malformed = |bytes: &[u8]| matches!(Image::decode(bytes),
+			Err(ImageError::Malformed(ImageFormat::Pnm, _)));
+		assert!(is_malformed(b"P6 2 1 255\n\x00\xff\x00"));
+		assert!(is_malformed(b"P2 2 1 10\n0 11"));
+		assert!(is_malformed(b"P3 x 1 255\n"));
+		assert!(is_malformed(b"P1 2 1\n0"));
+		assert!(is_malformed(b"P4\n0 1\n"));
+		assert!(is_malformed(b"P6 1 0 255\n"));
+		assert!(is_malformed(b"P2 0 0 255\n"));
+		assert!(matches!(Image::decode(b"P7\nWIDTH 1\n"), Err(ImageError::UnknownFormat)));
+	}
+
+	#[test]
+	fn encode_raw_ppm() {
+		let image = Image::from_pixels(2, 1, vec![palette::RED, Color { r: 1, g: 2, b: 3, a: 0 }]).unwrap();
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Pnm, &mut bytes).unwrap();
+		assert_eq!(b"P6\n2 1\n255\n\xff\x00\x00\x01\x02\x03".to_vec(), bytes);
+	}
+
+}
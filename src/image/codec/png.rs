@@ -0,0 +1,98 @@
+//! PNG decoding and encoding, relying on the [png](https://crates.io/crates/png) crate
+use std::io::Write;
+use ::png::{BitDepth, ColorType, Decoder, Encoder, EncodingError, Transformations};
+use crate::color::Color;
+use crate::image::codec::pixel_count;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+/// Decodes a PNG image. Paletted, grayscale and 16 bits images are expanded to 8 bits RGBA.
+/// Only the first frame of animated images is decoded.
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+	let malformed = |error: ::png::DecodingError| ImageError::Malformed(ImageFormat::Png, error.to_string());
+
+	let mut decoder = Decoder::new(bytes);
+	decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+	let mut reader = decoder.read_info().map_err(malformed)?;
+	pixel_count(ImageFormat::Png, reader.info().width, reader.info().height)?;
+	let mut buffer = vec![0; reader.output_buffer_size()];
+	let info = reader.next_frame(&mut buffer).map_err(malformed)?;
+
+	let data = &buffer[..info.buffer_size()];
+	let pixels = match info.color_type {
+		ColorType::Grayscale => data.iter().map(|&l| Color { r: l, g: l, b: l, a: u8::MAX }).collect(),
+		ColorType::GrayscaleAlpha => data.chunks_exact(2)
+			.map(|p| Color { r: p[0], g: p[0], b: p[0], a: p[1] })
+			.collect(),
+		ColorType::Rgb => data.chunks_exact(3)
+			.map(|p| Color { r: p[0], g: p[1], b: p[2], a: u8::MAX })
+			.collect(),
+		ColorType::Rgba => data.chunks_exact(4)
+			.map(|p| Color { r: p[0], g: p[1], b: p[2], a: p[3] })
+			.collect(),
+		ColorType::Indexed => return Err(ImageError::Unsupported(ImageFormat::Png, "unexpanded palette")),
+	};
+
+	Image::from_pixels(info.width, info.height, pixels)
+		.ok_or_else(|| ImageError::Malformed(ImageFormat::Png, "truncated image data".to_owned()))
+}
+
+/// Encodes the image as an eight bits RGBA PNG
+pub fn encode<W: Write>(image: &Image, writer: W) -> Result<(), ImageError> {
+	let mut encoder = Encoder::new(writer, image.width(), image.height());
+	encoder.set_color(ColorType::Rgba);
+	encoder.set_depth(BitDepth::Eight);
+	let data = image.pixels().iter().flat_map(|color| color.as_u8()).collect::<Vec<_>>();
+	encoder.write_header()
+		.and_then(|mut writer| writer.write_image_data(&data))
+		.map_err(encoding_error)
+}
+
+/// Converts the errors of the encoder
+pub(crate) fn encoding_error(error: EncodingError) -> ImageError {
+	match error {
+		EncodingError::IoError(error) => ImageError::Io(error),
+		error => ImageError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, error)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+
+	/// 2x1 RGBA image with a red and a translucent blue pixel
+	const RGBA: [u8; 71] = [
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+		0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0xf4, 0x22, 0x7f,
+		0x8a, 0x00, 0x00, 0x00, 0x0e, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xf8, 0xcf, 0xc0, 0x00,
+		0x42, 0x0d, 0x00, 0x0f, 0x7a, 0x03, 0x7e, 0x77, 0xe9, 0x7f, 0x97, 0x00, 0x00, 0x00, 0x00, 0x49,
+		0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+	];
+
+	#[test]
+	fn decode_rgba() {
+		let image = Image::decode(&RGBA).unwrap();
+		assert_eq!((2, 1), (image.width(), image.height()));
+		assert_eq!(Some(&palette::RED), image.pixel(0, 0));
+		assert_eq!(Some(&Color { r: 0, g: 0, b: 255, a: 128 }), image.pixel(1, 0));
+	}
+
+	#[test]
+	fn decode_corrupted() {
+		let mut corrupted = RGBA;
+		corrupted[45] = 0;
+		assert!(matches!(Image::decode(&corrupted), Err(ImageError::Malformed(ImageFormat::Png, _))));
+		assert!(matches!(Image::decode(&RGBA[..40]), Err(ImageError::Malformed(ImageFormat::Png, _))));
+	}
+
+	#[test]
+	fn encode_rgba() {
+		let image = Image::decode(&RGBA).unwrap();
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Png, &mut bytes).unwrap();
+		assert_eq!(image, Image::decode(&bytes).unwrap());
+	}
+
+}
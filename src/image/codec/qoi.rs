@@ -0,0 +1,229 @@
+//! Decoding and encoding of the Quite OK Image format, see the
+//! [specification](https://qoiformat.org/qoi-specification.pdf)
+use std::io::Write;
+use crate::color::Color;
+use crate::image::codec::pixel_count;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+const HEADER_SIZE: usize = 14;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const MASK: u8 = 0xc0;
+
+/// Initial value of the array of previously seen colors
+const UNSEEN: Color = Color { r: 0, g: 0, b: 0, a: 0 };
+
+/// Bytes marking the end of the stream
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+/// Longest run of repeated pixels of a single operation
+const MAX_RUN: u8 = 62;
+
+/// Position of the color in the array of previously seen colors
+fn hash(color: &Color) -> usize {
+	(color.r as usize * 3 + color.g as usize * 5 + color.b as usize * 7 + color.a as usize * 11) % 64
+}
+
+fn malformed(reason: &str) -> ImageError {
+	ImageError::Malformed(ImageFormat::Qoi, reason.to_owned())
+}
+
+/// Decodes a QOI image. The color space field of the header is ignored.
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+	if bytes.len() < HEADER_SIZE || !bytes.starts_with(b"qoif") {
+		return Err(malformed("truncated header"));
+	}
+	let width = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+	let height = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+	if !matches!(bytes[12], 3 | 4) {
+		return Err(malformed("invalid number of channels"));
+	}
+	let count = pixel_count(ImageFormat::Qoi, width, height)?;
+
+	let mut data = bytes[HEADER_SIZE..].iter().copied();
+	let mut next = || data.next().ok_or_else(|| malformed("truncated image data"));
+	let mut seen = [UNSEEN; 64];
+	let mut pixel = Color { r: 0, g: 0, b: 0, a: u8::MAX };
+	// A run takes one byte for up to 62 pixels, so a short file can't fill a huge image
+	let mut pixels = Vec::with_capacity(count.min((bytes.len() - HEADER_SIZE).saturating_mul(MAX_RUN as usize)));
+
+	while pixels.len() < count {
+		let op = next()?;
+		match op {
+			OP_RGB => {
+				pixel = Color { r: next()?, g: next()?, b: next()?, a: pixel.a };
+			}
+			OP_RGBA => {
+				pixel = Color { r: next()?, g: next()?, b: next()?, a: next()? };
+			}
+			_ => match op & MASK {
+				OP_INDEX => pixel = seen[op as usize],
+				OP_DIFF => {
+					pixel.r = pixel.r.wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+					pixel.g = pixel.g.wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+					pixel.b = pixel.b.wrapping_add(op & 0x03).wrapping_sub(2);
+				}
+				OP_LUMA => {
+					let dg = (op & 0x3f).wrapping_sub(32);
+					let second = next()?;
+					pixel.r = pixel.r.wrapping_add(dg).wrapping_add(second >> 4).wrapping_sub(8);
+					pixel.g = pixel.g.wrapping_add(dg);
+					pixel.b = pixel.b.wrapping_add(dg).wrapping_add(second & 0x0f).wrapping_sub(8);
+				}
+				_ => {
+					let run = (op & 0x3f) as usize + 1;
+					if pixels.len() + run > count {
+						return Err(malformed("run exceeds the image size"));
+					}
+					pixels.extend(std::iter::repeat_n(pixel, run - 1));
+				}
+			}
+		}
+		seen[hash(&pixel)] = pixel;
+		pixels.push(pixel);
+	}
+
+	Image::from_pixels(width, height, pixels).ok_or_else(|| malformed("truncated image data"))
+}
+
+/// Encodes the image as QOI, with three channels if all the pixels are opaque and four otherwise
+pub fn encode<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	let opaque = image.pixels().iter().all(|color| color.a == u8::MAX);
+	let mut bytes = b"qoif".to_vec();
+	bytes.extend(image.width().to_be_bytes());
+	bytes.extend(image.height().to_be_bytes());
+	bytes.extend([if opaque { 3 } else { 4 }, 0]);
+
+	let mut seen = [UNSEEN; 64];
+	let mut previous = Color { r: 0, g: 0, b: 0, a: u8::MAX };
+	let mut run = 0;
+	for pixel in image.pixels() {
+		if *pixel == previous {
+			run += 1;
+			if run == MAX_RUN {
+				bytes.push(OP_RUN | (run - 1));
+				run = 0;
+			}
+			continue;
+		}
+		if run > 0 {
+			bytes.push(OP_RUN | (run - 1));
+			run = 0;
+		}
+
+		let index = hash(pixel);
+		if seen[index] == *pixel {
+			bytes.push(OP_INDEX | index as u8);
+		} else if pixel.a != previous.a {
+			bytes.extend([OP_RGBA, pixel.r, pixel.g, pixel.b, pixel.a]);
+		} else {
+			let dr = pixel.r.wrapping_sub(previous.r) as i8;
+			let dg = pixel.g.wrapping_sub(previous.g) as i8;
+			let db = pixel.b.wrapping_sub(previous.b) as i8;
+			let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+			if [dr, dg, db].iter().all(|diff| (-2..=1).contains(diff)) {
+				bytes.push(OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+			} else if (-32..=31).contains(&dg) && (-8..=7).contains(&dr_dg) && (-8..=7).contains(&db_dg) {
+				bytes.extend([OP_LUMA | (dg + 32) as u8, ((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8]);
+			} else {
+				bytes.extend([OP_RGB, pixel.r, pixel.g, pixel.b]);
+			}
+		}
+		seen[index] = *pixel;
+		previous = *pixel;
+	}
+	if run > 0 {
+		bytes.push(OP_RUN | (run - 1));
+	}
+	bytes.extend(END_MARKER);
+
+	writer.write_all(&bytes)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::codec::qoi::END_MARKER;
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+
+	fn qoi(width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+		let mut bytes = b"qoif".to_vec();
+		bytes.extend(width.to_be_bytes());
+		bytes.extend(height.to_be_bytes());
+		bytes.extend([4, 0]);
+		bytes.extend(data);
+		bytes.extend(END_MARKER);
+		bytes
+	}
+
+	#[test]
+	fn decode_operations() {
+		let data = [
+			0xfe, 255, 0, 0,        // RGB: red
+			0xc1,                   // RUN: two more reds
+			0xff, 0, 0, 255, 128,   // RGBA: translucent blue
+			0x40 | 0b11_10_01,      // DIFF: r + 1, g + 0, b - 1
+			0x80 | 40, 0x88,        // LUMA: g + 8, r + 8, b + 8
+			50,                     // INDEX: red, as (255 * 3 + 255 * 11) % 64 = 50
+		];
+		let image = Image::decode(&qoi(7, 1, &data)).unwrap();
+		assert_eq!(vec![palette::RED, palette::RED, palette::RED,
+		                Color { r: 0, g: 0, b: 255, a: 128 },
+		                Color { r: 1, g: 0, b: 254, a: 128 },
+		                Color { r: 9, g: 8, b: 6, a: 128 },
+		                palette::RED], image.pixels());
+		let image = Image::decode(&qoi(2, 1, &[0xc0, 6])).unwrap();
+		assert_eq!(vec![palette::BLACK, Color { r: 0, g: 0, b: 0, a: 0 }], image.pixels());
+	}
+
+	#[test]
+	fn decode_malformed() {
+		let is_malformed = |bytes: &[u8]| matches!(Image::decode(bytes),
+			Err(ImageError::Malformed(ImageFormat::Qoi, _)));
+		assert!(is_malformed(&qoi(2, 2, &[0xc0])[..15]));
+		assert!(is_malformed(&qoi(2, 1, &[0xc5])));
+		assert!(is_malformed(b"qoif\0\0"));
+		// The biggest size allowed, but the data only covers a run
+		assert!(is_malformed(&qoi(1 << 14, 1 << 14, &[0xc0])));
+	}
+
+	#[test]
+	fn encode_operations() {
+		let pixels = vec![
+			palette::RED, palette::RED, palette::RED,
+			Color { r: 0, g: 0, b: 255, a: 128 },
+			Color { r: 1, g: 0, b: 254, a: 128 },
+			Color { r: 9, g: 8, b: 6, a: 128 },
+			palette::RED,
+		];
+		let image = Image::from_pixels(7, 1, pixels).unwrap();
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Qoi, &mut bytes).unwrap();
+		let data = [
+			0x40 | 0b01_10_10,      // DIFF: r - 1 wraps from the initial black to red
+			0xc1,                   // RUN: two more reds
+			0xff, 0, 0, 255, 128,   // RGBA: translucent blue
+			0x40 | 0b11_10_01,      // DIFF: r + 1, g + 0, b - 1
+			0x80 | 40, 0x88,        // LUMA: g + 8, r + 8, b + 8
+			50,                     // INDEX: red
+		];
+		assert_eq!(qoi(7, 1, &data), bytes);
+	}
+
+	#[test]
+	fn encode_long_runs() {
+		let image = Image::filled(100, 3, palette::WHITE);
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Qoi, &mut bytes).unwrap();
+		assert_eq!(3, bytes[12]);
+		assert_eq!(image, Image::decode(&bytes).unwrap());
+	}
+
+}
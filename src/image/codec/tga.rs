@@ -0,0 +1,52 @@
+//! Encoding of Truevision TGA images
+use std::io::Write;
+use crate::image::image_error::ImageError;
+use crate::image::{Image, ImageFormat};
+
+/// Signature at the end of the footer of the version 2.0 files
+const SIGNATURE: &[u8] = b"TRUEVISION-XFILE.\0";
+
+const TRUE_COLOR: u8 = 2;
+const TOP_TO_BOTTOM: u8 = 0x20;
+
+/// Encodes the image as an uncompressed 32 bits true-color TGA with the version 2.0 footer
+pub fn encode<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	let too_big = || ImageError::Unsupported(ImageFormat::Tga, "dimensions over 65535 pixels");
+	let width = u16::try_from(image.width()).map_err(|_| too_big())?;
+	let height = u16::try_from(image.height()).map_err(|_| too_big())?;
+
+	let mut bytes = vec![0, 0, TRUE_COLOR, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+	bytes.extend(width.to_le_bytes());
+	bytes.extend(height.to_le_bytes());
+	bytes.extend([32, TOP_TO_BOTTOM | 8]);
+	bytes.extend(image.pixels().iter().flat_map(|color| [color.b, color.g, color.r, color.a]));
+	bytes.extend([0; 8]);
+	bytes.extend(SIGNATURE);
+
+	writer.write_all(&bytes)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::{Image, ImageFormat};
+	use crate::image::codec::tga::SIGNATURE;
+
+	#[test]
+	fn encode_with_footer() {
+		let mut image = Image::filled(2, 2, palette::BLUE);
+		image.set_pixel(1, 0, Color { r: 1, g: 2, b: 3, a: 4 });
+		let mut bytes = Vec::new();
+		image.encode(ImageFormat::Tga, &mut bytes).unwrap();
+		assert_eq!(18 + 2 * 2 * 4 + 26, bytes.len());
+		assert_eq!([0, 0, 2], bytes[..3]);
+		assert_eq!([2, 0, 2, 0, 32, 0x28], bytes[12..18]);
+		assert_eq!([255, 0, 0, 255, 3, 2, 1, 4], bytes[18..26]);
+		assert!(bytes.ends_with(SIGNATURE));
+		assert!(matches!(Image::decode_format(&bytes, ImageFormat::Tga),
+			Err(ImageError::Unsupported(ImageFormat::Tga, _))));
+	}
+
+}
@@ -0,0 +1,342 @@
+//! Output of images on terminals with pixel graphics, through the
+//! [Sixel](https://vt100.net/docs/vt3xx-gp/chapter14.html) format or the
+//! [Kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/)
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::Write;
+use crate::color::Color;
+use crate::image::image_error::ImageError;
+use crate::image::Image;
+
+/// Most colors of the palette of a Sixel image supported by the usual terminals
+const SIXEL_COLORS: usize = 256;
+
+/// Levels of each channel of the palette used for the images with too many colors
+const CUBE_LEVELS: u32 = 6;
+
+/// Largest chunk of base64 data of a Kitty graphics command
+const KITTY_CHUNK: usize = 4096;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Writes the image as a Sixel escape sequence, drawing it at the position of the cursor of the
+/// terminal. Sixel images have a palette of up to 256 colors, so the images with more colors are
+/// reduced to a cube of 6x6x6 colors. The pixels with less than half opacity are left transparent
+/// and the rest are drawn opaque.
+///
+/// # Errors
+/// [ImageError::Io] if the writer fails
+///
+/// # Example
+/// ```no_run
+/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::image::graphics;
+/// let mut canvas = HeadlessCanvas::new(320, 200);
+/// canvas.fill_triangle((160, 0), (0, 199), (319, 199), palette::RED);
+/// graphics::write_sixel(canvas.frame(), std::io::stdout()).unwrap();
+/// ```
+///
+pub fn write_sixel<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	let (palette, indexes) = sixel_palette(image);
+	let width = image.width() as usize;
+	let mut output = format!("\x1bP0;1;0q\"1;1;{};{}", image.width(), image.height()).into_bytes();
+	for (i, color) in palette.iter().enumerate() {
+		let percent = |channel: u8| (channel as u32 * 100 + 127) / 255;
+		output.extend(format!("#{};2;{};{};{}", i, percent(color.r), percent(color.g), percent(color.b)).bytes());
+	}
+
+	let rows = indexes.chunks(width.max(1)).collect::<Vec<_>>();
+	for (band_index, band) in rows.chunks(6).enumerate() {
+		if band_index > 0 {
+			output.push(b'-');
+		}
+		let mut colors = band.iter().flat_map(|row| row.iter().flatten().copied()).collect::<Vec<_>>();
+		colors.sort_unstable();
+		colors.dedup();
+		for (i, &color) in colors.iter().enumerate() {
+			if i > 0 {
+				output.push(b'$');
+			}
+			let sixels = (0..width).map(|x| band.iter().enumerate()
+				.filter(|(_, row)| row[x] == Some(color))
+				.fold(0, |bits, (y, _)| bits | 1 << y))
+				.collect::<Vec<u8>>();
+			let used = sixels.iter().rposition(|&bits| bits != 0).map_or(0, |last| last + 1);
+			output.extend(format!("#{}", color).bytes());
+			write_sixel_runs(&mut output, &sixels[..used]);
+		}
+	}
+	output.extend(b"\x1b\\");
+	writer.write_all(&output)?;
+	writer.flush()?;
+	Ok(())
+}
+
+/// Writes the image with the Kitty graphics protocol, drawing it at the position of the cursor
+/// of the terminal with all its colors and alpha. The terminal is asked not to answer, so nothing
+/// is left to read on its input.
+///
+/// # Errors
+/// [ImageError::Io] if the writer fails
+///
+/// # Example
+/// ```no_run
+/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::image::graphics;
+/// let mut canvas = HeadlessCanvas::new(320, 200);
+/// canvas.fill_triangle((160, 0), (0, 199), (319, 199), palette::RED);
+/// graphics::write_kitty(canvas.frame(), std::io::stdout()).unwrap();
+/// ```
+///
+pub fn write_kitty<W: Write>(image: &Image, mut writer: W) -> Result<(), ImageError> {
+	let data = image.pixels().iter().flat_map(|color| color.as_u8()).collect::<Vec<_>>();
+	let encoded = base64(&data);
+	let chunks = encoded.chunks(KITTY_CHUNK).collect::<Vec<_>>();
+	let mut output = Vec::with_capacity(encoded.len() + chunks.len() * 16 + 48);
+	output.extend(format!("\x1b_Ga=T,f=32,q=2,s={},v={}", image.width(), image.height()).bytes());
+	if chunks.is_empty() {
+		output.extend(b";\x1b\\");
+	}
+	for (i, chunk) in chunks.iter().enumerate() {
+		if i > 0 {
+			output.extend(b"\x1b_G");
+		} else {
+			output.push(b',');
+		}
+		let more = if i + 1 < chunks.len() { 1 } else { 0 };
+		output.extend(format!("m={};", more).bytes());
+		output.extend(*chunk);
+		output.extend(b"\x1b\\");
+	}
+	writer.write_all(&output)?;
+	writer.flush()?;
+	Ok(())
+}
+
+/// Returns the palette of the image and the index of each pixel in it, or none for the
+/// transparent pixels
+fn sixel_palette(image: &Image) -> (Vec<Color>, Vec<Option<usize>>) {
+	let opaque = |color: &Color| color.a >= 128;
+	let mut palette = Vec::new();
+	let mut positions = HashMap::new();
+	for color in image.pixels().iter().filter(|color| opaque(color)) {
+		let key = (color.r, color.g, color.b);
+		if let Entry::Vacant(entry) = positions.entry(key) {
+			if palette.len() == SIXEL_COLORS {
+				return cube_palette(image);
+			}
+			entry.insert(palette.len());
+			palette.push(Color { r: color.r, g: color.g, b: color.b, a: 255 });
+		}
+	}
+	let indexes = image.pixels().iter()
+		.map(|color| opaque(color).then(|| positions[&(color.r, color.g, color.b)]))
+		.collect();
+	(palette, indexes)
+}
+
+/// Returns the palette of the cube of colors and the index of the nearest color of each pixel
+fn cube_palette(image: &Image) -> (Vec<Color>, Vec<Option<usize>>) {
+	let max = CUBE_LEVELS - 1;
+	let value = |level: u32| (level * 255 / max) as u8;
+	let palette = (0..CUBE_LEVELS.pow(3))
+		.map(|i| Color {
+			r: value(i / CUBE_LEVELS.pow(2)),
+			g: value(i / CUBE_LEVELS % CUBE_LEVELS),
+			b: value(i % CUBE_LEVELS),
+			a: 255,
+		})
+		.collect();
+	let level = |channel: u8| (channel as u32 * max + 127) / 255;
+	let indexes = image.pixels().iter()
+		.map(|color| (color.a >= 128).then(|| {
+			((level(color.r) * CUBE_LEVELS + level(color.g)) * CUBE_LEVELS + level(color.b)) as usize
+		}))
+		.collect();
+	(palette, indexes)
+}
+
+/// Writes the sixels of a color in a band, compressing the runs of repeated sixels
+fn write_sixel_runs(output: &mut Vec<u8>, sixels: &[u8]) {
+	let mut i = 0;
+	while i < sixels.len() {
+		let run = sixels[i..].iter().take_while(|&&bits| bits == sixels[i]).count();
+		let character = b'?' + sixels[i];
+		if run > 3 {
+			output.extend(format!("!{}", run).bytes());
+			output.push(character);
+		} else {
+			output.extend(std::iter::repeat_n(character, run));
+		}
+		i += run;
+	}
+}
+
+/// Encodes the data in base64 with padding
+pub(crate) fn base64(data: &[u8]) -> Vec<u8> {
+	let mut encoded = Vec::with_capacity(data.len().div_ceil(3) * 4);
+	for chunk in data.chunks(3) {
+		let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+		for i in 0..4 {
+			match i <= chunk.len() {
+				true => encoded.push(BASE64[(bits >> (18 - i * 6)) as usize & 0x3f]),
+				false => encoded.push(b'='),
+			}
+		}
+	}
+	encoded
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use crate::color::{Color, palette};
+	use crate::image::graphics::{BASE64, write_kitty, write_sixel};
+	use crate::image::Image;
+
+	const TRANSPARENT: Color = Color { r: 0, g: 0, b: 0, a: 0 };
+
+	/// Decodes the Sixel images written by the encoder
+	fn decode_sixel(bytes: &[u8]) -> Image {
+		let text = std::str::from_utf8(bytes).unwrap();
+		let body = text.strip_prefix("\x1bP0;1;0q\"1;1;").unwrap().strip_suffix("\x1b\\").unwrap();
+		let (size, body) = body.split_at(body.find('#').unwrap_or(body.len()));
+		let (width, height) = size.split_once(';').unwrap();
+		let mut image = Image::filled(width.parse().unwrap(), height.parse().unwrap(), TRANSPARENT);
+
+		let mut colors = HashMap::new();
+		let (mut color, mut x, mut band) = (0, 0, 0);
+		let mut chars = body.chars().peekable();
+		let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+			let mut digits = String::new();
+			while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+				digits.push(digit);
+			}
+			digits.parse::<u32>().unwrap()
+		};
+		while let Some(c) = chars.next() {
+			match c {
+				'#' => {
+					color = number(&mut chars);
+					if chars.next_if_eq(&';').is_some() {
+						let channels = (0..4).map(|_| {
+							let value = number(&mut chars);
+							chars.next_if_eq(&';');
+							value
+						}).collect::<Vec<_>>();
+						assert_eq!(2, channels[0]);
+						let value = |percent: u32| ((percent * 255 + 50) / 100) as u8;
+						colors.insert(color, Color { r: value(channels[1]), g: value(channels[2]), b: value(channels[3]), a: 255 });
+					}
+				}
+				'$' => x = 0,
+				'-' => {
+					x = 0;
+					band += 1;
+				}
+				'!' | '?'..='~' => {
+					let run = if c == '!' { number(&mut chars) } else { 1 };
+					let bits = if c == '!' { chars.next().unwrap() } else { c } as u32 - '?' as u32;
+					for _ in 0..run {
+						for y in (0..6).filter(|y| bits & 1 << y != 0) {
+							image.set_pixel(x, band * 6 + y, colors[&color]);
+						}
+						x += 1;
+					}
+				}
+				c => panic!("unexpected character {:?}", c),
+			}
+		}
+		image
+	}
+
+	/// Decodes the RGBA images written with the Kitty graphics protocol
+	fn decode_kitty(bytes: &[u8]) -> Image {
+		let text = std::str::from_utf8(bytes).unwrap();
+		let mut commands = text.split("\x1b\\").filter(|command| !command.is_empty());
+		let (control, first) = commands.next().unwrap().strip_prefix("\x1b_G").unwrap().split_once(';').unwrap();
+		let keys = control.split(',').filter_map(|pair| pair.split_once('=')).collect::<HashMap<_, _>>();
+		assert_eq!(("T", "32", "2"), (keys["a"], keys["f"], keys["q"]));
+
+		let mut encoded = first.to_owned();
+		let mut more = keys.get("m") == Some(&"1");
+		for command in commands {
+			assert!(more);
+			let (control, chunk) = command.strip_prefix("\x1b_G").unwrap().split_once(';').unwrap();
+			more = control == "m=1";
+			encoded.push_str(chunk);
+		}
+		assert!(!more);
+
+		let sextets = encoded.bytes().filter(|&byte| byte != b'=')
+			.map(|byte| BASE64.iter().position(|&digit| digit == byte).unwrap() as u32)
+			.collect::<Vec<_>>();
+		let data = sextets.chunks(4)
+			.flat_map(|chunk| {
+				let bits = chunk.iter().enumerate().fold(0, |bits, (i, sextet)| bits | sextet << (18 - i * 6));
+				bits.to_be_bytes()[1..chunk.len()].to_vec()
+			})
+			.collect::<Vec<_>>();
+		Image::from_rgba_bytes(keys["s"].parse().unwrap(), keys["v"].parse().unwrap(), &data).unwrap()
+	}
+
+	#[test]
+	fn sixel_roundtrip() {
+		let mut image = Image::filled(5, 8, palette::BLUE);
+		image.set_pixel(0, 0, palette::RED);
+		image.set_pixel(4, 7, palette::WHITE);
+		image.set_pixel(2, 3, TRANSPARENT);
+		image.set_pixel(3, 6, Color { r: 128, g: 0, b: 0, a: 200 });
+		let mut bytes = Vec::new();
+		write_sixel(&image, &mut bytes).unwrap();
+		assert!(bytes.starts_with(b"\x1bP0;1;0q\"1;1;5;8#0;2;100;0;0#1;2;0;0;100"));
+		let mut expected = image.clone();
+		expected.set_pixel(3, 6, Color { r: 128, g: 0, b: 0, a: 255 });
+		assert_eq!(expected, decode_sixel(&bytes));
+	}
+
+	#[test]
+	fn sixel_runs() {
+		let mut bytes = Vec::new();
+		write_sixel(&Image::filled(10, 2, palette::GREEN), &mut bytes).unwrap();
+		assert_eq!(b"\x1bP0;1;0q\"1;1;10;2#0;2;0;100;0#0!10B\x1b\\".as_slice(), bytes.as_slice());
+	}
+
+	#[test]
+	fn sixel_reduced_colors() {
+		let pixels = (0..32 * 16)
+			.map(|i| Color { r: (i % 32 * 8) as u8, g: (i / 32 * 16) as u8, b: 100, a: 255 })
+			.collect();
+		let image = Image::from_pixels(32, 16, pixels).unwrap();
+		let mut bytes = Vec::new();
+		write_sixel(&image, &mut bytes).unwrap();
+		let decoded = decode_sixel(&bytes);
+		for (original, reduced) in image.pixels().iter().zip(decoded.pixels()) {
+			let distance = |a: u8, b: u8| (a as i32 - b as i32).abs();
+			assert!(distance(original.r, reduced.r) <= 26 && distance(original.g, reduced.g) <= 26);
+			assert_eq!(102, reduced.b);
+		}
+	}
+
+	#[test]
+	fn kitty_roundtrip() {
+		let pixels = (0..64 * 32)
+			.map(|i| Color { r: (i % 64 * 4) as u8, g: (i / 64 * 8) as u8, b: i as u8, a: (i % 7 * 40) as u8 })
+			.collect();
+		let image = Image::from_pixels(64, 32, pixels).unwrap();
+		let mut bytes = Vec::new();
+		write_kitty(&image, &mut bytes).unwrap();
+		assert!(bytes.starts_with(b"\x1b_Ga=T,f=32,q=2,s=64,v=32,m=1;"));
+		assert_eq!(image, decode_kitty(&bytes));
+
+		let mut bytes = Vec::new();
+		write_kitty(&Image::filled(1, 1, palette::RED), &mut bytes).unwrap();
+		assert_eq!(b"\x1b_Ga=T,f=32,q=2,s=1,v=1,m=0;/wAA/w==\x1b\\".as_slice(), bytes.as_slice());
+	}
+
+}
@@ -0,0 +1,73 @@
+//! Throwable errors of the image API
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use crate::image::ImageFormat;
+
+/// Errors than can occur decoding or encoding an [`Image`]
+///
+/// [`Image`]: crate::image::Image
+pub enum ImageError {
+	/// The image file couldn't be read or written
+	Io(std::io::Error),
+	/// The data doesn't match any of the supported image formats
+	UnknownFormat,
+	/// The image uses a feature of its format that is not supported, or can't be encoded in the
+	/// format. Contains the feature.
+	Unsupported(ImageFormat, &'static str),
+	/// The image is malformed. Contains the reason of the error.
+	Malformed(ImageFormat, String),
+	/// A recording received a frame of a different size than the previous ones. Contains the size
+	/// of the recording and the one of the frame.
+	FrameSize((u32, u32), (u32, u32)),
+}
+
+impl ImageError {
+	fn message(&self) -> String {
+		match self {
+			Self::Io(error) => format!("The image could not be read or written: {}", error),
+			Self::UnknownFormat => "The image format is not supported. It should be PNG, BMP, PNM, QOI, TGA, \
+				farbfeld or GIF".to_owned(),
+			Self::Unsupported(format, feature) => format!("{} images are not fully supported: {}", format, feature),
+			Self::Malformed(format, reason) => format!("The {} image is malformed: {}", format, reason),
+			Self::FrameSize(expected, found) => format!("The frame of {}x{} doesn't match the size of the recording, \
+				{}x{}", found.0, found.1, expected.0, expected.1),
+		}
+	}
+}
+
+impl Error for ImageError {}
+
+impl Debug for ImageError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl Display for ImageError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl From<std::io::Error> for ImageError {
+	fn from(error: std::io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::image::image_error::ImageError;
+
+	#[test]
+	fn test_send() {
+		fn assert_send<T: Send>() {}
+		assert_send::<ImageError>();
+	}
+
+	#[test]
+	fn test_sync() {
+		fn assert_sync<T: Sync>() {}
+		assert_sync::<ImageError>();
+	}
+}
@@ -0,0 +1,178 @@
+//! Images to draw on the canvas
+pub use codec::ImageFormat;
+pub use rgba_image::Image;
+pub use transform::{Filter, Transform};
+
+use crate::canvas::{Canvas, Point};
+
+pub mod graphics;
+pub mod image_error;
+pub mod quantize;
+pub(crate) mod codec;
+mod rgba_image;
+pub(crate) mod transform;
+
+/// Rectangular area of an image, in pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+	/// Horizontal coordinate of the top-left corner
+	pub x: u32,
+	/// Vertical coordinate of the top-left corner
+	pub y: u32,
+	/// Width of the area
+	pub width: u32,
+	/// Height of the area
+	pub height: u32,
+}
+
+impl Rect {
+
+	/// Creates a new rectangle with the top-left corner in `(x, y)` and the given size
+	pub fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+		Rect { x, y, width, height }
+	}
+
+	/// Returns the area shared by both rectangles, empty if they don't overlap
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::image::Rect;
+	/// let a = Rect::new(0, 0, 10, 10);
+	/// let b = Rect::new(5, 8, 10, 10);
+	/// assert_eq!(Rect::new(5, 8, 5, 2), a.intersection(&b));
+	/// ```
+	///
+	pub fn intersection(&self, other: &Rect) -> Rect {
+		let x = self.x.max(other.x);
+		let y = self.y.max(other.y);
+		let right = self.x.saturating_add(self.width).min(other.x.saturating_add(other.width));
+		let bottom = self.y.saturating_add(self.height).min(other.y.saturating_add(other.height));
+		Rect::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+	}
+
+	/// Returns if the rectangle has no area
+	pub fn is_empty(&self) -> bool {
+		self.width == 0 || self.height == 0
+	}
+
+}
+
+/// Mirroring to apply to an image when drawing it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Flip {
+	/// The image is drawn as it is
+	#[default]
+	None,
+	/// The image is mirrored left to right
+	Horizontal,
+	/// The image is mirrored top to bottom
+	Vertical,
+	/// The image is mirrored in both axes, like rotating it half a turn
+	Both,
+}
+
+impl Flip {
+
+	/// Returns the position of the source pixel to draw in the position `(x, y)` of the destination
+	fn source(&self, x: u32, y: u32, source: &Rect) -> (u32, u32) {
+		let mirror_x = source.x + source.width - 1 - x;
+		let mirror_y = source.y + source.height - 1 - y;
+		match self {
+			Flip::None => (source.x + x, source.y + y),
+			Flip::Horizontal => (mirror_x, source.y + y),
+			Flip::Vertical => (source.x + x, mirror_y),
+			Flip::Both => (mirror_x, mirror_y),
+		}
+	}
+
+}
+
+/// Draws the region of the image on the canvas, clipping it at the canvas edges. The fully opaque
+/// pixels replace the ones of the canvas, the translucent ones are blended and the fully
+/// transparent ones are skipped.
+pub(crate) fn draw<C: Canvas + ?Sized>(canvas: &mut C, dest: Point, image: &Image, source: Rect,
+                                       flip: Flip) {
+	let size = (canvas.width(), canvas.height());
+	for (x, y, color) in placements(dest, image, source, flip, size) {
+		match color.a {
+			0 => {}
+			u8::MAX => canvas.draw_pixel(x, y, *color),
+			_ => canvas.blend_pixel(x, y, *color),
+		}
+	}
+}
+
+/// Returns the pixels of the image to draw along with their position in the canvas
+fn placements(dest: Point, image: &Image, source: Rect, flip: Flip, canvas_size: (u32, u32))
+              -> impl Iterator<Item = (u32, u32, &crate::color::Color)> {
+	let source = source.intersection(&image.bounds());
+	let width = source.width.min(canvas_size.0.saturating_sub(dest.0));
+	let height = source.height.min(canvas_size.1.saturating_sub(dest.1));
+	(0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+		.filter_map(move |(x, y)| {
+			let (sx, sy) = flip.source(x, y, &source);
+			image.pixel(sx, sy).map(|color| (dest.0 + x, dest.1 + y, color))
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::{Flip, Image, placements, Rect};
+
+	fn image() -> Image {
+		let mut image = Image::new(3, 2);
+		image.set_pixel(0, 0, palette::RED);
+		image.set_pixel(2, 0, palette::GREEN);
+		image.set_pixel(2, 1, palette::BLUE);
+		image
+	}
+
+	fn drawn(dest: (u32, u32), source: Rect, flip: Flip, size: (u32, u32)) -> Vec<(u32, u32, Color)> {
+		placements(dest, &image(), source, flip, size)
+			.filter(|(_, _, color)| color.a > 0)
+			.map(|(x, y, color)| (x, y, *color))
+			.collect()
+	}
+
+	#[test]
+	fn rect_intersection() {
+		let rect = Rect::new(2, 2, 4, 4);
+		assert_eq!(rect, rect.intersection(&Rect::new(0, 0, 10, 10)));
+		assert!(rect.intersection(&Rect::new(6, 0, 4, 10)).is_empty());
+		assert!(rect.intersection(&Rect::new(20, 20, 4, 4)).is_empty());
+	}
+
+	#[test]
+	fn draw_whole_image() {
+		let expected = vec![(10, 5, palette::RED), (12, 5, palette::GREEN), (12, 6, palette::BLUE)];
+		assert_eq!(expected, drawn((10, 5), image().bounds(), Flip::None, (100, 100)));
+	}
+
+	#[test]
+	fn draw_flipped_image() {
+		let bounds = image().bounds();
+		let expected = vec![(0, 0, palette::GREEN), (2, 0, palette::RED), (0, 1, palette::BLUE)];
+		assert_eq!(expected, drawn((0, 0), bounds, Flip::Horizontal, (100, 100)));
+		let expected = vec![(2, 0, palette::BLUE), (0, 1, palette::RED), (2, 1, palette::GREEN)];
+		assert_eq!(expected, drawn((0, 0), bounds, Flip::Vertical, (100, 100)));
+		let expected = vec![(0, 0, palette::BLUE), (0, 1, palette::GREEN), (2, 1, palette::RED)];
+		assert_eq!(expected, drawn((0, 0), bounds, Flip::Both, (100, 100)));
+	}
+
+	#[test]
+	fn draw_image_region() {
+		let expected = vec![(1, 0, palette::GREEN), (1, 1, palette::BLUE)];
+		assert_eq!(expected, drawn((0, 0), Rect::new(1, 0, 5, 5), Flip::None, (100, 100)));
+		let expected = vec![(0, 0, palette::BLUE), (0, 1, palette::GREEN)];
+		assert_eq!(expected, drawn((0, 0), Rect::new(2, 0, 1, 2), Flip::Vertical, (100, 100)));
+	}
+
+	#[test]
+	fn clip_at_canvas_edges() {
+		let expected = vec![(8, 9, palette::RED)];
+		assert_eq!(expected, drawn((8, 9), image().bounds(), Flip::None, (10, 10)));
+		assert!(drawn((10, 0), image().bounds(), Flip::None, (10, 10)).is_empty());
+	}
+
+}
@@ -0,0 +1,489 @@
+//! Reduction of the colors of an image to a palette, building the palette with median-cut or
+//! octree quantization and hiding the banding with error-diffusion or ordered dithering
+use std::collections::HashMap;
+use crate::color::Color;
+use crate::color::palette::{self, Palette};
+use crate::image::Image;
+
+/// Depth of the octree, one level for each bit of the channels
+const OCTREE_DEPTH: usize = 8;
+
+/// Channels of a color as a key of the histograms and the caches
+type Key = (u8, u8, u8, u8);
+
+/// Algorithm used to choose the colors of the palette of an image
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Quantizer {
+	/// Splits the colors of the image in boxes along their widest channel until there is one box
+	/// per color, and takes the mean of each box. Slower, but keeps the small details.
+	#[default]
+	MedianCut,
+	/// Builds a tree of the colors by their bits and merges its deepest leaves until there are
+	/// enough colors. Faster, and favors the most frequent colors.
+	Octree,
+}
+
+impl Quantizer {
+
+	/// Returns a palette of up to `colors` colors representing the image. Each color is the mean of
+	/// the pixels it replaces, including their opacity. The fully transparent pixels are ignored.
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// # use ferrux_canvas::image::quantize::Quantizer;
+	/// let mut image = Image::filled(2, 2, palette::RED);
+	/// image.set_pixel(0, 0, palette::BLUE);
+	/// let palette = Quantizer::MedianCut.palette(&image, 16);
+	/// assert_eq!(2, palette.len());
+	/// ```
+	///
+	pub fn palette(&self, image: &Image, colors: usize) -> Palette {
+		let mut histogram = HashMap::new();
+		for color in image.pixels().iter().filter(|color| color.a > 0) {
+			*histogram.entry((color.r, color.g, color.b, color.a)).or_insert(0u64) += 1;
+		}
+		let histogram = histogram.into_iter().collect::<Vec<_>>();
+		if colors == 0 || histogram.is_empty() {
+			return Palette::new();
+		}
+		Palette::from_colors(match self {
+			Quantizer::MedianCut => median_cut(histogram, colors),
+			Quantizer::Octree => octree(&histogram, colors),
+		})
+	}
+
+}
+
+/// Method used to spread the difference between the colors of the image and the ones of the palette
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dither {
+	/// Each pixel takes the closest color of the palette, showing bands in the gradients
+	#[default]
+	None,
+	/// Spreads the whole error of each pixel to the next ones, giving the smoothest results
+	FloydSteinberg,
+	/// Spreads three quarters of the error of each pixel, keeping more contrast, like the early
+	/// Macintosh images
+	Atkinson,
+	/// Offsets each pixel with a threshold matrix of the given size, giving a regular crosshatch
+	/// pattern that doesn't change between frames. The size is a power of two up to 16, other
+	/// sizes are rounded down.
+	Bayer(u32),
+}
+
+/// Returns the index in the palette of each pixel of the image, row by row. The pixels are `0` if
+/// the palette is empty. The fully transparent pixels take the index of the closest color too,
+/// which is only transparent if the palette has a transparent entry.
+///
+/// # Arguments
+/// * `image` - Image to reduce
+/// * `palette` - Colors to use
+/// * `dither` - Dithering method
+///
+pub fn indices(image: &Image, palette: &Palette, dither: Dither) -> Vec<usize> {
+	let (width, height) = (image.width() as usize, image.height() as usize);
+	if palette.is_empty() {
+		return vec![0; width * height];
+	}
+	let mut mapper = Mapper { colors: palette.colors(), cache: HashMap::new() };
+	match dither {
+		Dither::None => image.pixels().iter().map(|color| mapper.nearest(channels(color))).collect(),
+		Dither::FloydSteinberg => {
+			let kernel = [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+			diffuse(image, &mut mapper, &kernel, 16.0)
+		}
+		Dither::Atkinson => {
+			let kernel = [(1, 0, 1.0), (2, 0, 1.0), (-1, 1, 1.0), (0, 1, 1.0), (1, 1, 1.0), (0, 2, 1.0)];
+			diffuse(image, &mut mapper, &kernel, 8.0)
+		}
+		Dither::Bayer(size) => {
+			let matrix = bayer(size);
+			let size = (matrix.len() as f32).sqrt() as usize;
+			let spread = 255.0 / (palette.len().max(2) as f32).cbrt();
+			(0..width * height).map(|i| {
+				let color = &image.pixels()[i];
+				let offset = (matrix[i / width % size * size + i % width % size] - 0.5) * spread;
+				let channel = |value: u8| value as f32 + offset;
+				mapper.nearest([channel(color.r), channel(color.g), channel(color.b), color.a as f32])
+			}).collect()
+		}
+	}
+}
+
+/// Returns a copy of the image with each pixel replaced by a color of the palette. The fully
+/// transparent pixels are kept, and the image is returned unchanged if the palette is empty.
+///
+/// # Arguments
+/// * `image` - Image to reduce
+/// * `palette` - Colors to use
+/// * `dither` - Dithering method
+///
+/// # Example
+/// The following example reduces a gradient to black and white, dithering it.
+/// ```rust
+/// # use ferrux_canvas::color::{Color, palette};
+/// # use ferrux_canvas::color::palette::Palette;
+/// # use ferrux_canvas::image::Image;
+/// # use ferrux_canvas::image::quantize::{self, Dither};
+/// let pixels = (0..64).map(|x| Color { r: x * 4, g: x * 4, b: x * 4, a: 255 }).collect();
+/// let gradient = Image::from_pixels(64, 1, pixels).unwrap();
+/// let bw = Palette::from_colors(vec![palette::BLACK, palette::WHITE]);
+/// let dithered = quantize::remap(&gradient, &bw, Dither::FloydSteinberg);
+/// assert!(dithered.pixels().iter().all(|color| *color == palette::BLACK || *color == palette::WHITE));
+/// ```
+///
+pub fn remap(image: &Image, palette: &Palette, dither: Dither) -> Image {
+	if palette.is_empty() {
+		return image.clone();
+	}
+	let pixels = indices(image, palette, dither).into_iter()
+		.zip(image.pixels())
+		.map(|(index, color)| if color.a == 0 { *color } else { palette.colors()[index] })
+		.collect();
+	Image::from_pixels(image.width(), image.height(), pixels).unwrap_or_else(|| image.clone())
+}
+
+/// Reduces the image to a palette of up to `colors` colors built with the quantizer, returning
+/// the palette and the reduced image
+///
+/// # Arguments
+/// * `image` - Image to reduce
+/// * `colors` - Maximum number of colors of the palette
+/// * `quantizer` - Algorithm choosing the colors of the palette
+/// * `dither` - Dithering method
+///
+/// # Example
+/// The following example reduces the frame of the canvas to 16 colors before saving it.
+/// ```no_run
+/// # use ferrux_canvas::canvas::{Canvas, RasterCanvas};
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::image::quantize::{self, Dither, Quantizer};
+/// let mut canvas = HeadlessCanvas::new(320, 200);
+/// // ...
+/// let (_, image) = quantize::reduce(canvas.frame(), 16, Quantizer::Octree, Dither::Bayer(4));
+/// image.save("frame.png").unwrap();
+/// ```
+///
+pub fn reduce(image: &Image, colors: usize, quantizer: Quantizer, dither: Dither) -> (Palette, Image) {
+	let palette = quantizer.palette(image, colors);
+	let image = remap(image, &palette, dither);
+	(palette, image)
+}
+
+/// Searcher of the closest colors of the palette, remembering the colors already found
+struct Mapper<'a> {
+	colors: &'a [Color],
+	cache: HashMap<Key, usize>,
+}
+
+impl Mapper<'_> {
+
+	/// Returns the index of the color of the palette closest to the channels, clamped to the range
+	/// of the colors
+	fn nearest(&mut self, channels: [f32; 4]) -> usize {
+		let [r, g, b, a] = channels.map(|channel| channel.round().clamp(0.0, 255.0) as u8);
+		let colors = self.colors;
+		*self.cache.entry((r, g, b, a))
+			.or_insert_with(|| palette::nearest(colors, &Color { r, g, b, a }).unwrap_or(0))
+	}
+
+}
+
+fn channels(color: &Color) -> [f32; 4] {
+	[color.r as f32, color.g as f32, color.b as f32, color.a as f32]
+}
+
+/// Maps the image to the palette from left to right and top to bottom, spreading the error of
+/// each pixel to the next ones with the weights of the kernel. The opacity is not dithered, and
+/// the fully transparent pixels don't spread their error.
+fn diffuse(image: &Image, mapper: &mut Mapper, kernel: &[(isize, usize, f32)], divisor: f32) -> Vec<usize> {
+	let (width, height) = (image.width() as usize, image.height() as usize);
+	let mut errors = vec![[0.0f32; 3]; width * height];
+	let mut indices = Vec::with_capacity(width * height);
+	for (i, color) in image.pixels().iter().enumerate() {
+		let mut wanted = channels(color);
+		for (channel, error) in wanted.iter_mut().zip(errors[i]) {
+			*channel += error;
+		}
+		let index = mapper.nearest(wanted);
+		indices.push(index);
+		if color.a == 0 {
+			continue;
+		}
+		let found = channels(&mapper.colors[index]);
+		let (x, y) = (i % width, i / width);
+		for &(dx, dy, weight) in kernel {
+			let (nx, ny) = (x as isize + dx, y + dy);
+			if nx < 0 || nx as usize >= width || ny >= height {
+				continue;
+			}
+			let target = &mut errors[ny * width + nx as usize];
+			for channel in 0..3 {
+				target[channel] += (wanted[channel] - found[channel]) * weight / divisor;
+			}
+		}
+	}
+	indices
+}
+
+/// Returns the normalized thresholds of the Bayer matrix of the size, row by row, centered in
+/// their cells so they average one half
+fn bayer(size: u32) -> Vec<f32> {
+	let size = 1usize << (31 - size.clamp(2, 16).leading_zeros());
+	let mut matrix = vec![0usize];
+	let mut current = 1;
+	while current < size {
+		let next = current * 2;
+		let mut grown = vec![0; next * next];
+		for y in 0..next {
+			for x in 0..next {
+				let quadrant = [0, 2, 3, 1][(y / current) * 2 + x / current];
+				grown[y * next + x] = 4 * matrix[(y % current) * current + x % current] + quadrant;
+			}
+		}
+		matrix = grown;
+		current = next;
+	}
+	let cells = (size * size) as f32;
+	matrix.into_iter().map(|value| (value as f32 + 0.5) / cells).collect()
+}
+
+/// Returns the mean color of the entries of the histogram
+fn mean(entries: &[(Key, u64)]) -> Color {
+	let mut sums = [0u64; 4];
+	let mut count = 0;
+	for ((r, g, b, a), weight) in entries {
+		for (sum, channel) in sums.iter_mut().zip([r, g, b, a]) {
+			*sum += *channel as u64 * weight;
+		}
+		count += weight;
+	}
+	let [r, g, b, a] = sums.map(|sum| ((sum + count / 2) / count.max(1)) as u8);
+	Color { r, g, b, a }
+}
+
+/// Returns the channel of the key, in the order red, green, blue and alpha
+fn channel(key: &Key, index: usize) -> u8 {
+	[key.0, key.1, key.2, key.3][index]
+}
+
+/// Median-cut quantization: splits the box with the widest channel in two halves of the same
+/// number of pixels, until there are enough boxes or no box can be split
+fn median_cut(histogram: Vec<(Key, u64)>, colors: usize) -> Vec<Color> {
+	let widest = |entries: &[(Key, u64)]| (0..4)
+		.map(|index| {
+			let values = entries.iter().map(|(key, _)| channel(key, index));
+			let range = values.clone().max().unwrap_or(0) - values.min().unwrap_or(0);
+			(range, index)
+		})
+		.max()
+		.unwrap_or((0, 0));
+
+	let mut boxes = vec![histogram];
+	while boxes.len() < colors {
+		let Some((position, (_, index))) = boxes.iter()
+			.map(|entries| widest(entries))
+			.enumerate()
+			.filter(|(_, (range, _))| *range > 0)
+			.max_by_key(|(_, (range, _))| *range) else {
+			break;
+		};
+		let mut entries = boxes.swap_remove(position);
+		entries.sort_unstable_by_key(|(key, _)| channel(key, index));
+		let total = entries.iter().map(|(_, count)| count).sum::<u64>();
+		let mut seen = 0;
+		let split = entries.iter()
+			.position(|(_, count)| {
+				seen += count;
+				seen * 2 >= total
+			})
+			.map_or(1, |median| median + 1)
+			.clamp(1, entries.len() - 1);
+		let upper = entries.split_off(split);
+		boxes.push(entries);
+		boxes.push(upper);
+	}
+	boxes.iter().map(|entries| mean(entries)).collect()
+}
+
+/// Node of the octree, with the sum of the channels of the colors inside it
+#[derive(Default)]
+struct Node {
+	children: [Option<usize>; 8],
+	sums: [u64; 4],
+	count: u64,
+	leaf: bool,
+}
+
+/// Octree quantization: inserts the colors in a tree with a level for each bit of the channels,
+/// and merges the nodes of the deepest levels into their parents until there are enough leaves
+fn octree(histogram: &[(Key, u64)], colors: usize) -> Vec<Color> {
+	let mut nodes = vec![Node::default()];
+	let mut levels = vec![Vec::new(); OCTREE_DEPTH];
+	let mut leaves = 0;
+	for &(key, count) in histogram {
+		let (r, g, b, a) = key;
+		let mut node = 0;
+		for level in 0..OCTREE_DEPTH {
+			let bit = 7 - level;
+			let octant = (((r >> bit) & 1) << 2 | ((g >> bit) & 1) << 1 | ((b >> bit) & 1)) as usize;
+			node = match nodes[node].children[octant] {
+				Some(child) => child,
+				None => {
+					let child = nodes.len();
+					nodes.push(Node { leaf: level + 1 == OCTREE_DEPTH, ..Node::default() });
+					nodes[node].children[octant] = Some(child);
+					if level + 1 == OCTREE_DEPTH {
+						leaves += 1;
+					} else {
+						levels[level + 1].push(child);
+					}
+					child
+				}
+			};
+		}
+		let leaf = &mut nodes[node];
+		for (sum, channel) in leaf.sums.iter_mut().zip([r, g, b, a]) {
+			*sum += channel as u64 * count;
+		}
+		leaf.count += count;
+	}
+	levels[0].push(0);
+
+	while leaves > colors {
+		let Some(node) = levels.iter_mut().rev().find_map(|level| level.pop()) else {
+			break;
+		};
+		let children = std::mem::take(&mut nodes[node].children);
+		let mut merged = 0;
+		for child in children.into_iter().flatten() {
+			let (sums, count) = (nodes[child].sums, nodes[child].count);
+			let parent = &mut nodes[node];
+			for (sum, child_sum) in parent.sums.iter_mut().zip(sums) {
+				*sum += child_sum;
+			}
+			parent.count += count;
+			merged += 1;
+		}
+		nodes[node].leaf = true;
+		leaves = leaves + 1 - merged;
+	}
+
+	let mut colors = Vec::new();
+	let mut pending = vec![0];
+	while let Some(node) = pending.pop() {
+		let node = &nodes[node];
+		if node.leaf {
+			let [r, g, b, a] = node.sums.map(|sum| ((sum + node.count / 2) / node.count.max(1)) as u8);
+			colors.push(Color { r, g, b, a });
+		} else {
+			pending.extend(node.children.iter().rev().flatten());
+		}
+	}
+	colors
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::color::palette::Palette;
+	use crate::image::Image;
+	use crate::image::quantize::{self, bayer, Dither, Quantizer};
+
+	fn gray(value: u8) -> Color {
+		Color { r: value, g: value, b: value, a: 255 }
+	}
+
+	fn gradient() -> Image {
+		let pixels = (0..16).flat_map(|_| (0..16).map(|x| gray(x * 17))).collect();
+		Image::from_pixels(16, 16, pixels).unwrap()
+	}
+
+	fn black_and_white() -> Palette {
+		Palette::from_colors(vec![palette::BLACK, palette::WHITE])
+	}
+
+	fn whites(image: &Image) -> usize {
+		image.pixels().iter().filter(|&color| *color == palette::WHITE).count()
+	}
+
+	#[test]
+	fn median_cut_palettes() {
+		let mut image = gradient();
+		image.set_pixel(0, 0, Color { r: 0, g: 0, b: 0, a: 0 });
+		assert_eq!(16, Quantizer::MedianCut.palette(&image, 64).len());
+		let palette = Quantizer::MedianCut.palette(&image, 2);
+		assert_eq!(&[gray(68), gray(204)], palette.colors());
+		assert_eq!(&[gray(128)], Quantizer::MedianCut.palette(&image, 1).colors());
+		assert!(Quantizer::MedianCut.palette(&image, 0).is_empty());
+		assert!(Quantizer::MedianCut.palette(&Image::new(2, 2), 4).is_empty());
+	}
+
+	#[test]
+	fn octree_palettes() {
+		let mut image = Image::filled(4, 4, palette::RED);
+		image.set_pixel(0, 0, palette::BLUE);
+		image.set_pixel(1, 0, Color { r: 0, g: 0, b: 250, a: 255 });
+		image.set_pixel(2, 0, palette::GREEN);
+		assert_eq!(&[Color { r: 0, g: 0, b: 253, a: 255 }, palette::GREEN, palette::RED],
+		           Quantizer::Octree.palette(&image, 3).colors());
+		assert_eq!(4, Quantizer::Octree.palette(&image, 8).len());
+		assert_eq!(16, Quantizer::Octree.palette(&gradient(), 16).len());
+		assert!(Quantizer::Octree.palette(&gradient(), 4).len() <= 4);
+	}
+
+	#[test]
+	fn remap_without_dithering() {
+		let image = quantize::remap(&gradient(), &black_and_white(), Dither::None);
+		assert_eq!(128, whites(&image));
+		assert_eq!(Some(&palette::BLACK), image.pixel(7, 0));
+		assert_eq!(Some(&palette::WHITE), image.pixel(8, 0));
+		assert_eq!(gradient(), quantize::remap(&gradient(), &Palette::new(), Dither::FloydSteinberg));
+		assert_eq!(vec![0; 256], quantize::indices(&gradient(), &Palette::new(), Dither::Atkinson));
+	}
+
+	#[test]
+	fn keep_transparent_pixels() {
+		let transparent = Color { r: 0, g: 0, b: 0, a: 0 };
+		let mut image = gradient();
+		image.set_pixel(3, 0, transparent);
+		image.set_pixel(15, 15, transparent);
+		for dither in [Dither::None, Dither::FloydSteinberg, Dither::Atkinson, Dither::Bayer(4)] {
+			let (palette, reduced) = quantize::reduce(&image, 4, Quantizer::Octree, dither);
+			assert_eq!(Some(&transparent), reduced.pixel(3, 0));
+			assert_eq!(Some(&transparent), reduced.pixel(15, 15));
+			assert_eq!(254, reduced.pixels().iter().filter(|color| palette.colors().contains(color)).count());
+		}
+	}
+
+	#[test]
+	fn error_diffusion() {
+		let image = Image::filled(16, 16, gray(128));
+		let dithered = quantize::remap(&image, &black_and_white(), Dither::FloydSteinberg);
+		assert!((120..=136).contains(&whites(&dithered)));
+		assert_ne!(dithered.pixel(0, 0), dithered.pixel(1, 0));
+		let dithered = quantize::remap(&image, &black_and_white(), Dither::Atkinson);
+		assert!((96..=160).contains(&whites(&dithered)));
+		let indices = quantize::indices(&gradient(), &black_and_white(), Dither::FloydSteinberg);
+		let count = |row: &[usize]| row.iter().filter(|&&index| index == 1).count();
+		assert!(count(&indices[..8]) < count(&indices[8..16]));
+	}
+
+	#[test]
+	fn ordered_dithering() {
+		assert_eq!(vec![0.125, 0.625, 0.875, 0.375], bayer(2));
+		assert_eq!(64, bayer(8).len());
+		assert_eq!(64, bayer(11).len());
+		assert_eq!(4, bayer(0).len());
+		let image = Image::filled(16, 16, gray(128));
+		let dithered = quantize::remap(&image, &black_and_white(), Dither::Bayer(4));
+		assert_eq!(128, whites(&dithered));
+		assert_eq!(dithered.pixel(0, 0), dithered.pixel(4, 4));
+		let (palette, reduced) = quantize::reduce(&gradient(), 4, Quantizer::MedianCut, Dither::Bayer(8));
+		assert_eq!(4, palette.len());
+		assert!(reduced.pixels().iter().all(|color| palette.colors().contains(color)));
+	}
+
+}
@@ -0,0 +1,366 @@
+use std::io::Write;
+use std::path::Path;
+use crate::color::{Color, premultiplied_color};
+use crate::image::codec;
+use crate::image::image_error::ImageError;
+use crate::image::{Filter, ImageFormat, Rect};
+
+/// Owned grid of RGBA pixels, like a sprite or a texture, to draw on the canvas with
+/// [`Canvas::draw_image`] and [`Canvas::draw_image_region`].
+///
+/// # Example
+/// The following example builds a small checkerboard and draws it on the canvas.
+/// ```no_run
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::image::Image;
+/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+/// let mut image = Image::new(8, 8);
+/// for (x, y) in (0..8).flat_map(|x| (0..8).map(move |y| (x, y))).filter(|(x, y)| (x + y) % 2 == 0) {
+///   image.set_pixel(x, y, palette::WHITE);
+/// }
+/// canvas.draw_image((100, 100), &image);
+/// ```
+///
+/// [`Canvas::draw_image`]: crate::canvas::Canvas::draw_image
+/// [`Canvas::draw_image_region`]: crate::canvas::Canvas::draw_image_region
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+	width: u32,
+	height: u32,
+	pixels: Vec<Color>,
+}
+
+/// Fully transparent color used to initialize the images
+const TRANSPARENT: Color = Color { r: 0, g: 0, b: 0, a: 0 };
+
+impl Image {
+
+	/// Maximum number of pixels of an image, a gigabyte of RGBA data
+	pub const MAX_PIXELS: usize = 1 << 28;
+
+	/// Creates a new image of the given size with all its pixels transparent
+	///
+	/// # Panics
+	/// If the image has more than [Image::MAX_PIXELS] pixels
+	///
+	pub fn new(width: u32, height: u32) -> Self {
+		Self::filled(width, height, TRANSPARENT)
+	}
+
+	/// Creates a new image of the given size with all its pixels of the same color
+	///
+	/// # Panics
+	/// If the image has more than [Image::MAX_PIXELS] pixels. Use [Image::try_filled] when the
+	/// size comes from user input.
+	///
+	pub fn filled(width: u32, height: u32, color: Color) -> Self {
+		Self::try_filled(width, height, color)
+			.unwrap_or_else(|| panic!("image of {}x{} pixels exceeds the maximum size", width, height))
+	}
+
+	/// Creates a new image of the given size with all its pixels of the same color
+	///
+	/// # Errors
+	/// Returns `None` if the image has more than [Image::MAX_PIXELS] pixels
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// assert!(Image::try_filled(640, 480, palette::BLACK).is_some());
+	/// assert!(Image::try_filled(u32::MAX, u32::MAX, palette::BLACK).is_none());
+	/// ```
+	///
+	pub fn try_filled(width: u32, height: u32, color: Color) -> Option<Self> {
+		let count = Self::pixel_count(width, height)?;
+		Some(Self { width, height, pixels: vec![color; count] })
+	}
+
+	/// Creates a new image with the given pixels, in row-major order
+	///
+	/// # Errors
+	/// Returns `None` if the number of pixels doesn't match the size of the image or the image
+	/// has more than [Image::MAX_PIXELS] pixels
+	///
+	pub fn from_pixels(width: u32, height: u32, pixels: Vec<Color>) -> Option<Self> {
+		if Self::pixel_count(width, height) != Some(pixels.len()) {
+			return None;
+		}
+		Some(Self { width, height, pixels })
+	}
+
+	/// Creates a new image from a buffer of RGBA bytes, four per pixel in row-major order
+	///
+	/// # Errors
+	/// Returns `None` if the length of the buffer doesn't match the size of the image or the
+	/// image has more than [Image::MAX_PIXELS] pixels
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// let image = Image::from_rgba_bytes(2, 1, &[255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+	/// assert_eq!(Some(&palette::BLUE), image.pixel(1, 0));
+	/// ```
+	///
+	pub fn from_rgba_bytes(width: u32, height: u32, bytes: &[u8]) -> Option<Self> {
+		if Self::pixel_count(width, height).map(|count| count * 4) != Some(bytes.len()) {
+			return None;
+		}
+		let pixels = bytes.chunks_exact(4)
+			.map(|pixel| Color { r: pixel[0], g: pixel[1], b: pixel[2], a: pixel[3] })
+			.collect();
+		Some(Self { width, height, pixels })
+	}
+
+	/// Loads the image stored in the specified file. The format is detected from the content.
+	///
+	/// # Errors
+	/// * [ImageError::Io] if the file can't be read
+	/// * [ImageError::UnknownFormat] if the format of the file is not supported
+	/// * [ImageError::Unsupported] if the image uses a feature of its format not supported
+	/// * [ImageError::Malformed] if the image is corrupted
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::canvas::Canvas;
+	/// # use ferrux_canvas::image::Image;
+	/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+	/// # let mut canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+	/// let sprite = Image::open("assets/player.png").unwrap();
+	/// canvas.draw_image((100, 100), &sprite);
+	/// ```
+	///
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+		Self::decode(&std::fs::read(path)?)
+	}
+
+	/// Decodes an image from the content of an image file, detecting its format. The supported
+	/// formats are listed in [ImageFormat].
+	///
+	/// # Errors
+	/// * [ImageError::UnknownFormat] if the format of the data is not supported
+	/// * [ImageError::Unsupported] if the image uses a feature of its format not supported
+	/// * [ImageError::Malformed] if the image is corrupted
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// let image = Image::decode(b"P3 1 1 255 255 0 0").unwrap();
+	/// assert_eq!(Some(&palette::RED), image.pixel(0, 0));
+	/// ```
+	///
+	pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+		let format = ImageFormat::detect(bytes).ok_or(ImageError::UnknownFormat)?;
+		codec::decode(bytes, format)
+	}
+
+	/// Decodes an image in the specified format
+	///
+	/// # Errors
+	/// * [ImageError::Unsupported] if the image uses a feature of its format not supported
+	/// * [ImageError::Malformed] if the image is corrupted or isn't in the specified format
+	///
+	pub fn decode_format(bytes: &[u8], format: ImageFormat) -> Result<Self, ImageError> {
+		codec::decode(bytes, format)
+	}
+
+	/// Saves the image in the specified file. The format is chosen from the extension of the file.
+	///
+	/// # Errors
+	/// * [ImageError::UnknownFormat] if the extension doesn't match any of the supported formats
+	/// * [ImageError::Unsupported] if the image can't be encoded in the format, like when its size
+	///   exceeds the limits of the format
+	/// * [ImageError::Io] if the file can't be written
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::Image;
+	/// Image::filled(64, 64, palette::RED).save("red.qoi").unwrap();
+	/// ```
+	///
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+		let format = path.as_ref().extension()
+			.and_then(|extension| extension.to_str())
+			.and_then(ImageFormat::from_extension)
+			.ok_or(ImageError::UnknownFormat)?;
+		let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+		self.encode(format, &mut file)?;
+		file.flush()?;
+		Ok(())
+	}
+
+	/// Encodes the image in the specified format and writes it
+	///
+	/// # Errors
+	/// * [ImageError::Unsupported] if the image can't be encoded in the format, like when its size
+	///   exceeds the limits of the format
+	/// * [ImageError::Io] if the writing fails
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::palette;
+	/// # use ferrux_canvas::image::{Image, ImageFormat};
+	/// let mut ppm = Vec::new();
+	/// Image::filled(1, 1, palette::RED).encode(ImageFormat::Pnm, &mut ppm).unwrap();
+	/// assert_eq!(b"P6\n1 1\n255\n\xff\x00\x00".to_vec(), ppm);
+	/// ```
+	///
+	pub fn encode<W: Write>(&self, format: ImageFormat, writer: W) -> Result<(), ImageError> {
+		codec::encode(self, format, writer)
+	}
+
+	/// Width of the image
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	/// Height of the image
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Returns the area covered by the whole image
+	pub fn bounds(&self) -> Rect {
+		Rect::new(0, 0, self.width, self.height)
+	}
+
+	/// Returns the color of the pixel in the given coordinates, `None` if they are out of the image
+	pub fn pixel(&self, x: u32, y: u32) -> Option<&Color> {
+		if x < self.width && y < self.height {
+			self.pixels.get((y * self.width + x) as usize)
+		} else {
+			None
+		}
+	}
+
+	/// Changes the color of the pixel in the given coordinates. Coordinates out of the image are
+	/// ignored.
+	pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+		if x < self.width && y < self.height {
+			self.pixels[(y * self.width + x) as usize] = color;
+		}
+	}
+
+	/// Returns the color of the image in the given position, measured in pixels from the top-left
+	/// corner of the image, so the center of the first pixel is `(0.5, 0.5)`. Positions out of the
+	/// image return `None`.
+	///
+	/// # Arguments
+	/// * `u`, `v` - Position to sample
+	/// * `filter` - Sampling method to use
+	///
+	/// # Example
+	/// ```rust
+	/// # use ferrux_canvas::color::{Color, palette};
+	/// # use ferrux_canvas::image::{Filter, Image};
+	/// let image = Image::from_pixels(2, 1, vec![palette::BLACK, palette::WHITE]).unwrap();
+	/// assert_eq!(Some(palette::BLACK), image.sample(0.9, 0.5, Filter::Nearest));
+	/// assert_eq!(Some(Color { r: 102, g: 102, b: 102, a: 255 }), image.sample(0.9, 0.5, Filter::Bilinear));
+	/// ```
+	///
+	pub fn sample(&self, u: f32, v: f32, filter: Filter) -> Option<Color> {
+		if !(0.0..self.width as f32).contains(&u) || !(0.0..self.height as f32).contains(&v) {
+			return None;
+		}
+		match filter {
+			Filter::Nearest => self.pixel(u as u32, v as u32).copied(),
+			Filter::Bilinear => {
+				let (x, y) = ((u - 0.5).max(0.0), (v - 0.5).max(0.0));
+				let (x0, y0) = (x as u32, y as u32);
+				let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+				let (fx, fy) = (x.fract(), y.fract());
+				let pixel = |x, y| &self.pixels[(y * self.width + x) as usize];
+				// Mixed with premultiplied alpha, so the transparent pixels don't darken the edges
+				Some(premultiplied_color::mix([
+					(pixel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+					(pixel(x1, y0), fx * (1.0 - fy)),
+					(pixel(x0, y1), (1.0 - fx) * fy),
+					(pixel(x1, y1), fx * fy),
+				]))
+			}
+		}
+	}
+
+	/// Returns all the pixels of the image in row-major order
+	pub fn pixels(&self) -> &[Color] {
+		&self.pixels
+	}
+
+	/// Returns a new image with a copy of the specified region of this one. The region is clipped
+	/// to the bounds of the image.
+	pub fn sub_image(&self, region: Rect) -> Image {
+		let region = region.intersection(&self.bounds());
+		let pixels = (region.y..region.y + region.height)
+			.flat_map(|y| (region.x..region.x + region.width).map(move |x| (x, y)))
+			.map(|(x, y)| self.pixels[(y * self.width + x) as usize])
+			.collect();
+		Image {
+			width: region.width,
+			height: region.height,
+			pixels,
+		}
+	}
+
+	/// Returns the number of pixels of an image of the given size, or `None` if it exceeds
+	/// [Image::MAX_PIXELS]
+	pub(crate) fn pixel_count(width: u32, height: u32) -> Option<usize> {
+		(width as usize).checked_mul(height as usize).filter(|&count| count <= Self::MAX_PIXELS)
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::color::{Color, palette};
+	use crate::image::{Filter, Image, Rect};
+
+	#[test]
+	fn pixel_access() {
+		let mut image = Image::new(2, 2);
+		assert_eq!(0, image.pixel(1, 1).unwrap().a);
+		image.set_pixel(1, 1, palette::RED);
+		image.set_pixel(2, 1, palette::RED);
+		assert_eq!(Some(&palette::RED), image.pixel(1, 1));
+		assert_eq!(None, image.pixel(2, 1));
+		assert_eq!(1, image.pixels().iter().filter(|&color| *color == palette::RED).count());
+	}
+
+	#[test]
+	fn invalid_sizes() {
+		assert!(Image::from_pixels(2, 2, vec![palette::RED; 3]).is_none());
+		assert!(Image::from_rgba_bytes(1, 1, &[0, 0, 0]).is_none());
+	}
+
+	#[test]
+	fn size_limit() {
+		assert_eq!(Some(Image::MAX_PIXELS), Image::pixel_count(1 << 14, 1 << 14));
+		assert!(Image::try_filled(1 << 14, (1 << 14) + 1, palette::RED).is_none());
+		assert!(Image::try_filled(u32::MAX, u32::MAX, palette::RED).is_none());
+		assert!(Image::from_pixels(1 << 16, 1 << 16, Vec::new()).is_none());
+		assert!(Image::from_rgba_bytes(u32::MAX, 2, &[]).is_none());
+	}
+
+	#[test]
+	fn bilinear_sampling_without_fringes() {
+		let image = Image::from_pixels(2, 1, vec![Color { r: 0, g: 0, b: 0, a: 0 }, palette::WHITE]).unwrap();
+		assert_eq!(Some(Color { r: 255, g: 255, b: 255, a: 128 }), image.sample(1.0, 0.5, Filter::Bilinear));
+		assert_eq!(Some(Color { r: 0, g: 0, b: 0, a: 0 }), image.sample(0.5, 0.5, Filter::Bilinear));
+		assert_eq!(Some(palette::WHITE), image.sample(1.9, 0.5, Filter::Bilinear));
+	}
+
+	#[test]
+	fn sub_image() {
+		let mut image = Image::filled(4, 3, palette::WHITE);
+		image.set_pixel(3, 2, palette::BLUE);
+		let sub = image.sub_image(Rect::new(2, 1, 5, 5));
+		assert_eq!((2, 2), (sub.width(), sub.height()));
+		assert_eq!(Some(&palette::BLUE), sub.pixel(1, 1));
+		assert_eq!(Some(&palette::WHITE), sub.pixel(0, 0));
+	}
+
+}
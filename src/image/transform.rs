@@ -0,0 +1,191 @@
+use crate::canvas::{Canvas, Point};
+use crate::color::Color;
+use crate::image::Image;
+
+/// Sampling method used to read an image in positions between its pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Filter {
+	/// Takes the color of the closest pixel, keeping the hard edges of pixel art
+	#[default]
+	Nearest,
+	/// Interpolates the colors of the four closest pixels, smoothing the image
+	Bilinear,
+}
+
+/// Scaling and rotation to apply to an image when drawing it with
+/// [`Canvas::draw_image_transformed`]. Both are applied around the pivot, a point of the image
+/// that is placed in the destination point of the canvas.
+///
+/// # Example
+/// The following transformation doubles the size of a 16x16 sprite and rotates it a quarter turn
+/// clockwise around its center.
+/// ```rust
+/// # use ferrux_canvas::image::Transform;
+/// let transform = Transform::new()
+///   .with_scale(2.0, 2.0)
+///   .with_rotation(std::f32::consts::FRAC_PI_2)
+///   .with_pivot(8.0, 8.0);
+/// ```
+///
+/// [`Canvas::draw_image_transformed`]: crate::canvas::Canvas::draw_image_transformed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+	scale: (f32, f32),
+	rotation: f32,
+	pivot: (f32, f32),
+}
+
+impl Transform {
+
+	/// Creates a new transformation without scaling nor rotation and the pivot in the top-left
+	/// corner of the image
+	pub fn new() -> Transform {
+		Transform::default()
+	}
+
+	/// Sets the horizontal and vertical scale factors. Negative factors mirror the image.
+	pub fn with_scale(mut self, x: f32, y: f32) -> Transform {
+		self.scale = (x, y);
+		self
+	}
+
+	/// Sets the rotation angle in radians, clockwise as the vertical axis of the canvas points down
+	pub fn with_rotation(mut self, radians: f32) -> Transform {
+		self.rotation = radians;
+		self
+	}
+
+	/// Sets the pivot, in pixels of the image measured from its top-left corner
+	pub fn with_pivot(mut self, x: f32, y: f32) -> Transform {
+		self.pivot = (x, y);
+		self
+	}
+
+	/// Maps a point of the image to its position in the canvas, relative to the destination point
+	fn apply(&self, (u, v): (f32, f32)) -> (f32, f32) {
+		let x = (u - self.pivot.0) * self.scale.0;
+		let y = (v - self.pivot.1) * self.scale.1;
+		let (sin, cos) = self.rotation.sin_cos();
+		(x * cos - y * sin, x * sin + y * cos)
+	}
+
+	/// Maps a point of the canvas, relative to the destination point, to its position in the image
+	fn invert(&self, (x, y): (f32, f32)) -> (f32, f32) {
+		let (sin, cos) = self.rotation.sin_cos();
+		let (x, y) = (x * cos + y * sin, y * cos - x * sin);
+		(x / self.scale.0 + self.pivot.0, y / self.scale.1 + self.pivot.1)
+	}
+
+}
+
+impl Default for Transform {
+	fn default() -> Self {
+		Transform {
+			scale: (1.0, 1.0),
+			rotation: 0.0,
+			pivot: (0.0, 0.0),
+		}
+	}
+}
+
+/// Draws the transformed image on the canvas, blending its translucent pixels
+pub(crate) fn draw<C: Canvas + ?Sized>(canvas: &mut C, dest: Point, image: &Image,
+                                       transform: &Transform, filter: Filter) {
+	let size = (canvas.width(), canvas.height());
+	for (x, y, color) in placements(dest, image, transform, filter, size) {
+		match color.a {
+			0 => {}
+			u8::MAX => canvas.draw_pixel(x, y, color),
+			_ => canvas.blend_pixel(x, y, color),
+		}
+	}
+}
+
+/// Returns the colors of the transformed image to draw along with their position in the canvas.
+/// Each pixel of the canvas covered by the image is sampled in the image through its center.
+fn placements(dest: Point, image: &Image, transform: &Transform, filter: Filter,
+              canvas_size: (u32, u32)) -> Vec<(u32, u32, Color)> {
+	if transform.scale.0 == 0.0 || transform.scale.1 == 0.0 {
+		return Vec::new();
+	}
+	let (width, height) = (image.width() as f32, image.height() as f32);
+	let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+		.map(|corner| transform.apply(corner));
+	let bound = |values: [f32; 4], offset: u32, limit: u32| {
+		let min = values.iter().fold(f32::INFINITY, |a, &b| a.min(b)) + offset as f32;
+		let max = values.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b)) + offset as f32;
+		(min.floor().max(0.0) as u32, (max.ceil().max(0.0) as u32).min(limit))
+	};
+	let (min_x, max_x) = bound(corners.map(|(x, _)| x), dest.0, canvas_size.0);
+	let (min_y, max_y) = bound(corners.map(|(_, y)| y), dest.1, canvas_size.1);
+
+	let mut placements = Vec::new();
+	for y in min_y..max_y {
+		for x in min_x..max_x {
+			let center = (x as f32 + 0.5 - dest.0 as f32, y as f32 + 0.5 - dest.1 as f32);
+			let (u, v) = transform.invert(center);
+			if let Some(color) = image.sample(u, v, filter) {
+				placements.push((x, y, color));
+			}
+		}
+	}
+	placements
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::FRAC_PI_2;
+	use crate::color::{Color, palette};
+	use crate::image::{Filter, Image, Transform};
+	use crate::image::transform::placements;
+
+	fn image() -> Image {
+		Image::from_pixels(2, 1, vec![palette::RED, palette::BLUE]).unwrap()
+	}
+
+	fn drawn(dest: (u32, u32), transform: Transform) -> Vec<(u32, u32, Color)> {
+		placements(dest, &image(), &transform, Filter::Nearest, (10, 10))
+	}
+
+	#[test]
+	fn identity() {
+		assert_eq!(vec![(3, 3, palette::RED), (4, 3, palette::BLUE)], drawn((3, 3), Transform::new()));
+	}
+
+	#[test]
+	fn scaling() {
+		let expected = vec![(0, 0, palette::RED), (1, 0, palette::RED), (2, 0, palette::BLUE),
+		                    (3, 0, palette::BLUE), (0, 1, palette::RED), (1, 1, palette::RED),
+		                    (2, 1, palette::BLUE), (3, 1, palette::BLUE)];
+		assert_eq!(expected, drawn((0, 0), Transform::new().with_scale(2.0, 2.0)));
+		let mirrored = Transform::new().with_scale(-1.0, 1.0).with_pivot(2.0, 0.0);
+		assert_eq!(vec![(0, 0, palette::BLUE), (1, 0, palette::RED)], drawn((0, 0), mirrored));
+		assert!(drawn((0, 0), Transform::new().with_scale(0.0, 1.0)).is_empty());
+	}
+
+	#[test]
+	fn rotation() {
+		let quarter = Transform::new().with_rotation(FRAC_PI_2).with_pivot(1.0, 0.5);
+		assert_eq!(vec![(5, 4, palette::RED), (5, 5, palette::BLUE)], drawn((5, 5), quarter));
+		let half = Transform::new().with_rotation(2.0 * FRAC_PI_2);
+		assert_eq!(vec![(3, 4, palette::BLUE), (4, 4, palette::RED)], drawn((5, 5), half));
+	}
+
+	#[test]
+	fn clipping() {
+		assert_eq!(vec![(9, 9, palette::RED)], drawn((9, 9), Transform::new()));
+		let half = Transform::new().with_rotation(2.0 * FRAC_PI_2);
+		assert_eq!(vec![(0, 0, palette::RED)], drawn((1, 1), half));
+	}
+
+	#[test]
+	fn bilinear_scaling() {
+		let transform = Transform::new().with_scale(4.0, 1.0);
+		let colors = placements((0, 0), &image(), &transform, Filter::Bilinear, (10, 10))
+			.into_iter()
+			.map(|(_, _, color)| (color.r, color.b))
+			.collect::<Vec<_>>();
+		assert_eq!(vec![(255, 0), (223, 32), (159, 96), (96, 159), (32, 223), (0, 255)], colors[1..7]);
+	}
+
+}
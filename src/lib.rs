@@ -3,8 +3,9 @@
 //! color in the screen. In its current state it only works with [Winit](https://crates.io/crates/winit).
 //!
 //! # Building a canvas
-//! Right now, the only [`Canvas`] provided is [`WinitCanvas`], which requires a [`Window`], which
-//! will need itself an [`EventLoop`] reference.
+//! The main [`Canvas`] provided is [`WinitCanvas`], which requires a [`Window`], which will need
+//! itself an [`EventLoop`] reference. To draw without a window, like in servers or tests, there's
+//! the [`HeadlessCanvas`].
 //!
 //! ```no_run
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -61,11 +62,17 @@
 //! [`render`]: canvas::Canvas::render
 //! [`reset_frame`]: canvas::Canvas::reset_frame
 //! [`WinitCanvas`]: canvas::winit::WinitCanvas
+//! [`HeadlessCanvas`]: canvas::headless::HeadlessCanvas
 //! [`Window`]: winit::window::Window
 //! [`EventLoop`]: winit::event_loop::EventLoop
 //!
 
 pub mod canvas;
 pub mod color;
+pub mod font;
+pub mod image;
+pub mod record;
+#[cfg(feature = "script")]
+pub mod script;
 
 extern crate winit;
\ No newline at end of file
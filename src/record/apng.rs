@@ -0,0 +1,156 @@
+//! Recording of animated PNGs
+use std::io::Write;
+use std::time::Duration;
+use ::png::{BitDepth, ColorType, Encoder};
+use crate::image::codec::png::encoding_error;
+use crate::image::image_error::ImageError;
+use crate::image::Image;
+use crate::record::{FrameSink, finished, size_changed};
+
+/// Sink writing the frames as an animated PNG, keeping all their colors and alpha. APNG needs to
+/// know the number of frames before writing them, so they are kept in memory until the recording
+/// is finished.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::record::{ApngSink, Recorder};
+/// let sink = ApngSink::new(Vec::new()).with_delay(Duration::from_millis(40));
+/// let mut recorder = Recorder::new(HeadlessCanvas::new(32, 32), sink);
+/// for x in 0..32 {
+///   recorder.draw_line((x, 0), (x, 31), palette::RED);
+///   recorder.render().unwrap();
+/// }
+/// let (_, sink) = recorder.finish().unwrap();
+/// let png = sink.into_inner().unwrap();
+/// assert!(png.starts_with(b"\x89PNG"));
+/// ```
+///
+pub struct ApngSink<W: Write> {
+	writer: W,
+	frames: Vec<Image>,
+	delay: u16,
+	plays: u32,
+	finished: bool,
+}
+
+impl<W: Write> ApngSink<W> {
+
+	/// Returns a new sink writing to the given writer, with frames of 1/10 second that loop forever
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			frames: Vec::new(),
+			delay: 100,
+			plays: 0,
+			finished: false,
+		}
+	}
+
+	/// Sets the time each frame is shown, up to 65.535 seconds. It's stored in milliseconds.
+	pub fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay = delay.as_millis().min(u16::MAX as u128) as u16;
+		self
+	}
+
+	/// Sets the number of times the animation is played, zero to loop forever
+	pub fn with_plays(mut self, plays: u32) -> Self {
+		self.plays = plays;
+		self
+	}
+
+	/// Returns the writer, once the recording is finished
+	pub fn into_inner(self) -> Option<W> {
+		self.finished.then_some(self.writer)
+	}
+
+}
+
+impl<W: Write> FrameSink for ApngSink<W> {
+
+	fn write_frame(&mut self, frame: &Image) -> Result<(), ImageError> {
+		if self.finished {
+			return Err(finished());
+		}
+		let size = (frame.width(), frame.height());
+		let first = self.frames.first().map(|first| (first.width(), first.height()));
+		if let Some(first) = first.filter(|&first| first != size) {
+			return Err(size_changed(first, size));
+		}
+		self.frames.push(frame.clone());
+		Ok(())
+	}
+
+	/// Writes all the frames. Nothing is written if there are no frames.
+	fn finish(&mut self) -> Result<(), ImageError> {
+		if self.finished {
+			return Ok(());
+		}
+		self.finished = true;
+		let Some(first) = self.frames.first() else {
+			return Ok(());
+		};
+
+		let mut encoder = Encoder::new(&mut self.writer, first.width(), first.height());
+		encoder.set_color(ColorType::Rgba);
+		encoder.set_depth(BitDepth::Eight);
+		encoder.set_animated(self.frames.len() as u32, self.plays).map_err(encoding_error)?;
+		encoder.set_frame_delay(self.delay, 1000).map_err(encoding_error)?;
+		let mut writer = encoder.write_header().map_err(encoding_error)?;
+		for frame in self.frames.drain(..) {
+			let data = frame.pixels().iter().flat_map(|color| color.as_u8()).collect::<Vec<_>>();
+			writer.write_image_data(&data).map_err(encoding_error)?;
+		}
+		writer.finish().map_err(encoding_error)?;
+		self.writer.flush()?;
+		Ok(())
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use ::png::Decoder;
+	use crate::color::{Color, palette};
+	use crate::image::image_error::ImageError;
+	use crate::image::Image;
+	use crate::record::{ApngSink, FrameSink};
+
+	#[test]
+	fn write_animation() {
+		let mut sink = ApngSink::new(Vec::new()).with_delay(Duration::from_millis(250)).with_plays(2);
+		let translucent = Color { r: 0, g: 0, b: 255, a: 128 };
+		sink.write_frame(&Image::filled(3, 2, palette::RED)).unwrap();
+		sink.write_frame(&Image::filled(3, 2, translucent)).unwrap();
+		sink.finish().unwrap();
+		let bytes = sink.into_inner().unwrap();
+
+		let mut reader = Decoder::new(bytes.as_slice()).read_info().unwrap();
+		let control = reader.info().animation_control.unwrap();
+		assert_eq!((2, 2), (control.num_frames, control.num_plays));
+		let mut buffer = vec![0; reader.output_buffer_size()];
+		let mut frames = Vec::new();
+		while let Ok(info) = reader.next_frame(&mut buffer) {
+			let delay = reader.info().frame_control.map(|control| (control.delay_num, control.delay_den));
+			frames.push((delay, buffer[..info.line_size].to_vec()));
+		}
+		assert_eq!(2, frames.len());
+		assert_eq!((Some((250, 1000)), [255, 0, 0, 255].repeat(3)), frames[0]);
+		assert_eq!((Some((250, 1000)), translucent.as_u8().repeat(3)), frames[1]);
+	}
+
+	#[test]
+	fn reject_invalid_frames() {
+		let mut sink = ApngSink::new(Vec::new());
+		sink.write_frame(&Image::filled(3, 2, palette::RED)).unwrap();
+		let resized = sink.write_frame(&Image::filled(2, 3, palette::RED));
+		assert!(matches!(resized, Err(ImageError::FrameSize((3, 2), (2, 3)))));
+		sink.finish().unwrap();
+		assert!(matches!(sink.write_frame(&Image::filled(3, 2, palette::RED)), Err(ImageError::Io(_))));
+	}
+
+}
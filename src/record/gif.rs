@@ -0,0 +1,167 @@
+//! Recording of animated GIFs
+use std::io::Write;
+use std::time::Duration;
+use ::gif::{Encoder, Repeat};
+use crate::image::codec::gif::{DEFAULT_SPEED, encoding_error, frame};
+use crate::image::image_error::ImageError;
+use crate::image::Image;
+use crate::record::{FrameSink, finished, size_changed};
+
+/// Sink writing the frames as an animated GIF. The frames are written as they arrive, quantizing
+/// each one to its own palette of 256 colors. GIF only supports fully opaque or fully transparent
+/// pixels, so the translucent pixels are written opaque.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::record::{GifSink, Recorder};
+/// let sink = GifSink::new(Vec::new())
+///   .with_delay(Duration::from_millis(100))
+///   .with_plays(1);
+/// let mut recorder = Recorder::new(HeadlessCanvas::new(32, 32), sink);
+/// for x in 0..32 {
+///   recorder.draw_line((x, 0), (x, 31), palette::RED);
+///   recorder.render().unwrap();
+/// }
+/// let (_, sink) = recorder.finish().unwrap();
+/// let gif = sink.into_inner().unwrap();
+/// assert!(gif.starts_with(b"GIF89a"));
+/// ```
+///
+pub struct GifSink<W: Write> {
+	writer: Option<W>,
+	encoder: Option<Encoder<W>>,
+	size: Option<(u32, u32)>,
+	delay: u16,
+	plays: u16,
+	speed: i32,
+}
+
+impl<W: Write> GifSink<W> {
+
+	/// Returns a new sink writing to the given writer, with frames of 1/10 second that loop forever
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer: Some(writer),
+			encoder: None,
+			size: None,
+			delay: 10,
+			plays: 0,
+			speed: DEFAULT_SPEED,
+		}
+	}
+
+	/// Sets the time each frame is shown. GIF stores it in hundredths of a second, so it's rounded
+	/// down to them.
+	pub fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+		self
+	}
+
+	/// Sets the number of times the animation is played, zero to loop forever
+	pub fn with_plays(mut self, plays: u16) -> Self {
+		self.plays = plays;
+		self
+	}
+
+	/// Sets the speed of the color quantization of the frames with more than 256 colors, from 1
+	/// for the best quality to 30 for the fastest encoding. The default is 10.
+	pub fn with_quantization_speed(mut self, speed: i32) -> Self {
+		self.speed = speed.clamp(1, 30);
+		self
+	}
+
+	/// Returns the writer, once the recording is finished or if no frame was written
+	pub fn into_inner(self) -> Option<W> {
+		self.writer
+	}
+
+	/// Starts the encoder with the size of the first frame
+	fn start(&mut self, width: u16, height: u16) -> Result<(), ImageError> {
+		let writer = self.writer.take().ok_or_else(finished)?;
+		let mut encoder = Encoder::new(writer, width, height, &[]).map_err(encoding_error)?;
+		match self.plays {
+			0 => encoder.set_repeat(Repeat::Infinite).map_err(encoding_error)?,
+			1 => {}
+			plays => encoder.set_repeat(Repeat::Finite(plays - 1)).map_err(encoding_error)?,
+		}
+		self.encoder = Some(encoder);
+		Ok(())
+	}
+
+}
+
+impl<W: Write> FrameSink for GifSink<W> {
+
+	fn write_frame(&mut self, image: &Image) -> Result<(), ImageError> {
+		if self.encoder.is_none() && self.size.is_some() {
+			return Err(finished());
+		}
+		let size = (image.width(), image.height());
+		if let Some(first) = self.size.filter(|&first| first != size) {
+			return Err(size_changed(first, size));
+		}
+		let mut frame = frame(image, self.speed)?;
+		frame.delay = self.delay;
+		if self.encoder.is_none() {
+			self.start(frame.width, frame.height)?;
+			self.size = Some(size);
+		}
+		match self.encoder.as_mut() {
+			Some(encoder) => encoder.write_frame(&frame).map_err(encoding_error),
+			None => Err(finished()),
+		}
+	}
+
+	fn finish(&mut self) -> Result<(), ImageError> {
+		if let Some(encoder) = self.encoder.take() {
+			let mut writer = encoder.into_inner()?;
+			writer.flush()?;
+			self.writer = Some(writer);
+		}
+		Ok(())
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use ::gif::{ColorOutput, DecodeOptions};
+	use crate::color::palette;
+	use crate::image::image_error::ImageError;
+	use crate::image::Image;
+	use crate::record::{FrameSink, GifSink};
+
+	#[test]
+	fn write_animation() {
+		let mut sink = GifSink::new(Vec::new()).with_delay(Duration::from_millis(250));
+		sink.write_frame(&Image::filled(3, 2, palette::RED)).unwrap();
+		sink.write_frame(&Image::filled(3, 2, palette::BLUE)).unwrap();
+		sink.finish().unwrap();
+		let bytes = sink.into_inner().unwrap();
+
+		let mut options = DecodeOptions::new();
+		options.set_color_output(ColorOutput::RGBA);
+		let mut decoder = options.read_info(bytes.as_slice()).unwrap();
+		let mut frames = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			frames.push((frame.delay, frame.buffer[..4].to_vec()));
+		}
+		assert_eq!(vec![(25, vec![255, 0, 0, 255]), (25, vec![0, 0, 255, 255])], frames);
+	}
+
+	#[test]
+	fn reject_invalid_frames() {
+		let mut sink = GifSink::new(Vec::new());
+		sink.write_frame(&Image::filled(3, 2, palette::RED)).unwrap();
+		let resized = sink.write_frame(&Image::filled(2, 3, palette::RED));
+		assert!(matches!(resized, Err(ImageError::FrameSize((3, 2), (2, 3)))));
+		sink.finish().unwrap();
+		assert!(matches!(sink.write_frame(&Image::filled(3, 2, palette::RED)), Err(ImageError::Io(_))));
+	}
+
+}
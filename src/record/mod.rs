@@ -0,0 +1,254 @@
+//! Recording of the rendered frames into animations and video streams
+
+pub use apng::ApngSink;
+pub use gif::GifSink;
+pub use y4m::{ChromaSubsampling, Y4mSink};
+
+use log::error;
+use crate::canvas::canvas_error::CanvasError;
+use crate::canvas::{Canvas, Point, RasterCanvas};
+use crate::color::Color;
+use crate::image::Image;
+use crate::image::image_error::ImageError;
+
+mod apng;
+mod gif;
+mod y4m;
+
+/// Destination of the frames captured by a [Recorder], like an animation file or a video stream
+pub trait FrameSink {
+
+	/// Stores a new frame after the previous ones
+	///
+	/// # Errors
+	/// [ImageError] if the frame can't be encoded or written
+	///
+	fn write_frame(&mut self, frame: &Image) -> Result<(), ImageError>;
+
+	/// Completes the recording, writing anything still pending. No more frames should be written
+	/// after calling it.
+	///
+	/// # Errors
+	/// [ImageError] if the pending data can't be encoded or written
+	///
+	fn finish(&mut self) -> Result<(), ImageError>;
+
+}
+
+/// Canvas wrapping another one to capture its frames each time it's rendered, or every Nth render,
+/// and store them in a [FrameSink].
+///
+/// # Example
+/// The following example records the morphing triangle of the crate example in an endless GIF,
+/// capturing one of each two frames.
+/// ```no_run
+/// # use std::fs::File;
+/// # use std::time::Duration;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::record::{GifSink, Recorder};
+/// # let window = winit::window::Window::new(&winit::event_loop::EventLoop::new()).unwrap();
+/// # let canvas = ferrux_canvas::canvas::winit::WinitCanvas::new(&window).unwrap();
+/// let sink = GifSink::new(File::create("triangle.gif").unwrap())
+///   .with_delay(Duration::from_millis(40));
+/// let mut recorder = Recorder::new(canvas, sink).with_frame_step(2);
+/// for x in 1..100 {
+///   recorder.draw_triangle((100, 100 - x), (100 - x, 100), (200 - x, 200 - x), palette::WHITE);
+///   recorder.render().unwrap();
+///   recorder.reset_frame();
+/// }
+/// let (canvas, sink) = recorder.finish().unwrap();
+/// ```
+///
+pub struct Recorder<C: RasterCanvas, S: FrameSink> {
+	canvas: C,
+	sink: S,
+	step: u32,
+	renders: u64,
+}
+
+impl<C: RasterCanvas, S: FrameSink> Recorder<C, S> {
+
+	/// Returns a new recorder capturing every rendered frame of the canvas into the sink
+	pub fn new(canvas: C, sink: S) -> Self {
+		Self {
+			canvas,
+			sink,
+			step: 1,
+			renders: 0,
+		}
+	}
+
+	/// Captures only one of each `step` rendered frames, starting by the first one. A step of zero
+	/// is handled as one.
+	pub fn with_frame_step(mut self, step: u32) -> Self {
+		self.step = step.max(1);
+		self
+	}
+
+	/// Returns the recorded canvas
+	pub fn canvas(&self) -> &C {
+		&self.canvas
+	}
+
+	/// Returns the sink of the frames
+	pub fn sink(&self) -> &S {
+		&self.sink
+	}
+
+	/// Finishes the recording, returning the canvas and the sink
+	///
+	/// # Errors
+	/// [ImageError] if the sink fails completing the recording
+	///
+	pub fn finish(mut self) -> Result<(C, S), ImageError> {
+		self.sink.finish()?;
+		Ok((self.canvas, self.sink))
+	}
+
+}
+
+impl<C: RasterCanvas, S: FrameSink> Canvas for Recorder<C, S> {
+
+	fn width(&self) -> u32 {
+		self.canvas.width()
+	}
+
+	fn height(&self) -> u32 {
+		self.canvas.height()
+	}
+
+	/// Renders the frame of the wrapped canvas and captures it if it's its turn
+	///
+	/// # Errors
+	/// * Any error rendering the wrapped canvas
+	/// * [CanvasError::Recording] if the sink fails storing the frame
+	///
+	fn render(&mut self) -> Result<(), CanvasError> {
+		self.canvas.render()?;
+		if self.renders.is_multiple_of(self.step as u64) {
+			self.sink.write_frame(self.canvas.frame()).map_err(|e| {
+				error!("sink.write_frame() failed: {:?}", e);
+				CanvasError::Recording
+			})?;
+		}
+		self.renders += 1;
+		Ok(())
+	}
+
+	fn draw_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.canvas.draw_pixel(x, y, color);
+	}
+
+	fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+		self.canvas.blend_pixel(x, y, color);
+	}
+
+	fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+		self.canvas.draw_line(start, end, color);
+	}
+
+	fn draw_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.canvas.draw_triangle(point_a, point_b, point_c, color);
+	}
+
+	fn fill_triangle(&mut self, point_a: Point, point_b: Point, point_c: Point, color: Color) {
+		self.canvas.fill_triangle(point_a, point_b, point_c, color);
+	}
+
+	fn clear_frame(&mut self) -> Result<(), CanvasError> {
+		self.canvas.clear_frame()
+	}
+
+	fn reset_frame(&mut self) {
+		self.canvas.reset_frame();
+	}
+
+	fn resize(&mut self, width: u32, height: u32) {
+		self.canvas.resize(width, height);
+	}
+
+}
+
+impl<C: RasterCanvas, S: FrameSink> RasterCanvas for Recorder<C, S> {
+
+	fn frame(&self) -> &Image {
+		self.canvas.frame()
+	}
+
+}
+
+/// Returns the error of a sink receiving frames of different sizes, which animations and video
+/// streams don't allow
+fn size_changed(first: (u32, u32), size: (u32, u32)) -> ImageError {
+	ImageError::FrameSize(first, size)
+}
+
+/// Returns the error of a sink receiving frames after finishing the recording
+fn finished() -> ImageError {
+	ImageError::Io(std::io::Error::other("the recording is finished"))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::{Canvas, RasterCanvas};
+	use crate::canvas::canvas_error::CanvasError;
+	use crate::canvas::headless::HeadlessCanvas;
+	use crate::color::palette;
+	use crate::image::Image;
+	use crate::image::image_error::ImageError;
+	use crate::record::{FrameSink, Recorder};
+
+	/// Sink keeping the frames in memory
+	#[derive(Default)]
+	struct Frames {
+		frames: Vec<Image>,
+		finished: bool,
+	}
+
+	impl FrameSink for Frames {
+		fn write_frame(&mut self, frame: &Image) -> Result<(), ImageError> {
+			if frame.width() > 4 {
+				return Err(ImageError::UnknownFormat);
+			}
+			self.frames.push(frame.clone());
+			Ok(())
+		}
+
+		fn finish(&mut self) -> Result<(), ImageError> {
+			self.finished = true;
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn record_every_frame() {
+		let mut recorder = Recorder::new(HeadlessCanvas::new(2, 2), Frames::default());
+		for x in 0..3 {
+			recorder.draw_pixel(x, 0, palette::WHITE);
+			recorder.render().unwrap();
+		}
+		let (canvas, sink) = recorder.finish().unwrap();
+		assert!(sink.finished);
+		assert_eq!(3, sink.frames.len());
+		assert_eq!(Some(&palette::BLACK), sink.frames[0].pixel(1, 0));
+		assert_eq!(Some(&palette::WHITE), sink.frames[1].pixel(1, 0));
+		assert_eq!(canvas.frame(), &sink.frames[2]);
+	}
+
+	#[test]
+	fn record_every_nth_frame() {
+		let mut recorder = Recorder::new(HeadlessCanvas::new(2, 2), Frames::default()).with_frame_step(3);
+		for _ in 0..7 {
+			recorder.render().unwrap();
+		}
+		assert_eq!(3, recorder.sink().frames.len());
+	}
+
+	#[test]
+	fn sink_errors() {
+		let mut recorder = Recorder::new(HeadlessCanvas::new(8, 2), Frames::default());
+		assert!(matches!(recorder.render(), Err(CanvasError::Recording)));
+	}
+
+}
@@ -0,0 +1,222 @@
+//! Recording of raw YUV4MPEG2 video streams
+use std::io::Write;
+use crate::color::Color;
+use crate::image::Image;
+use crate::image::image_error::ImageError;
+use crate::record::{FrameSink, finished, size_changed};
+
+/// Resolution of the color planes of the stream compared to the luma one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChromaSubsampling {
+	/// The color planes have half the width and height of the image, each of their samples is the
+	/// average of a block of 2x2 pixels. It's the format expected by most encoders.
+	#[default]
+	Yuv420,
+	/// The color planes have the full resolution of the image
+	Yuv444,
+}
+
+/// Sink writing the frames as a YUV4MPEG2 stream, the raw video format read by encoders like
+/// ffmpeg or x264. The colors are converted to YUV with the BT.601 coefficients in limited range,
+/// and the alpha channel is ignored.
+///
+/// # Example
+/// The following example renders a video without any window, to be encoded with
+/// `ffmpeg -i video.y4m video.mp4`.
+/// ```no_run
+/// # use std::fs::File;
+/// # use ferrux_canvas::canvas::Canvas;
+/// # use ferrux_canvas::canvas::headless::HeadlessCanvas;
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::record::{Recorder, Y4mSink};
+/// let sink = Y4mSink::new(File::create("video.y4m").unwrap()).with_frame_rate(60, 1);
+/// let mut recorder = Recorder::new(HeadlessCanvas::new(640, 480), sink);
+/// for x in 0..640 {
+///   recorder.draw_line((x, 0), (x, 479), palette::RED);
+///   recorder.render().unwrap();
+/// }
+/// recorder.finish().unwrap();
+/// ```
+///
+pub struct Y4mSink<W: Write> {
+	writer: W,
+	size: Option<(u32, u32)>,
+	frame_rate: (u32, u32),
+	subsampling: ChromaSubsampling,
+	finished: bool,
+}
+
+impl<W: Write> Y4mSink<W> {
+
+	/// Returns a new sink writing to the given writer a stream of 30 frames per second in 4:2:0
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			size: None,
+			frame_rate: (30, 1),
+			subsampling: ChromaSubsampling::default(),
+			finished: false,
+		}
+	}
+
+	/// Sets the frames per second as a fraction, like 30000/1001 for NTSC. Zero values are
+	/// handled as one.
+	pub fn with_frame_rate(mut self, numerator: u32, denominator: u32) -> Self {
+		self.frame_rate = (numerator.max(1), denominator.max(1));
+		self
+	}
+
+	/// Sets the resolution of the color planes
+	pub fn with_subsampling(mut self, subsampling: ChromaSubsampling) -> Self {
+		self.subsampling = subsampling;
+		self
+	}
+
+	/// Returns the writer
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+
+	/// Writes the header of the stream
+	fn write_header(&mut self, width: u32, height: u32) -> Result<(), ImageError> {
+		let colorspace = match self.subsampling {
+			ChromaSubsampling::Yuv420 => "420jpeg",
+			ChromaSubsampling::Yuv444 => "444",
+		};
+		writeln!(self.writer, "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C{}", width, height,
+		         self.frame_rate.0, self.frame_rate.1, colorspace)?;
+		Ok(())
+	}
+
+}
+
+impl<W: Write> FrameSink for Y4mSink<W> {
+
+	fn write_frame(&mut self, frame: &Image) -> Result<(), ImageError> {
+		if self.finished {
+			return Err(finished());
+		}
+		let size = (frame.width(), frame.height());
+		match self.size {
+			None => {
+				self.write_header(size.0, size.1)?;
+				self.size = Some(size);
+			}
+			Some(first) if first != size => return Err(size_changed(first, size)),
+			_ => {}
+		}
+
+		let yuv = frame.pixels().iter().map(yuv).collect::<Vec<_>>();
+		let mut data = Vec::with_capacity(yuv.len() * 3 + 6);
+		data.extend(b"FRAME\n");
+		data.extend(yuv.iter().map(|&(y, _, _)| y));
+		match self.subsampling {
+			ChromaSubsampling::Yuv444 => {
+				data.extend(yuv.iter().map(|&(_, u, _)| u));
+				data.extend(yuv.iter().map(|&(_, _, v)| v));
+			}
+			ChromaSubsampling::Yuv420 => {
+				let (u, v) = subsample(&yuv, size.0 as usize, size.1 as usize);
+				data.extend(u);
+				data.extend(v);
+			}
+		}
+		self.writer.write_all(&data)?;
+		Ok(())
+	}
+
+	fn finish(&mut self) -> Result<(), ImageError> {
+		self.finished = true;
+		self.writer.flush()?;
+		Ok(())
+	}
+
+}
+
+/// Converts the color to BT.601 YUV in limited range
+fn yuv(color: &Color) -> (u8, u8, u8) {
+	let (r, g, b) = (color.r as i32, color.g as i32, color.b as i32);
+	let y = ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16;
+	let u = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+	let v = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+	(y as u8, u as u8, v as u8)
+}
+
+/// Returns the color planes with half the width and height, averaging each block of 2x2 pixels.
+/// The blocks of the last column and row of images with odd sizes only average their pixels.
+fn subsample(yuv: &[(u8, u8, u8)], width: usize, height: usize) -> (Vec<u8>, Vec<u8>) {
+	let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+	let mut u = Vec::with_capacity(half_width * half_height);
+	let mut v = Vec::with_capacity(half_width * half_height);
+	for y in 0..half_height {
+		for x in 0..half_width {
+			let block = (y * 2..(y * 2 + 2).min(height))
+				.flat_map(|y| (x * 2..(x * 2 + 2).min(width)).map(move |x| y * width + x))
+				.map(|i| yuv[i])
+				.collect::<Vec<_>>();
+			let average = |channel: fn(&(u8, u8, u8)) -> u8| {
+				let sum = block.iter().map(|pixel| channel(pixel) as usize).sum::<usize>();
+				((sum + block.len() / 2) / block.len()) as u8
+			};
+			u.push(average(|pixel| pixel.1));
+			v.push(average(|pixel| pixel.2));
+		}
+	}
+	(u, v)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::canvas::Canvas;
+	use crate::canvas::headless::HeadlessCanvas;
+	use crate::color::{Color, palette};
+	use crate::image::Image;
+	use crate::image::image_error::ImageError;
+	use crate::record::{ChromaSubsampling, FrameSink, Recorder, Y4mSink};
+	use crate::record::y4m::yuv;
+
+	#[test]
+	fn convert_to_yuv() {
+		assert_eq!((16, 128, 128), yuv(&palette::BLACK));
+		assert_eq!((235, 128, 128), yuv(&palette::WHITE));
+		assert_eq!((82, 90, 240), yuv(&palette::RED));
+		assert_eq!((41, 240, 110), yuv(&palette::BLUE));
+	}
+
+	#[test]
+	fn write_444_stream() {
+		let mut sink = Y4mSink::new(Vec::new()).with_subsampling(ChromaSubsampling::Yuv444);
+		sink.write_frame(&Image::from_pixels(2, 1, vec![palette::RED, palette::WHITE]).unwrap()).unwrap();
+		sink.write_frame(&Image::filled(2, 1, palette::BLACK)).unwrap();
+		sink.finish().unwrap();
+		let mut expected = b"YUV4MPEG2 W2 H1 F30:1 Ip A1:1 C444\n".to_vec();
+		expected.extend(b"FRAME\n\x52\xeb\x5a\x80\xf0\x80");
+		expected.extend(b"FRAME\n\x10\x10\x80\x80\x80\x80");
+		assert_eq!(expected, sink.into_inner());
+	}
+
+	#[test]
+	fn write_420_stream() {
+		let mut canvas = HeadlessCanvas::new(3, 2);
+		canvas.draw_line((0, 0), (0, 1), palette::RED);
+		let sink = Y4mSink::new(Vec::new()).with_frame_rate(25, 0);
+		let mut recorder = Recorder::new(canvas, sink);
+		recorder.render().unwrap();
+		let (_, sink) = recorder.finish().unwrap();
+
+		let mut expected = b"YUV4MPEG2 W3 H2 F25:1 Ip A1:1 C420jpeg\n".to_vec();
+		expected.extend(b"FRAME\n");
+		expected.extend([82, 16, 16, 82, 16, 16]);
+		expected.extend([109, 128, 184, 128]);
+		assert_eq!(expected, sink.into_inner());
+	}
+
+	#[test]
+	fn reject_resized_frames() {
+		let mut sink = Y4mSink::new(Vec::new());
+		sink.write_frame(&Image::filled(2, 2, palette::RED)).unwrap();
+		let resized = sink.write_frame(&Image::filled(4, 2, Color { r: 1, g: 2, b: 3, a: 255 }));
+		assert!(matches!(resized, Err(ImageError::FrameSize((2, 2), (4, 2)))));
+	}
+
+}
@@ -0,0 +1,370 @@
+//! Drawing scripts written in JSON or RON, to author scenes without writing Rust. They are
+//! rendered from the command line with the `ferrux-render` binary.
+//!
+//! A script sets the size and background of the canvas and lists the drawing instructions, with
+//! the colors written in any of the CSS syntaxes, see [Color::parse]:
+//! ```json
+//! {
+//!   "width": 64,
+//!   "height": 64,
+//!   "background": "white",
+//!   "instructions": [
+//!     { "fill_triangle": { "points": [[32, 0], [0, 63], [63, 63]], "color": "#ff0000" } },
+//!     { "text": { "position": [2, 2], "text": "Hi", "color": "rgb(0 0 0 / 50%)" } }
+//!   ]
+//! }
+//! ```
+//!
+//! The same script in RON:
+//! ```ron
+//! (
+//!   width: 64,
+//!   height: 64,
+//!   background: "white",
+//!   instructions: [
+//!     fill_triangle(points: ((32, 0), (0, 63), (63, 63)), color: "#ff0000"),
+//!     text(position: (2, 2), text: "Hi", color: "rgb(0 0 0 / 50%)"),
+//!   ],
+//! )
+//! ```
+pub use script_error::ScriptError;
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::canvas::headless::HeadlessCanvas;
+use crate::canvas::{Canvas, Point, RasterCanvas};
+use crate::color::{Color, palette};
+use crate::image::Image;
+
+pub mod script_error;
+
+/// Formats of the script files
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScriptFormat {
+	/// JavaScript Object Notation
+	Json,
+	/// Rusty Object Notation
+	Ron,
+}
+
+impl ScriptFormat {
+
+	/// Returns the format matching the extension of a file name, ignoring the case
+	pub fn from_extension(extension: &str) -> Option<ScriptFormat> {
+		match extension.to_ascii_lowercase().as_str() {
+			"json" => Some(ScriptFormat::Json),
+			"ron" => Some(ScriptFormat::Ron),
+			_ => None,
+		}
+	}
+
+	/// Returns the format of the file, by its extension
+	fn from_path(path: &Path) -> Result<ScriptFormat, ScriptError> {
+		path.extension()
+			.and_then(|extension| extension.to_str())
+			.and_then(ScriptFormat::from_extension)
+			.ok_or(ScriptError::UnknownFormat)
+	}
+
+}
+
+/// Drawing instruction of a [Script], matching a method of [Canvas]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Instruction {
+	/// [Canvas::draw_pixel]
+	Pixel {
+		x: u32,
+		y: u32,
+		#[serde(with = "css")]
+		color: Color,
+	},
+	/// [Canvas::draw_line]
+	Line {
+		start: Point,
+		end: Point,
+		#[serde(with = "css")]
+		color: Color,
+	},
+	/// [Canvas::draw_triangle]
+	Triangle {
+		points: [Point; 3],
+		#[serde(with = "css")]
+		color: Color,
+	},
+	/// [Canvas::fill_triangle]
+	FillTriangle {
+		points: [Point; 3],
+		#[serde(with = "css")]
+		color: Color,
+	},
+	/// [Canvas::draw_text], with the built-in font
+	Text {
+		position: Point,
+		text: String,
+		#[serde(with = "css")]
+		color: Color,
+	},
+	/// [Canvas::draw_image] of an image file. Relative paths start in the folder of the script.
+	Image {
+		dest: Point,
+		path: PathBuf,
+	},
+}
+
+/// Script of drawing instructions, readable and writable as JSON or RON. The colors are read in
+/// any of the CSS syntaxes, see [Color::parse], and written as `#rrggbbaa`.
+///
+/// # Example
+/// ```rust
+/// # use ferrux_canvas::color::palette;
+/// # use ferrux_canvas::script::{Script, ScriptFormat};
+/// let script = Script::parse(r#"{
+///   "width": 8, "height": 8,
+///   "instructions": [{ "line": { "start": [0, 0], "end": [7, 7], "color": "red" } }]
+/// }"#, ScriptFormat::Json).unwrap();
+/// let image = script.render().unwrap();
+/// assert_eq!(Some(&palette::RED), image.pixel(4, 4));
+/// assert_eq!(Some(&palette::BLACK), image.pixel(4, 0));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+	/// Width of the canvas
+	pub width: u32,
+	/// Height of the canvas
+	pub height: u32,
+	/// Color of the canvas before drawing the instructions, black if it's not written
+	#[serde(default = "black", with = "css")]
+	pub background: Color,
+	/// Drawing instructions, in order
+	#[serde(default)]
+	pub instructions: Vec<Instruction>,
+}
+
+impl Script {
+
+	/// Returns an empty script with a black background
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			background: palette::BLACK,
+			instructions: Vec::new(),
+		}
+	}
+
+	/// Reads a script file, detecting its format by the extension. The relative paths of the
+	/// images are resolved from the folder of the script.
+	///
+	/// # Errors
+	/// * [ScriptError::Io] if the file can't be read
+	/// * [ScriptError::UnknownFormat] if the extension isn't `json` or `ron`
+	/// * [ScriptError::Syntax] if the script is invalid
+	///
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ScriptError> {
+		let path = path.as_ref();
+		let format = ScriptFormat::from_path(path)?;
+		let mut script = Self::parse(&std::fs::read_to_string(path)?, format)?;
+		let folder = path.parent().unwrap_or(Path::new(""));
+		for instruction in script.instructions.iter_mut() {
+			if let Instruction::Image { path, .. } = instruction {
+				*path = folder.join(&*path);
+			}
+		}
+		Ok(script)
+	}
+
+	/// Parses a script in the specified format
+	///
+	/// # Errors
+	/// [ScriptError::Syntax] if the script is invalid, with the position of the error
+	///
+	pub fn parse(text: &str, format: ScriptFormat) -> Result<Self, ScriptError> {
+		match format {
+			ScriptFormat::Json => serde_json::from_str(text).map_err(|e| ScriptError::Syntax(e.to_string())),
+			ScriptFormat::Ron => ron::from_str(text).map_err(|e| ScriptError::Syntax(e.to_string())),
+		}
+	}
+
+	/// Writes the script to a file, in the format of its extension
+	///
+	/// # Errors
+	/// * [ScriptError::UnknownFormat] if the extension isn't `json` or `ron`
+	/// * [ScriptError::Io] if the file can't be written
+	///
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ScriptError> {
+		let format = ScriptFormat::from_path(path.as_ref())?;
+		std::fs::write(path, self.serialize(format)?)?;
+		Ok(())
+	}
+
+	/// Returns the script written in the specified format, indented to be easy to edit
+	///
+	/// # Errors
+	/// [ScriptError::Serialization] if the script can't be written in the format
+	///
+	pub fn serialize(&self, format: ScriptFormat) -> Result<String, ScriptError> {
+		match format {
+			ScriptFormat::Json => serde_json::to_string_pretty(self)
+				.map_err(|e| ScriptError::Serialization(e.to_string())),
+			ScriptFormat::Ron => ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+				.map_err(|e| ScriptError::Serialization(e.to_string())),
+		}
+	}
+
+	/// Draws the instructions on the canvas, without the background
+	///
+	/// # Errors
+	/// [ScriptError::Image] if an image can't be loaded, which stops the drawing
+	///
+	pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), ScriptError> {
+		for instruction in &self.instructions {
+			match instruction {
+				Instruction::Pixel { x, y, color } => canvas.draw_pixel(*x, *y, *color),
+				Instruction::Line { start, end, color } => canvas.draw_line(*start, *end, *color),
+				Instruction::Triangle { points: [a, b, c], color } => canvas.draw_triangle(*a, *b, *c, *color),
+				Instruction::FillTriangle { points: [a, b, c], color } => canvas.fill_triangle(*a, *b, *c, *color),
+				Instruction::Text { position, text, color } => canvas.draw_text(*position, text, *color),
+				Instruction::Image { dest, path } => canvas.draw_image(*dest, &Image::open(path)?),
+			}
+		}
+		Ok(())
+	}
+
+	/// Draws the script on a new headless canvas with its size and background, returning the
+	/// resulting image. The rendering is deterministic, the same script always gives the same image.
+	///
+	/// # Errors
+	/// * [ScriptError::Size] if the canvas has more than [Image::MAX_PIXELS] pixels
+	/// * [ScriptError::Image] if an image can't be loaded
+	///
+	pub fn render(&self) -> Result<Image, ScriptError> {
+		let background = Image::try_filled(self.width, self.height, self.background)
+			.ok_or(ScriptError::Size(self.width, self.height))?;
+		let mut canvas = HeadlessCanvas::from_image(&background);
+		self.draw(&mut canvas)?;
+		Ok(canvas.frame().clone())
+	}
+
+}
+
+fn black() -> Color {
+	palette::BLACK
+}
+
+/// Serialization of the colors, read as CSS colors and written as `#rrggbbaa`
+mod css {
+	use serde::{Deserialize, Deserializer, Serializer};
+	use serde::de::Error;
+	use crate::color::Color;
+
+	pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}{:02x}", color.r, color.g, color.b, color.a))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+		let text = String::deserialize(deserializer)?;
+		Color::parse(&text).map_err(D::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+	use crate::color::{Color, palette};
+	use crate::image::Image;
+	use crate::script::{Instruction, Script, ScriptError, ScriptFormat};
+
+	fn script() -> Script {
+		let mut script = Script::new(8, 6);
+		script.background = palette::WHITE;
+		script.instructions = vec![
+			Instruction::FillTriangle { points: [(0, 0), (7, 0), (0, 5)], color: palette::BLUE },
+			Instruction::Line { start: (0, 5), end: (7, 5), color: Color { r: 1, g: 2, b: 3, a: 4 } },
+			Instruction::Pixel { x: 7, y: 0, color: palette::RED },
+			Instruction::Triangle { points: [(4, 2), (6, 2), (6, 4)], color: palette::GREEN },
+			Instruction::Text { position: (0, 0), text: "x".to_owned(), color: palette::RED },
+		];
+		script
+	}
+
+	#[test]
+	fn parse_json() {
+		let json = r##"{
+			"width": 8, "height": 6, "background": "white",
+			"instructions": [
+				{ "fill_triangle": { "points": [[0, 0], [7, 0], [0, 5]], "color": "#00f" } },
+				{ "line": { "start": [0, 5], "end": [7, 5], "color": "#01020304" } },
+				{ "pixel": { "x": 7, "y": 0, "color": "rgb(255, 0, 0)" } },
+				{ "triangle": { "points": [[4, 2], [6, 2], [6, 4]], "color": "lime" } },
+				{ "text": { "position": [0, 0], "text": "x", "color": "hsl(0 100% 50%)" } }
+			]
+		}"##;
+		assert_eq!(script(), Script::parse(json, ScriptFormat::Json).unwrap());
+		let empty = Script::parse(r#"{ "width": 2, "height": 2 }"#, ScriptFormat::Json).unwrap();
+		assert_eq!(Script::new(2, 2), empty);
+	}
+
+	#[test]
+	fn parse_ron() {
+		let ron = r##"(
+			width: 8, height: 6, background: "#ffffffff",
+			instructions: [
+				fill_triangle(points: ((0, 0), (7, 0), (0, 5)), color: "blue"),
+				line(start: (0, 5), end: (7, 5), color: "rgba(1, 2, 3, 1.6%)"),
+				pixel(x: 7, y: 0, color: "#f00"),
+				triangle(points: ((4, 2), (6, 2), (6, 4)), color: "#00ff00"),
+				text(position: (0, 0), text: "x", color: "red"),
+			],
+		)"##;
+		assert_eq!(script(), Script::parse(ron, ScriptFormat::Ron).unwrap());
+	}
+
+	#[test]
+	fn serialize_and_parse() {
+		for format in [ScriptFormat::Json, ScriptFormat::Ron] {
+			let text = script().serialize(format).unwrap();
+			assert_eq!(script(), Script::parse(&text, format).unwrap());
+		}
+	}
+
+	#[test]
+	fn invalid_scripts() {
+		let invalid_color = r#"{ "width": 2, "height": 2, "background": "whiteish" }"#;
+		assert!(matches!(Script::parse(invalid_color, ScriptFormat::Json), Err(ScriptError::Syntax(_))));
+		let unknown = r#"(width: 2, height: 2, instructions: [circle(center: (1, 1))])"#;
+		match Script::parse(unknown, ScriptFormat::Ron) {
+			Err(ScriptError::Syntax(reason)) => assert!(reason.contains("circle"), "{}", reason),
+			other => panic!("unexpected result {:?}", other),
+		}
+		assert!(matches!(Script::open("scene.toml"), Err(ScriptError::UnknownFormat)));
+	}
+
+	#[test]
+	fn render_script() {
+		let image = script().render().unwrap();
+		assert_eq!((8, 6), (image.width(), image.height()));
+		assert_eq!(Some(&palette::RED), image.pixel(7, 0));
+		assert_eq!(Some(&palette::BLUE), image.pixel(1, 3));
+		assert_eq!(Some(&palette::WHITE), image.pixel(7, 4));
+		assert_eq!(image, script().render().unwrap());
+	}
+
+	#[test]
+	fn missing_images() {
+		let mut script = Script::new(2, 2);
+		script.instructions.push(Instruction::Image { dest: (0, 0), path: PathBuf::from("missing.png") });
+		assert!(matches!(script.render(), Err(ScriptError::Image(_))));
+		script.instructions.clear();
+		assert_eq!(Image::filled(2, 2, palette::BLACK), script.render().unwrap());
+	}
+
+	#[test]
+	fn oversized_canvas() {
+		assert!(matches!(Script::new(u32::MAX, u32::MAX).render(), Err(ScriptError::Size(u32::MAX, u32::MAX))));
+		assert!(matches!(Script::new(1 << 20, 1 << 20).render(), Err(ScriptError::Size(_, _))));
+	}
+
+}
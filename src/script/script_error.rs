@@ -0,0 +1,79 @@
+//! Throwable errors of the drawing scripts
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use crate::image::image_error::ImageError;
+
+/// Errors than can occur reading, writing or drawing a [`Script`]
+///
+/// [`Script`]: crate::script::Script
+pub enum ScriptError {
+	/// The script file couldn't be read or written
+	Io(std::io::Error),
+	/// The extension of the script file doesn't match any of the supported formats
+	UnknownFormat,
+	/// The script is not valid. Contains the reason of the error, with its position in the file.
+	Syntax(String),
+	/// The script couldn't be serialized. Contains the reason of the error.
+	Serialization(String),
+	/// An image of the script couldn't be loaded
+	Image(ImageError),
+	/// The canvas of the script is too big to be rendered. Contains its width and height.
+	Size(u32, u32),
+}
+
+impl ScriptError {
+	fn message(&self) -> String {
+		match self {
+			Self::Io(error) => format!("The script could not be read or written: {}", error),
+			Self::UnknownFormat => "The script format is not supported. It should be JSON or RON".to_owned(),
+			Self::Syntax(reason) => format!("The script is invalid: {}", reason),
+			Self::Serialization(reason) => format!("The script could not be serialized: {}", reason),
+			Self::Image(error) => format!("An image of the script could not be loaded: {}", error),
+			Self::Size(width, height) => format!("The canvas of {}x{} pixels is too big to be rendered", width, height),
+		}
+	}
+}
+
+impl Error for ScriptError {}
+
+impl Debug for ScriptError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl Display for ScriptError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl From<std::io::Error> for ScriptError {
+	fn from(error: std::io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+impl From<ImageError> for ScriptError {
+	fn from(error: ImageError) -> Self {
+		Self::Image(error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::script::script_error::ScriptError;
+
+	#[test]
+	fn test_send() {
+		fn assert_send<T: Send>() {}
+		assert_send::<ScriptError>();
+	}
+
+	#[test]
+	fn test_sync() {
+		fn assert_sync<T: Sync>() {}
+		assert_sync::<ScriptError>();
+	}
+
+}